@@ -1,6 +1,7 @@
 //! Run a simple Attractor pipeline via the compiled graph.
 
-use streamweave_attractor::{RunOptions, dot_parser, run_compiled_graph};
+use streamweave_attractor::lint::LintRegistry;
+use streamweave_attractor::{HandlerRegistry, RunOptions, dot_parser, run_compiled_graph};
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
@@ -28,6 +29,8 @@ async fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
       agent_cmd: None,
       stage_dir: None,
       execution_log_path: None,
+      handlers: HandlerRegistry::default(),
+      lints: LintRegistry::default(),
     },
   )
   .await?;
@@ -1,12 +1,12 @@
 //! Agent invocation: run the agent command (agent_cmd option) with prompt as stdin, read outcome.json.
-//! Shared by runner and CodergenNode.
+//! Shared by runner and the codergen handler.
 
-use crate::types::NodeOutcome;
+use crate::types::{NodeOutcome, OutcomeStatus};
 use std::collections::HashMap;
 use std::fs;
-use std::io::Write;
 use std::path::Path;
-use std::process::Command;
+use tokio::io::AsyncWriteExt;
+use tokio::process::Command;
 use tracing::instrument;
 
 /// Resolves the stage directory path (defaults to DEFAULT_STAGE_DIR when None).
@@ -16,11 +16,30 @@ fn stage_path(stage_dir: Option<&Path>) -> std::path::PathBuf {
     .unwrap_or_else(|| std::path::PathBuf::from(crate::DEFAULT_STAGE_DIR))
 }
 
-/// Reads outcome.json and returns (outcome from file if present, context_updates).
-/// outcome: None = no file or no "outcome" field; Some(true) = success; Some(false) = fail/error.
-pub(crate) fn read_outcome_file(
-  stage_dir: Option<&Path>,
-) -> Option<(Option<bool>, HashMap<String, String>)> {
+/// Fields read from the agent's outcome.json.
+#[derive(Debug)]
+pub(crate) struct OutcomeFile {
+  /// None = no "outcome" field; "fail"/"error" = Error, "retry" = Retry, anything else = Success.
+  pub outcome: Option<OutcomeStatus>,
+  /// String values of the "context_updates" object.
+  pub context_updates: HashMap<String, String>,
+  /// "preferred_label": label of the outgoing edge the agent wants to take.
+  pub preferred_label: Option<String>,
+  /// "suggested_next_ids": node ids the agent suggests going to next, in order.
+  pub suggested_next_ids: Vec<String>,
+}
+
+impl OutcomeFile {
+  /// Copies context updates and routing hints onto `outcome`.
+  fn apply_to(self, outcome: &mut NodeOutcome) {
+    outcome.context_updates = self.context_updates;
+    outcome.preferred_label = self.preferred_label;
+    outcome.suggested_next_ids = self.suggested_next_ids;
+  }
+}
+
+/// Reads outcome.json from the stage directory. None when the file is missing or invalid JSON.
+pub(crate) fn read_outcome_file(stage_dir: Option<&Path>) -> Option<OutcomeFile> {
   let path = stage_path(stage_dir).join("outcome.json");
   if !path.exists() {
     return None;
@@ -28,9 +47,10 @@ pub(crate) fn read_outcome_file(
   let s = fs::read_to_string(&path).ok()?;
   let v: serde_json::Value = serde_json::from_str(&s).ok()?;
   let outcome_str = v.get("outcome").and_then(|o| o.as_str());
-  let is_success = outcome_str.map(|s| {
-    let lower = s.trim().to_lowercase();
-    !(lower == "fail" || lower == "error")
+  let status = outcome_str.map(|s| match s.trim().to_lowercase().as_str() {
+    "fail" | "error" => OutcomeStatus::Error,
+    "retry" => OutcomeStatus::Retry,
+    _ => OutcomeStatus::Success,
   });
   let empty: serde_json::Map<String, serde_json::Value> = serde_json::Map::new();
   let obj = v
@@ -43,13 +63,34 @@ pub(crate) fn read_outcome_file(
       map.insert(k.clone(), s.to_string());
     }
   }
-  Some((is_success, map))
+  let preferred_label = v
+    .get("preferred_label")
+    .and_then(|l| l.as_str())
+    .map(|l| l.trim().to_string())
+    .filter(|l| !l.is_empty());
+  let suggested_next_ids = v
+    .get("suggested_next_ids")
+    .and_then(|ids| ids.as_array())
+    .map(|ids| {
+      ids
+        .iter()
+        .filter_map(|id| id.as_str().map(String::from))
+        .collect()
+    })
+    .unwrap_or_default();
+  Some(OutcomeFile {
+    outcome: status,
+    context_updates: map,
+    preferred_label,
+    suggested_next_ids,
+  })
 }
 
-/// Runs the agent command with prompt as stdin; returns NodeOutcome based on exit code.
-/// Used by the compiled workflow and by CodergenNode.
+/// Runs the agent command with prompt as stdin and `ATTRACTOR_STAGE_DIR` set to the stage
+/// directory (created if missing); returns NodeOutcome based on exit code. Used by the compiled
+/// workflow and by the codergen handler. Dropping the future kills the agent process.
 #[instrument(level = "trace", skip(agent_cmd, prompt, stage_dir))]
-pub(crate) fn run_agent(
+pub(crate) async fn run_agent(
   agent_cmd: &str,
   prompt: &str,
   stage_dir: Option<&std::path::Path>,
@@ -60,32 +101,39 @@ pub(crate) fn run_agent(
     None => return NodeOutcome::error("agent_cmd is empty"),
   };
 
+  let stage = stage_path(stage_dir);
+  if let Err(e) = fs::create_dir_all(&stage) {
+    return NodeOutcome::error(format!("creating stage dir {}: {}", stage.display(), e));
+  }
   match Command::new(bin)
     .args(args)
+    .env("ATTRACTOR_STAGE_DIR", &stage)
     .stdin(std::process::Stdio::piped())
     .stdout(std::process::Stdio::inherit())
     .stderr(std::process::Stdio::inherit())
+    .kill_on_drop(true)
     .spawn()
   {
     Ok(mut child) => {
       if let Some(mut stdin) = child.stdin.take() {
-        let _ = stdin.write_all(prompt.as_bytes());
-        let _ = stdin.write_all(b"\n");
+        let _ = stdin.write_all(prompt.as_bytes()).await;
+        let _ = stdin.write_all(b"\n").await;
       }
-      match child.wait() {
+      match child.wait().await {
         Ok(status) => {
           let from_file = read_outcome_file(stage_dir);
-          let use_file_fail =
-            status.success() && from_file.as_ref().is_some_and(|(o, _)| o == &Some(false));
-          if use_file_fail {
-            let (_, updates) = from_file.unwrap();
-            let mut outcome = NodeOutcome::error("agent reported outcome=fail in outcome.json");
-            outcome.context_updates = updates;
-            outcome
-          } else if status.success() {
-            let mut outcome = NodeOutcome::success("agent completed");
-            if let Some((_, updates)) = from_file {
-              outcome.context_updates = updates;
+          if status.success() {
+            let mut outcome = match from_file.as_ref().and_then(|f| f.outcome) {
+              Some(OutcomeStatus::Error) => {
+                NodeOutcome::error("agent reported outcome=fail in outcome.json")
+              }
+              Some(OutcomeStatus::Retry) => {
+                NodeOutcome::retry("agent reported outcome=retry in outcome.json")
+              }
+              _ => NodeOutcome::success("agent completed"),
+            };
+            if let Some(file) = from_file {
+              file.apply_to(&mut outcome);
             }
             outcome
           } else {
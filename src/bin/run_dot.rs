@@ -1,4 +1,4 @@
-//! CLI: Run an Attractor pipeline from a .dot file.
+//! CLI: Run an Attractor pipeline from a .dot file (or .json / .yaml, see `pipeline_file`).
 //!
 //! Uses the compiled pipeline: parse DOT → validate → run (real exec + agent when ATTRACTOR_AGENT_CMD set).
 //! Supports fix-and-retry cycles via the runner loop.
@@ -6,27 +6,40 @@
 //! Usage: `run_dot [OPTIONS] <path-to-dot-file>`
 //! Example: run_dot examples/workflows/pre-push.dot
 //!
+//! `run_dot fmt [--check] <path>...` rewrites pipeline files in canonical form (see `dot_format`).
+//! With --check, files are left alone and it exits 1 if any would change or cannot be formatted.
+//!
+//! `run_dot validate [--format json] [--deny-warnings] <path>...` checks pipeline files without
+//! running them. It exits 1 on any error, or on any warning with --deny-warnings.
+//!
 //! With --run-dir DIR, execution log is written to DIR/execution.log.json when --execution-log is used.
 //! With --resume DIR, run resumes from DIR/execution.log.json (same .dot file). Execution log is the only persisted run state.
 //!
 //! Set RUST_LOG=streamweave_attractor=trace for TRACE-level span enter/exit and events.
 
-use clap::Parser;
+use clap::{Parser, Subcommand, ValueEnum};
+use serde::Serialize;
 use std::env;
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::process;
+use std::sync::Arc;
+use streamweave_attractor::handler::WaitHumanHandler;
+use streamweave_attractor::interviewer::{AutoApproveInterviewer, FileDropInterviewer};
+use streamweave_attractor::lint::{LintRegistry, RuleLevel};
 use streamweave_attractor::{
-  DEFAULT_STAGE_DIR, RunOptions, dot_parser, execution_log_io, run_compiled_graph,
+  DEFAULT_STAGE_DIR, Diagnostic, HandlerRegistry, PipelineFormat, RunOptions, Severity, dot_format,
+  dot_writer, execution_log_io, load_pipeline, pipeline_file, run_compiled_graph,
 };
 use tracing::info;
 use tracing_subscriber::{EnvFilter, fmt::format::FmtSpan};
 
-/// Run an Attractor pipeline from a .dot file.
+/// Run an Attractor pipeline from a .dot file (or .json / .yaml).
 ///
 /// Environment variables (see --help for ATTRACTOR_AGENT_CMD and ATTRACTOR_STAGE_DIR).
 #[derive(Parser, Debug)]
 #[command(name = "run_dot")]
+#[command(args_conflicts_with_subcommands = true, subcommand_negates_reqs = true)]
 #[command(
   after_help = r#"Environment variables (override --agent-cmd and --stage-dir when set):
   ATTRACTOR_AGENT_CMD   Command for agent/codergen nodes (e.g. cursor-agent). When set, agent steps
@@ -35,6 +48,8 @@ use tracing_subscriber::{EnvFilter, fmt::format::FmtSpan};
   ATTRACTOR_EXECUTION_LOG  Unset=off. 1 or true=write execution log to <stage_dir>/execution.log.json.
                            Any other value=path to execution log file. Overridden by --execution-log.
 
+Human gates (wait.human) ask on the console unless --auto-approve or --answers-dir is given.
+
 Run state (execution log only):
   --run-dir DIR   Run directory; with --execution-log, log is written to DIR/execution.log.json.
   --resume DIR    Resume from DIR/execution.log.json (same .dot file).
@@ -44,7 +59,14 @@ Examples:
   run_dot --run-dir .attractor_run examples/workflows/pre-push.dot
   run_dot --resume .attractor_run examples/workflows/pre-push.dot
   run_dot --stage-dir /tmp/stage examples/workflows/pre-push.dot
-  run_dot --execution-log /tmp/execution.log.json examples/workflows/pre-push.dot"#
+  run_dot --execution-log /tmp/execution.log.json examples/workflows/pre-push.dot
+  run_dot --answers-dir .attractor/gates pipeline.dot
+  run_dot pipeline.yaml
+  run_dot fmt examples/workflows/*.dot
+  run_dot fmt --check examples/workflows/*.dot
+  run_dot validate examples/workflows/*.dot
+  run_dot validate --format json --deny-warnings pipeline.yaml
+  run_dot validate --allow prompt_on_llm_nodes --deny goal_gate_has_retry pipeline.dot"#
 )]
 struct Args {
   /// Command for agent/codergen nodes (e.g. cursor-agent). Overridden by ATTRACTOR_AGENT_CMD if set.
@@ -67,9 +89,197 @@ struct Args {
   #[arg(long = "execution-log", value_name = "PATH", num_args = 0..=1)]
   execution_log: Option<Option<PathBuf>>,
 
-  /// Path to the .dot workflow file
-  #[arg(value_name = "path-to-dot-file")]
-  dot_path: PathBuf,
+  /// Answer every human gate (wait.human) with its default choice, else its first edge.
+  #[arg(long, conflicts_with = "answers_dir")]
+  auto_approve: bool,
+
+  /// Ask human gates through files: write DIR/<node>.question.json and wait for DIR/<node>.answer.
+  #[arg(long, value_name = "DIR")]
+  answers_dir: Option<PathBuf>,
+
+  /// Path to the workflow file (.dot, .json, .yaml or .yml)
+  #[arg(value_name = "path-to-dot-file", required = true)]
+  dot_path: Option<PathBuf>,
+
+  #[command(subcommand)]
+  command: Option<Command>,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+  /// Rewrite pipeline files in canonical form, keeping each file's format.
+  Fmt {
+    /// Do not write; list files that would change and exit 1 if there are any.
+    #[arg(long)]
+    check: bool,
+
+    /// Pipeline files to format
+    #[arg(value_name = "path-to-dot-file", required = true)]
+    paths: Vec<PathBuf>,
+  },
+  /// Check pipeline files against the validation rules without running them.
+  Validate {
+    /// Output format
+    #[arg(long, value_enum, default_value_t = OutputFormat::Text)]
+    format: OutputFormat,
+
+    /// Exit 1 on warnings as well as errors.
+    #[arg(long)]
+    deny_warnings: bool,
+
+    /// Do not report RULE (repeatable)
+    #[arg(long = "allow", value_name = "RULE")]
+    allow: Vec<String>,
+
+    /// Report RULE as a warning (repeatable)
+    #[arg(long = "warn", value_name = "RULE")]
+    warn: Vec<String>,
+
+    /// Report RULE as an error (repeatable)
+    #[arg(long = "deny", value_name = "RULE")]
+    deny: Vec<String>,
+
+    /// Pipeline files to check
+    #[arg(value_name = "path-to-dot-file", required = true)]
+    paths: Vec<PathBuf>,
+  },
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug)]
+enum OutputFormat {
+  /// `path: severity[rule]: message`, with fix hints, and a summary line
+  Text,
+  /// A JSON array of diagnostics, each with its `file`
+  Json,
+}
+
+/// A diagnostic and the file it was found in, as written by `validate --format json`.
+#[derive(Serialize)]
+struct FileDiagnostic<'a> {
+  file: String,
+  #[serde(flatten)]
+  diagnostic: &'a Diagnostic,
+}
+
+/// Builds the lint registry for `validate` from the `--allow`/`--warn`/`--deny` flags.
+fn lint_registry(
+  allow: &[String],
+  warn: &[String],
+  deny: &[String],
+) -> Result<LintRegistry, String> {
+  let mut lints = LintRegistry::default();
+  for (rules, level) in [
+    (allow, RuleLevel::Allow),
+    (warn, RuleLevel::Warn),
+    (deny, RuleLevel::Deny),
+  ] {
+    for rule in rules {
+      lints.set_level(rule, level)?;
+    }
+  }
+  Ok(lints)
+}
+
+/// Validates each file and prints the diagnostics. Returns false if there is an error, or a
+/// warning with `deny_warnings`.
+fn validate_files(
+  paths: &[PathBuf],
+  lints: &LintRegistry,
+  format: OutputFormat,
+  deny_warnings: bool,
+) -> bool {
+  let results: Vec<(&PathBuf, Vec<Diagnostic>)> = paths
+    .iter()
+    .map(|path| {
+      let diagnostics = match load_pipeline(path) {
+        Ok(graph) => lints.validate(&graph),
+        Err(e) => vec![Diagnostic::new("parse", Severity::Error, e)],
+      };
+      (path, diagnostics)
+    })
+    .collect();
+  let all = || results.iter().flat_map(|(_, ds)| ds);
+  let errors = all().filter(|d| d.severity == Severity::Error).count();
+  let warnings = all().filter(|d| d.severity == Severity::Warning).count();
+
+  match format {
+    OutputFormat::Text => {
+      for (path, diagnostics) in &results {
+        for d in diagnostics {
+          println!("{}: {}", path.display(), d);
+          if let Some(fix) = &d.fix {
+            println!("  fix: {}", fix);
+          }
+        }
+      }
+      println!(
+        "{} file(s) checked: {} error(s), {} warning(s)",
+        results.len(),
+        errors,
+        warnings
+      );
+    }
+    OutputFormat::Json => {
+      let out: Vec<FileDiagnostic> = results
+        .iter()
+        .flat_map(|(path, ds)| {
+          ds.iter().map(|d| FileDiagnostic {
+            file: path.display().to_string(),
+            diagnostic: d,
+          })
+        })
+        .collect();
+      println!(
+        "{}",
+        serde_json::to_string_pretty(&out).expect("diagnostics serialize")
+      );
+    }
+  }
+  errors == 0 && (!deny_warnings || warnings == 0)
+}
+
+/// Formats each file in place (or only reports, with `check`). Returns false if any file
+/// failed to read or parse, or would change under `check`.
+fn fmt_files(paths: &[PathBuf], check: bool) -> bool {
+  let mut ok = true;
+  for path in paths {
+    match fmt_file(path, check) {
+      Ok(false) => {}
+      Ok(true) if check => {
+        println!("would reformat {}", path.display());
+        ok = false;
+      }
+      Ok(true) => println!("reformatted {}", path.display()),
+      Err(e) => {
+        eprintln!("Error formatting {}: {}", path.display(), e);
+        ok = false;
+      }
+    }
+  }
+  ok
+}
+
+/// Formats one file. Returns whether its content differs from the canonical form. A file is
+/// never rewritten into something that parses to a different pipeline.
+fn fmt_file(path: &Path, check: bool) -> Result<bool, String> {
+  let source = fs::read_to_string(path).map_err(|e| e.to_string())?;
+  let format = PipelineFormat::from_path(path);
+  let graph = pipeline_file::parse_pipeline(&source, format)?;
+  let formatted = match format {
+    PipelineFormat::Dot => dot_format::format_dot(&source).map_err(|e| e.to_string())?,
+    _ => pipeline_file::write_pipeline(&graph, format)?,
+  };
+  let reformatted = pipeline_file::parse_pipeline(&formatted, format)?;
+  if dot_writer::write_dot(&reformatted) != dot_writer::write_dot(&graph) {
+    return Err("formatting would change the pipeline, so the file was left as is".to_string());
+  }
+  if formatted == source {
+    return Ok(false);
+  }
+  if !check {
+    fs::write(path, &formatted).map_err(|e| e.to_string())?;
+  }
+  Ok(true)
 }
 
 #[tokio::main]
@@ -78,10 +288,36 @@ async fn main() {
   tracing_subscriber::fmt()
     .with_env_filter(EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("info")))
     .with_span_events(FmtSpan::ENTER | FmtSpan::EXIT)
+    // Logs go to stderr so stdout carries only results (e.g. `validate --format json`).
+    .with_writer(std::io::stderr)
     .init();
 
   info!("run_dot starting");
   let args = Args::parse();
+  match &args.command {
+    Some(Command::Fmt { check, paths }) => {
+      process::exit(if fmt_files(paths, *check) { 0 } else { 1 });
+    }
+    Some(Command::Validate {
+      format,
+      deny_warnings,
+      allow,
+      warn,
+      deny,
+      paths,
+    }) => {
+      let lints = lint_registry(allow, warn, deny).unwrap_or_else(|e| {
+        eprintln!("Error: {}", e);
+        process::exit(1);
+      });
+      process::exit(if validate_files(paths, &lints, *format, *deny_warnings) {
+        0
+      } else {
+        1
+      });
+    }
+    None => {}
+  }
 
   // Env vars override flags. These are the values used by the program (not read from env again).
   let agent_cmd = env::var("ATTRACTOR_AGENT_CMD")
@@ -114,7 +350,10 @@ async fn main() {
 
   info!(agent_cmd = ?agent_cmd, stage_dir = %stage_dir.display(), run_dir = %run_dir.display(), resume = args.resume.is_some(), execution_log_path = ?execution_log_path, "options (env or flags)");
 
-  let path = &args.dot_path;
+  let path = args
+    .dot_path
+    .as_deref()
+    .expect("clap requires path-to-dot-file without a subcommand");
   let dot = match fs::read_to_string(path) {
     Ok(s) => s,
     Err(e) => {
@@ -123,10 +362,11 @@ async fn main() {
     }
   };
 
-  let ast = match dot_parser::parse_dot(&dot) {
+  let format = PipelineFormat::from_path(path);
+  let ast = match pipeline_file::parse_pipeline(&dot, format) {
     Ok(a) => a,
     Err(e) => {
-      eprintln!("Error parsing DOT: {}", e);
+      eprintln!("Error parsing {}: {}", format, e);
       process::exit(1);
     }
   };
@@ -159,6 +399,19 @@ async fn main() {
       }
     });
 
+  let mut handlers = HandlerRegistry::default();
+  if args.auto_approve {
+    handlers.register(
+      "wait.human",
+      WaitHumanHandler::new(Arc::new(AutoApproveInterviewer)),
+    );
+  } else if let Some(dir) = args.answers_dir {
+    handlers.register(
+      "wait.human",
+      WaitHumanHandler::new(Arc::new(FileDropInterviewer::new(dir))),
+    );
+  }
+
   let options = RunOptions {
     run_dir: run_dir_for_options,
     resume_state,
@@ -166,6 +419,8 @@ async fn main() {
     agent_cmd,
     stage_dir: Some(stage_dir),
     execution_log_path,
+    handlers,
+    lints: LintRegistry::default(),
   };
 
   let r = match run_compiled_graph(&ast, options).await {
@@ -1,63 +1,94 @@
 //! Compile AttractorGraph (AST) to StreamWeave graph.
 //!
 //! Phase 1: Trivial start→exit with identity nodes.
-//! Phase 2: ExecNode for exec, CodergenNode for codergen, identity for start/exit.
-//! Phase 3: Direct port routing: success -> "out", error -> "error" (no router).
+//! Phase 2: HandlerNode running each node's registered handler, identity for start/exit.
+//! Phase 3: Runtime edge routing: nodes with several outgoing edges get an EdgeRouterNode
+//! that applies select_edge (spec §3.3) to each payload; a single edge is wired directly.
 //! Phase 4: Multiple edges to same (node, port) are merged via StreamWeave MergeNode.
+//! Phase 5: The exit router's first result shuts the run down, so fix-and-retry cycles
+//! (which never close their channels) still complete; unrouted handler ports end the run.
+//! Phase 6: Goal gates: with any `goal_gate` node, edges into exit pass a CheckGoalGatesNode
+//! that jumps to the gate's retry target or fails the run while a gate is unsatisfied.
+//! Phase 7: Parallel nodes run their branches inside their handler, so their ports are wired
+//! to the branches' fan-in node instead of to the branches.
 
-use crate::nodes::{CodergenNode, ExecNode, IdentityNode, OutcomeRouterNode, validate_graph};
+use crate::execution_log_io::ExecutionLogSink;
+use crate::handler::{HandlerRegistry, HandlerServices, handler_type_of};
+use crate::lint::LintRegistry;
+use crate::nodes::edge_router_node::NO_EDGE_PORT;
+use crate::nodes::run_shutdown::RunShutdown;
+use crate::nodes::step_recorder::StepRecorder;
+use crate::nodes::{
+  CheckGoalGatesNode, EdgeRouterNode, HandlerNode, IdentityNode, OutcomeRouterNode,
+};
+use crate::parallel::{PARALLEL, fan_in_of};
 use crate::types::AttractorGraph;
 use std::collections::HashMap;
 use std::path::Path;
+use std::sync::Arc;
 use streamweave::graph_builder::GraphBuilder;
 use streamweave::node::Node;
 use streamweave::nodes::stream::MergeNode;
-use tracing::{info, instrument};
+use tracing::{info, instrument, warn};
 
-/// Returns true if the condition string matches `outcome=fail` or `outcome=error`.
-#[instrument(level = "trace")]
-fn condition_is_outcome_error(cond: Option<&str>) -> bool {
-  cond
-    .map(|c| {
-      let c = c.trim().to_lowercase();
-      c == "outcome=fail"
-        || c.starts_with("outcome=fail")
-        || c == "outcome=error"
-        || c.starts_with("outcome=error")
-    })
-    .unwrap_or(false)
+/// Options for [compile_attractor_graph]; the default compiles from the start node with the
+/// built-in handlers and lint rules, no agent command, no stage dir and no step log.
+#[derive(Clone, Default)]
+pub struct CompileOptions<'a> {
+  /// Node the graph input is connected to; the start node when None (e.g. set on resume).
+  pub entry_node_id: Option<&'a str>,
+  /// Command for agent/codergen nodes (e.g. cursor-agent).
+  pub agent_cmd: Option<&'a str>,
+  /// Directory for agent outcome.json and staging.
+  pub stage_dir: Option<&'a Path>,
+  /// If set, every node records each execution to it (execution.log.json steps).
+  pub step_log: Option<&'a ExecutionLogSink>,
+  /// Handlers for the graph's node types; [HandlerRegistry::default] has the built-in ones.
+  pub handlers: HandlerRegistry,
+  /// Rules the graph is checked with before it compiles; [LintRegistry::default] has the
+  /// built-in ones.
+  pub lints: LintRegistry,
 }
 
 /// Compiles an AttractorGraph (AST) to a StreamWeave Graph.
 ///
-/// - Start/exit: IdentityNode (pass-through)
-/// - Exec nodes: ExecNode with command (rejects exec without command per design §2.2)
-/// - Codergen/other: CodergenNode (invokes ATTRACTOR_AGENT_CMD with prompt)
+/// - Start/exit: IdentityNode (pass-through, so the run reports the outcome before exit)
+/// - Every other node: HandlerNode running the handler `options.handlers` has for its handler
+///   type (`exec` runs its command, `codergen` invokes `options.agent_cmd` with the prompt)
+/// - Nodes with several outgoing edges: EdgeRouterNode (runtime edge selection)
 ///
-/// When `entry_node_id` is `Some(id)`, the graph input is connected to that node;
+/// The graph is validated first with `options.lints`, at their levels. When they include a
+/// `type_known` rule, it is replaced by one checking handler types against `options.handlers`,
+/// so a node whose type has no handler is an error.
+///
+/// When `options.entry_node_id` is `Some(id)`, the graph input is connected to that node;
 /// when `None`, to the start node.
-#[instrument(level = "trace", skip(ast))]
+///
+/// Exec and codergen nodes rerun while they return a retryable outcome, up to their
+/// `max_retries` (else the graph's `default_max_retry`) and waiting per their `retry_policy`;
+/// the last attempt escalates to the error port.
+///
+/// When `options.step_log` is `Some`, every node records each execution to it (execution.log.json steps).
+#[instrument(level = "trace", skip(ast, options))]
 pub fn compile_attractor_graph(
   ast: &AttractorGraph,
-  entry_node_id: Option<&str>,
-  agent_cmd: Option<&str>,
-  stage_dir: Option<&Path>,
+  options: &CompileOptions<'_>,
 ) -> Result<streamweave::graph::Graph, String> {
   info!("compiling AttractorGraph to StreamWeave graph");
-  validate_graph::validate(ast)?;
-
-  // DSL rule §2.2: reject exec nodes without command
-  for (id, n) in &ast.nodes {
-    if n.handler_type.as_deref() == Some("exec") && n.command.is_none() {
-      return Err(format!("exec node '{}' requires a command attribute", id));
-    }
+  let handlers = &options.handlers;
+  let mut lints = options.lints.clone();
+  if lints.has_rule("type_known") {
+    lints.register(handlers.lint_rule());
+  }
+  for d in lints.validate_or_raise(ast)? {
+    warn!(rule = %d.rule, node = ?d.node_id, "{}", d.message);
   }
 
   let start_id = ast
     .find_start()
     .map(|n| n.id.clone())
     .ok_or("missing start node")?;
-  let entry_id = match entry_node_id {
+  let entry_id = match options.entry_node_id {
     Some(id) => {
       if !ast.nodes.contains_key(id) {
         return Err(format!("entry node '{}' is not a node in the graph", id));
@@ -71,8 +102,14 @@ pub fn compile_attractor_graph(
     .map(|n| n.id.clone())
     .ok_or("missing exit node")?;
 
-  // Trivial graph (start -> exit only): use predefined graph! pipeline
-  if ast.nodes.len() == 2
+  let graph_ast = Arc::new(ast.clone());
+  let recorder = options
+    .step_log
+    .map(|sink| StepRecorder::new(sink.clone(), Arc::clone(&graph_ast)));
+
+  // Trivial graph (start -> exit only): use predefined graph! pipeline (does not record steps)
+  if recorder.is_none()
+    && ast.nodes.len() == 2
     && ast.edges.len() == 1
     && start_id == "start"
     && exit_id == "exit"
@@ -86,43 +123,126 @@ pub fn compile_attractor_graph(
 
   // StreamWeave's dataflow execution supports cycles (one channel per edge, one task per node).
   // Include all edges including fix→exec back-edges so fix-and-retry loops run in-graph.
+  // Cycles never close their channels, so the exit router shuts the whole run down instead.
+  let shutdown = RunShutdown::new();
 
   let mut builder = GraphBuilder::new("compiled_attractor");
+  let services = HandlerServices {
+    agent_cmd: options.agent_cmd.map(String::from),
+    stage_dir: options.stage_dir.map(std::path::PathBuf::from),
+    graph: Arc::clone(&graph_ast),
+    handlers: handlers.clone(),
+    step_recorder: recorder.clone(),
+  };
 
   for (node_id, node) in &ast.nodes {
-    let sw_node: Box<dyn Node> = match node.handler_type.as_deref().unwrap_or("codergen") {
-      "start" | "exit" => Box::new(IdentityNode::new(&node.id)),
-      "exec" => {
-        let cmd = node.command.as_ref().expect("validated above").clone();
-        Box::new(ExecNode::new(&node.id, cmd))
+    let sw_node: Box<dyn Node> = match handler_type_of(node) {
+      "start" | "exit" => {
+        let mut n = IdentityNode::new(&node.id).with_shutdown(shutdown.clone());
+        if let Some(r) = &recorder {
+          n = n.with_step_recorder(r.clone());
+        }
+        Box::new(n)
       }
       _ => {
-        let prompt = node.prompt.as_deref().unwrap_or("").to_string();
-        let cmd = agent_cmd.map(String::from);
-        let dir = stage_dir.map(std::path::PathBuf::from);
-        Box::new(CodergenNode::new(&node.id, prompt, cmd, dir))
+        let handler = handlers.resolve(node)?;
+        let mut n = HandlerNode::new(node.clone(), handler, services.clone())
+          .with_shutdown(shutdown.clone())
+          .with_retries(ast.max_retries_for(node_id), ast.retry_policy_for(node_id));
+        if let Some(r) = &recorder {
+          n = n.with_step_recorder(r.clone());
+        }
+        Box::new(n)
       }
     };
     builder = builder.add_node(node_id, sw_node);
   }
 
-  let resolved: Vec<(String, String, String, String)> = ast
-    .edges
-    .iter()
-    .map(|e| {
-      let (source_port, target_port) = if condition_is_outcome_error(e.condition.as_deref()) {
-        ("error".to_string(), "in".to_string())
-      } else {
-        ("out".to_string(), "in".to_string())
+  // Route each node's payloads to its next node. A single edge is always taken (spec §3.3 falls
+  // back to any edge), so it is wired directly; several edges get an EdgeRouterNode that runs
+  // select_edge on every payload and sends the ones no edge takes to the exit router as errors.
+  // Exit feeds the exit router, which routes by outcome so the runner can wait for the first of
+  // output or error; nodes with no outgoing edge end the run there.
+  let exit_router_id = "exit_router";
+  let mut resolved: Vec<(String, String, String, String)> = vec![(
+    exit_id.clone(),
+    "out".to_string(),
+    exit_router_id.to_string(),
+    "in".to_string(),
+  )];
+  // Goal gates (spec §3.4): when any node is a goal gate, everything headed for exit first
+  // passes a CheckGoalGatesNode, which lets it through, jumps back to a retry target, or fails
+  // the run.
+  let exit_target = if ast.nodes.values().any(|n| n.goal_gate) {
+    let gate_id = "goal_gates";
+    let gate =
+      CheckGoalGatesNode::new(gate_id, Arc::clone(&graph_ast)).with_shutdown(shutdown.clone());
+    for port in gate.output_port_names() {
+      let to = match port.as_str() {
+        "pass" => exit_id.clone(),
+        "fail" => exit_router_id.to_string(),
+        _ => port["retry_".len()..].to_string(),
       };
-      (
-        e.from_node.clone(),
-        source_port,
-        e.to_node.clone(),
-        target_port,
-      )
-    })
-    .collect();
+      resolved.push((gate_id.to_string(), port.clone(), to, "in".to_string()));
+    }
+    builder = builder.add_node(gate_id, Box::new(gate));
+    gate_id.to_string()
+  } else {
+    exit_id.clone()
+  };
+  let into = |to: &str| {
+    if to == exit_id {
+      exit_target.clone()
+    } else {
+      to.to_string()
+    }
+  };
+  let mut node_ids: Vec<&String> = ast.nodes.keys().filter(|id| **id != exit_id).collect();
+  node_ids.sort();
+  for id in node_ids {
+    let ports: &[&str] = match ast.nodes[id].handler_type.as_deref() {
+      Some("start") => &["out"],
+      _ => &["out", "error"],
+    };
+    let edges = ast.outgoing_edges(id);
+    let target = match edges.as_slice() {
+      // A parallel node runs its branches itself and continues at their fan-in.
+      _ if handler_type_of(&ast.nodes[id]) == PARALLEL => {
+        into(fan_in_of(ast, id).ok_or_else(|| {
+          format!(
+            "parallel node '{}' has no parallel.fan_in node after it",
+            id
+          )
+        })?)
+      }
+      [] => exit_router_id.to_string(),
+      [e] => into(&e.to_node),
+      _ => {
+        let router_id = format!("route_{}", id);
+        let router = EdgeRouterNode::new(&router_id, id, Arc::clone(&graph_ast))
+          .with_shutdown(shutdown.clone());
+        for port in router.output_port_names() {
+          let to = if port == NO_EDGE_PORT {
+            exit_router_id.to_string()
+          } else {
+            into(port)
+          };
+          resolved.push((router_id.clone(), port.clone(), to, "in".to_string()));
+        }
+        builder = builder.add_node(&router_id, Box::new(router));
+        router_id
+      }
+    };
+    for port in ports {
+      resolved.push((
+        id.clone(),
+        port.to_string(),
+        target.clone(),
+        "in".to_string(),
+      ));
+    }
+  }
+
   let mut groups: HashMap<(String, String), Vec<(String, String)>> = HashMap::new();
   for (from, src_port, to, to_port) in &resolved {
     let key = (to.clone(), to_port.clone());
@@ -131,6 +251,10 @@ pub fn compile_attractor_graph(
       .or_default()
       .push((from.clone(), src_port.clone()));
   }
+  builder = builder.add_node(
+    exit_router_id,
+    Box::new(OutcomeRouterNode::new(exit_router_id).with_shutdown(shutdown)),
+  );
   for ((to_node, to_port), list) in &groups {
     if list.len() > 1 {
       let merge_id = format!("merge_{}_{}", to_node, to_port);
@@ -151,15 +275,6 @@ pub fn compile_attractor_graph(
     }
   }
 
-  // Route exit payload by outcome so runner can wait for first of output or error (avoids hang).
-  let exit_router_id = "exit_router";
-  builder = builder
-    .add_node(
-      exit_router_id,
-      Box::new(OutcomeRouterNode::new(exit_router_id)),
-    )
-    .connect(&exit_id, "out", exit_router_id, "in");
-
   let graph = builder
     .input::<std::sync::Arc<dyn std::any::Any + Send + Sync>>("input", &entry_id, "in", None)
     .output("output", exit_router_id, "success")
@@ -1,9 +1,11 @@
 //! Tests for the AttractorGraph → StreamWeave compiler.
 
-use crate::compiler::compile_attractor_graph;
+use crate::compiler::{CompileOptions, compile_attractor_graph};
 use crate::dot_parser::parse_dot;
+use crate::execution_log_io::ExecutionLogSink;
+use crate::lint::LintRegistry;
 use crate::runner::run_streamweave_graph;
-use crate::types::GraphPayload;
+use crate::types::{ExecutionLog, GraphPayload};
 use std::collections::HashMap;
 
 #[test]
@@ -18,7 +20,7 @@ fn compile_rejects_exec_without_command() {
     }
   "#;
   let ast = parse_dot(dot).unwrap();
-  match compile_attractor_graph(&ast, None, None, None) {
+  match compile_attractor_graph(&ast, &CompileOptions::default()) {
     Ok(_) => panic!("expected compile to fail (exec without command)"),
     Err(e) => {
       assert!(e.to_lowercase().contains("exec"));
@@ -43,9 +45,11 @@ fn compile_with_conditional_routing() {
     }
   "#;
   let ast = parse_dot(dot).unwrap();
-  let graph = compile_attractor_graph(&ast, None, None, None).unwrap();
-  // Graph built with direct out/error ports for run
+  let graph = compile_attractor_graph(&ast, &CompileOptions::default()).unwrap();
+  // run has two outgoing edges, so an edge router selects between them at runtime
   assert!(graph.name().contains("compiled"));
+  assert!(graph.find_node_by_name("route_run").is_some());
+  assert!(graph.find_node_by_name("route_fix").is_none());
 }
 
 #[test]
@@ -59,7 +63,7 @@ fn compile_trivial_start_exit() {
     }
   "#;
   let ast = parse_dot(dot).unwrap();
-  let graph = compile_attractor_graph(&ast, None, None, None).unwrap();
+  let graph = compile_attractor_graph(&ast, &CompileOptions::default()).unwrap();
   // Trivial case may be built via graph! or builder
   assert!(graph.find_node_by_name("start").is_some());
   assert!(graph.find_node_by_name("exit").is_some());
@@ -76,13 +80,91 @@ async fn run_streamweave_graph_trivial_start_exit() {
     }
   "#;
   let ast = parse_dot(dot).unwrap();
-  let graph = compile_attractor_graph(&ast, None, None, None).unwrap();
+  let graph = compile_attractor_graph(&ast, &CompileOptions::default()).unwrap();
   let initial = GraphPayload::initial(HashMap::new(), "start");
   let out = run_streamweave_graph(graph, initial).await.unwrap();
   // Identity path: one trigger in → one item out
   assert!(out.is_some(), "expected one output from start→exit graph");
 }
 
+#[tokio::test]
+async fn run_streamweave_graph_ends_a_cycle_whose_conditions_all_miss() {
+  let dot = r#"
+    digraph G {
+      graph [goal="test"]
+      start [shape=Mdiamond]
+      check [type=exec, command="true"]
+      fix [type=exec, command="true"]
+      exit [shape=Msquare]
+      start -> check
+      check -> fix [condition="outcome=fail"]
+      check -> exit [condition="tests_passed=true"]
+      fix -> check
+    }
+  "#;
+  let ast = parse_dot(dot).unwrap();
+  let graph = compile_attractor_graph(&ast, &CompileOptions::default()).unwrap();
+  let initial = GraphPayload::initial(HashMap::new(), "start");
+  let out = tokio::time::timeout(
+    std::time::Duration::from_secs(10),
+    run_streamweave_graph(graph, initial),
+  )
+  .await
+  .expect("run ends instead of waiting for a payload no edge took")
+  .unwrap();
+  let payload = out.unwrap().downcast::<GraphPayload>().unwrap();
+  // No condition matches, so routing falls back to the edges by weight and name (spec §3.3).
+  assert_eq!(payload.completed_nodes, ["start", "check", "exit"]);
+}
+
+#[tokio::test]
+async fn run_streamweave_graph_records_steps_to_log_sink() {
+  let dot = r#"
+    digraph G {
+      graph [goal="test"]
+      start [shape=Mdiamond]
+      run [type=exec, command="true"]
+      exit [shape=Msquare]
+      start -> run -> exit
+    }
+  "#;
+  let ast = parse_dot(dot).unwrap();
+  let sink = ExecutionLogSink::new(
+    ExecutionLog {
+      version: 1,
+      goal: "test".to_string(),
+      started_at: "2026-02-14T10:00:00Z".to_string(),
+      finished_at: None,
+      final_status: "in_progress".to_string(),
+      completed_nodes: vec![],
+      steps: vec![],
+    },
+    None,
+  );
+  let graph = compile_attractor_graph(
+    &ast,
+    &CompileOptions {
+      step_log: Some(&sink),
+      ..Default::default()
+    },
+  )
+  .unwrap();
+  let initial = GraphPayload::initial(HashMap::new(), "start");
+  let out = run_streamweave_graph(graph, initial).await.unwrap();
+  assert!(
+    out.is_some(),
+    "expected one output from start→run→exit graph"
+  );
+  let steps = sink.snapshot().steps;
+  let nodes: Vec<&str> = steps.iter().map(|s| s.node_id.as_str()).collect();
+  assert_eq!(nodes, ["start", "run", "exit"]);
+  assert_eq!(steps[1].step, 2);
+  assert_eq!(steps[1].handler_type.as_deref(), Some("exec"));
+  assert_eq!(steps[1].next_node_id.as_deref(), Some("exit"));
+  assert_eq!(steps[2].next_node_id, None);
+  assert_eq!(steps[2].completed_nodes_after, ["start", "run", "exit"]);
+}
+
 #[test]
 fn compile_err_no_start() {
   let dot = r#"
@@ -92,7 +174,7 @@ fn compile_err_no_start() {
     }
   "#;
   let ast = parse_dot(dot).unwrap();
-  match compile_attractor_graph(&ast, None, None, None) {
+  match compile_attractor_graph(&ast, &CompileOptions::default()) {
     Ok(_) => panic!("expected compile to fail (no start)"),
     Err(e) => assert!(e.to_lowercase().contains("start")),
   }
@@ -107,7 +189,7 @@ fn compile_err_no_exit() {
     }
   "#;
   let ast = parse_dot(dot).unwrap();
-  match compile_attractor_graph(&ast, None, None, None) {
+  match compile_attractor_graph(&ast, &CompileOptions::default()) {
     Ok(_) => panic!("expected compile to fail (no exit)"),
     Err(e) => assert!(e.to_lowercase().contains("exit")),
   }
@@ -119,7 +201,7 @@ fn compile_pre_push_dot() {
     std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("examples/workflows/pre-push.dot");
   let dot = std::fs::read_to_string(&path).unwrap();
   let ast = parse_dot(&dot).unwrap();
-  let graph = compile_attractor_graph(&ast, None, None, None).unwrap();
+  let graph = compile_attractor_graph(&ast, &CompileOptions::default()).unwrap();
   assert!(graph.name().contains("compiled"));
 }
 
@@ -134,7 +216,13 @@ fn compile_rejects_invalid_entry_node_id() {
     }
   "#;
   let ast = parse_dot(dot).unwrap();
-  match compile_attractor_graph(&ast, Some("not_a_node"), None, None) {
+  match compile_attractor_graph(
+    &ast,
+    &CompileOptions {
+      entry_node_id: Some("not_a_node"),
+      ..Default::default()
+    },
+  ) {
     Ok(_) => panic!("expected compile to fail (invalid entry node id)"),
     Err(e) => {
       assert!(
@@ -157,8 +245,65 @@ fn compile_accepts_valid_entry_node_id_for_resume() {
     }
   "#;
   let ast = parse_dot(dot).unwrap();
-  let graph = compile_attractor_graph(&ast, Some("exit"), None, None).unwrap();
+  let graph = compile_attractor_graph(
+    &ast,
+    &CompileOptions {
+      entry_node_id: Some("exit"),
+      ..Default::default()
+    },
+  )
+  .unwrap();
   assert!(graph.name().contains("compiled"));
   assert!(graph.find_node_by_name("start").is_some());
   assert!(graph.find_node_by_name("exit").is_some());
 }
+
+#[test]
+fn compile_checks_custom_rules_and_levels() {
+  use crate::lint::{FnRule, RuleLevel};
+  use crate::types::{Diagnostic, Severity};
+
+  let dot = r#"
+    digraph G {
+      graph [goal="test"]
+      start [shape=Mdiamond]
+      exit [shape=Msquare]
+      draft [label="Draft"]
+      start -> draft -> exit
+    }
+  "#;
+  let ast = parse_dot(dot).unwrap();
+  let compile = |lints: &LintRegistry| {
+    compile_attractor_graph(
+      &ast,
+      &CompileOptions {
+        lints: lints.clone(),
+        ..Default::default()
+      },
+    )
+  };
+  assert!(compile(&LintRegistry::default()).is_ok());
+
+  let mut lints = LintRegistry::default();
+  lints
+    .set_level("prompt_on_llm_nodes", RuleLevel::Deny)
+    .unwrap();
+  let err = compile(&lints).err().expect("denied warning fails compile");
+  assert!(err.contains("draft"), "{}", err);
+
+  let mut lints = LintRegistry::default();
+  lints.register(FnRule::new("no_drafts", |graph| {
+    graph
+      .nodes
+      .keys()
+      .filter(|id| id.as_str() == "draft")
+      .map(|id| {
+        Diagnostic::new("no_drafts", Severity::Error, "drafts are not allowed").with_node(id)
+      })
+      .collect()
+  }));
+  let err = compile(&lints).err().expect("custom rule fails compile");
+  assert_eq!(err, "drafts are not allowed");
+  lints.set_level("no_drafts", RuleLevel::Allow).unwrap();
+  assert!(compile(&lints).is_ok());
+}
@@ -0,0 +1,528 @@
+//! Edge condition expressions (attractor-spec §3.3 step 1).
+//!
+//! Conditions are parsed once, when the graph is built, into a [ConditionExpr]; bad syntax is a
+//! [ConditionParseError] at parse time instead of a condition that silently never matches.
+//!
+//! ```text
+//! expr       := and ( "||" and )*
+//! and        := unary ( "&&" unary )*
+//! unary      := "!" unary | "(" expr ")" | comparison
+//! comparison := key [ op value ]
+//! op         := "=" | "==" | "!=" | "<" | "<=" | ">" | ">=" | "contains" | "matches" | "=~"
+//! key        := "outcome" | "preferred_label" | "context." name | name
+//! value      := bare word | "double quoted" | 'single quoted'
+//! ```
+//!
+//! - `outcome` compares case-insensitively, ignores `_` (`partial_success`) and accepts `fail`
+//!   for `Error`.
+//! - Context keys compare case-insensitively; missing keys read as the empty string.
+//!   `context.name` looks up `context.name`, then `name`.
+//! - `contains` is case-insensitive like `=`; `matches` is case-sensitive unless the regex
+//!   says otherwise (`(?i)`).
+//! - `<`, `<=`, `>`, `>=` compare numerically (false when the context value is not a number).
+//! - A key without an operator is true when its value is non-empty and not `false` or `0`.
+
+use crate::types::{NodeOutcome, RunContext};
+use regex::Regex;
+use std::fmt;
+use std::str::FromStr;
+use tracing::instrument;
+
+/// A parsed edge condition: the source text plus its expression tree.
+#[derive(Debug, Clone)]
+pub struct Condition {
+  /// Condition as written in the DOT file.
+  source: String,
+  /// Parsed expression.
+  expr: ConditionExpr,
+}
+
+/// Expression tree of a condition.
+#[derive(Debug, Clone)]
+pub enum ConditionExpr {
+  /// Both sides hold.
+  And(Box<ConditionExpr>, Box<ConditionExpr>),
+  /// Either side holds.
+  Or(Box<ConditionExpr>, Box<ConditionExpr>),
+  /// Negation.
+  Not(Box<ConditionExpr>),
+  /// `key op value`.
+  Compare {
+    key: ConditionKey,
+    op: CompareOp,
+    value: String,
+  },
+  /// `key matches regex` / `key =~ regex`.
+  Matches { key: ConditionKey, regex: Regex },
+  /// Bare `key`: non-empty and not `false` / `0`.
+  Truthy(ConditionKey),
+}
+
+/// Left-hand side of a comparison.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConditionKey {
+  /// Outcome status of the node that just ran.
+  Outcome,
+  /// Label the node asked to follow.
+  PreferredLabel,
+  /// Run context key, as written (with or without the `context.` prefix).
+  Context(String),
+}
+
+/// Comparison operator.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CompareOp {
+  Eq,
+  Ne,
+  Lt,
+  Le,
+  Gt,
+  Ge,
+  Contains,
+}
+
+/// Syntax error in a condition, with the byte offset where it was detected.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConditionParseError {
+  /// What went wrong.
+  pub message: String,
+  /// Byte offset into the condition source.
+  pub offset: usize,
+}
+
+impl fmt::Display for ConditionParseError {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    write!(f, "{} at column {}", self.message, self.offset + 1)
+  }
+}
+
+impl std::error::Error for ConditionParseError {}
+
+impl Condition {
+  /// Parses a condition expression.
+  #[instrument(level = "trace")]
+  pub fn parse(source: &str) -> Result<Self, ConditionParseError> {
+    let tokens = tokenize(source)?;
+    let mut parser = Parser {
+      tokens,
+      pos: 0,
+      end: source.len(),
+      depth: 0,
+    };
+    let expr = parser.parse_or()?;
+    if let Some((tok, offset)) = parser.tokens.get(parser.pos) {
+      return Err(ConditionParseError {
+        message: format!("unexpected {}", tok.describe()),
+        offset: *offset,
+      });
+    }
+    Ok(Self {
+      source: source.to_string(),
+      expr,
+    })
+  }
+
+  /// Condition as written.
+  pub fn as_str(&self) -> &str {
+    &self.source
+  }
+
+  /// Parsed expression tree.
+  pub fn expr(&self) -> &ConditionExpr {
+    &self.expr
+  }
+
+  /// Evaluates the condition for a node's outcome and the run context after its updates.
+  #[instrument(level = "trace", skip(self, outcome, context), fields(condition = %self.source))]
+  pub fn evaluate(&self, outcome: &NodeOutcome, context: &RunContext) -> bool {
+    self.expr.evaluate(outcome, context)
+  }
+}
+
+impl FromStr for Condition {
+  type Err = ConditionParseError;
+
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    Self::parse(s)
+  }
+}
+
+impl fmt::Display for Condition {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    f.write_str(&self.source)
+  }
+}
+
+impl PartialEq for Condition {
+  fn eq(&self, other: &Self) -> bool {
+    self.source == other.source
+  }
+}
+
+impl ConditionExpr {
+  /// Run context keys the expression reads, as written, in order of appearance.
+  pub fn context_keys(&self) -> Vec<&str> {
+    match self {
+      Self::And(a, b) | Self::Or(a, b) => {
+        let mut keys = a.context_keys();
+        keys.extend(b.context_keys());
+        keys
+      }
+      Self::Not(e) => e.context_keys(),
+      Self::Compare { key, .. } | Self::Matches { key, .. } | Self::Truthy(key) => match key {
+        ConditionKey::Context(name) => vec![name.as_str()],
+        _ => vec![],
+      },
+    }
+  }
+
+  /// Value of the expression after `outcome` whatever the context holds: `Some` when the
+  /// outcome status alone decides it, `None` when it depends on context keys or the preferred
+  /// label.
+  pub fn decided_by_outcome(&self, outcome: &NodeOutcome) -> Option<bool> {
+    match self {
+      Self::And(a, b) => match (a.decided_by_outcome(outcome), b.decided_by_outcome(outcome)) {
+        (Some(false), _) | (_, Some(false)) => Some(false),
+        (Some(true), Some(true)) => Some(true),
+        _ => None,
+      },
+      Self::Or(a, b) => match (a.decided_by_outcome(outcome), b.decided_by_outcome(outcome)) {
+        (Some(true), _) | (_, Some(true)) => Some(true),
+        (Some(false), Some(false)) => Some(false),
+        _ => None,
+      },
+      Self::Not(e) => e.decided_by_outcome(outcome).map(|v| !v),
+      Self::Compare { key, .. } | Self::Matches { key, .. } | Self::Truthy(key) => {
+        (*key == ConditionKey::Outcome).then(|| self.evaluate(outcome, &RunContext::new()))
+      }
+    }
+  }
+
+  /// Evaluates this expression (see [Condition::evaluate]).
+  pub fn evaluate(&self, outcome: &NodeOutcome, context: &RunContext) -> bool {
+    match self {
+      Self::And(a, b) => a.evaluate(outcome, context) && b.evaluate(outcome, context),
+      Self::Or(a, b) => a.evaluate(outcome, context) || b.evaluate(outcome, context),
+      Self::Not(e) => !e.evaluate(outcome, context),
+      Self::Compare { key, op, value } => {
+        let actual = key.resolve(outcome, context);
+        match op {
+          CompareOp::Eq => key.value_equals(value, &actual),
+          CompareOp::Ne => !key.value_equals(value, &actual),
+          CompareOp::Contains => actual
+            .to_ascii_lowercase()
+            .contains(&value.to_ascii_lowercase()),
+          CompareOp::Lt | CompareOp::Le | CompareOp::Gt | CompareOp::Ge => {
+            match (actual.trim().parse::<f64>(), value.parse::<f64>()) {
+              (Ok(a), Ok(v)) => match op {
+                CompareOp::Lt => a < v,
+                CompareOp::Le => a <= v,
+                CompareOp::Gt => a > v,
+                _ => a >= v,
+              },
+              _ => false,
+            }
+          }
+        }
+      }
+      Self::Matches { key, regex } => regex.is_match(&key.resolve(outcome, context)),
+      Self::Truthy(key) => {
+        let v = key.resolve(outcome, context);
+        let v = v.trim();
+        !(v.is_empty() || v.eq_ignore_ascii_case("false") || v == "0")
+      }
+    }
+  }
+}
+
+impl ConditionKey {
+  /// Current value of the key; missing context keys are the empty string.
+  fn resolve(&self, outcome: &NodeOutcome, context: &RunContext) -> String {
+    match self {
+      Self::Outcome => context
+        .get("outcome")
+        .cloned()
+        .unwrap_or_else(|| format!("{:?}", outcome.status)),
+      Self::PreferredLabel => outcome
+        .preferred_label
+        .clone()
+        .or_else(|| context.get("preferred_label").cloned())
+        .unwrap_or_default(),
+      Self::Context(name) => context
+        .get(name)
+        .or_else(|| name.strip_prefix("context.").and_then(|n| context.get(n)))
+        .cloned()
+        .unwrap_or_default(),
+    }
+  }
+
+  /// `=` semantics: outcome aliases for [ConditionKey::Outcome], case-insensitive otherwise.
+  fn value_equals(&self, expected: &str, actual: &str) -> bool {
+    match self {
+      Self::Outcome => outcome_value_matches(expected, actual),
+      _ => actual.eq_ignore_ascii_case(expected.trim()),
+    }
+  }
+}
+
+/// Returns true if a condition's outcome value (e.g. `success`, `fail`) names the context outcome.
+/// Context holds the status in Debug form (`Success`, `Error`, ...); `fail` is an alias for `Error`.
+#[instrument(level = "trace")]
+pub(crate) fn outcome_value_matches(expected: &str, outcome_str: &str) -> bool {
+  let expected = expected.trim();
+  let normalized = expected.replace('_', "");
+  normalized.eq_ignore_ascii_case(outcome_str)
+    || ((expected.eq_ignore_ascii_case("fail") || expected.eq_ignore_ascii_case("failure"))
+      && outcome_str.eq_ignore_ascii_case("error"))
+}
+
+/// Lexical token of a condition.
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+  LParen,
+  RParen,
+  And,
+  Or,
+  Not,
+  Op(CompareOp),
+  Match,
+  Word(String),
+  Quoted(String),
+}
+
+impl Token {
+  /// Short description for error messages.
+  fn describe(&self) -> String {
+    match self {
+      Self::LParen => "'('".to_string(),
+      Self::RParen => "')'".to_string(),
+      Self::And => "'&&'".to_string(),
+      Self::Or => "'||'".to_string(),
+      Self::Not => "'!'".to_string(),
+      Self::Op(op) => format!("operator {:?}", op),
+      Self::Match => "operator 'matches'".to_string(),
+      Self::Word(w) => format!("'{}'", w),
+      Self::Quoted(q) => format!("\"{}\"", q),
+    }
+  }
+}
+
+/// Characters that end a bare word.
+fn is_delimiter(c: char) -> bool {
+  c.is_whitespace() || "()&|!=<>\"'".contains(c)
+}
+
+/// Splits a condition into tokens with their byte offsets.
+fn tokenize(source: &str) -> Result<Vec<(Token, usize)>, ConditionParseError> {
+  let mut tokens = Vec::new();
+  let mut chars = source.char_indices().peekable();
+  while let Some(&(i, c)) = chars.peek() {
+    let rest = &source[i..];
+    let (tok, len) = if c.is_whitespace() {
+      chars.next();
+      continue;
+    } else if c == '(' {
+      (Token::LParen, 1)
+    } else if c == ')' {
+      (Token::RParen, 1)
+    } else if rest.starts_with("&&") {
+      (Token::And, 2)
+    } else if rest.starts_with("||") {
+      (Token::Or, 2)
+    } else if c == '&' || c == '|' {
+      return Err(ConditionParseError {
+        message: format!("expected '{}{}'", c, c),
+        offset: i,
+      });
+    } else if rest.starts_with("!=") {
+      (Token::Op(CompareOp::Ne), 2)
+    } else if c == '!' {
+      (Token::Not, 1)
+    } else if rest.starts_with("=~") {
+      (Token::Match, 2)
+    } else if rest.starts_with("==") {
+      (Token::Op(CompareOp::Eq), 2)
+    } else if c == '=' {
+      (Token::Op(CompareOp::Eq), 1)
+    } else if rest.starts_with("<=") {
+      (Token::Op(CompareOp::Le), 2)
+    } else if c == '<' {
+      (Token::Op(CompareOp::Lt), 1)
+    } else if rest.starts_with(">=") {
+      (Token::Op(CompareOp::Ge), 2)
+    } else if c == '>' {
+      (Token::Op(CompareOp::Gt), 1)
+    } else if c == '"' || c == '\'' {
+      let (value, len) = lex_quoted(rest, c).ok_or(ConditionParseError {
+        message: "unterminated string".to_string(),
+        offset: i,
+      })?;
+      (Token::Quoted(value), len)
+    } else {
+      let len = rest.find(is_delimiter).unwrap_or(rest.len());
+      (Token::Word(rest[..len].to_string()), len)
+    };
+    tokens.push((tok, i));
+    while chars.peek().is_some_and(|&(j, _)| j < i + len) {
+      chars.next();
+    }
+  }
+  Ok(tokens)
+}
+
+/// Reads a quoted string starting at `quote`; `\` escapes the next character.
+/// Returns the unescaped value and the byte length including both quotes.
+fn lex_quoted(s: &str, quote: char) -> Option<(String, usize)> {
+  let mut value = String::new();
+  let mut escaped = false;
+  for (i, c) in s.char_indices().skip(1) {
+    if escaped {
+      value.push(c);
+      escaped = false;
+    } else if c == '\\' {
+      escaped = true;
+    } else if c == quote {
+      return Some((value, i + c.len_utf8()));
+    } else {
+      value.push(c);
+    }
+  }
+  None
+}
+
+/// Recursive-descent parser over the token list.
+struct Parser {
+  /// Tokens with byte offsets.
+  tokens: Vec<(Token, usize)>,
+  /// Index of the next token.
+  pos: usize,
+  /// Source length (offset reported for errors at end of input).
+  end: usize,
+  /// Current `!`/parenthesis nesting, capped at [MAX_NESTING].
+  depth: usize,
+}
+
+/// Deepest `!`/parenthesis nesting accepted, so hostile input cannot overflow the stack.
+const MAX_NESTING: usize = 64;
+
+impl Parser {
+  fn peek(&self) -> Option<&Token> {
+    self.tokens.get(self.pos).map(|(t, _)| t)
+  }
+
+  fn offset(&self) -> usize {
+    self.tokens.get(self.pos).map_or(self.end, |(_, o)| *o)
+  }
+
+  fn error(&self, message: impl Into<String>) -> ConditionParseError {
+    ConditionParseError {
+      message: message.into(),
+      offset: self.offset(),
+    }
+  }
+
+  fn parse_or(&mut self) -> Result<ConditionExpr, ConditionParseError> {
+    let mut lhs = self.parse_and()?;
+    while self.peek() == Some(&Token::Or) {
+      self.pos += 1;
+      let rhs = self.parse_and()?;
+      lhs = ConditionExpr::Or(Box::new(lhs), Box::new(rhs));
+    }
+    Ok(lhs)
+  }
+
+  fn parse_and(&mut self) -> Result<ConditionExpr, ConditionParseError> {
+    let mut lhs = self.parse_unary()?;
+    while self.peek() == Some(&Token::And) {
+      self.pos += 1;
+      let rhs = self.parse_unary()?;
+      lhs = ConditionExpr::And(Box::new(lhs), Box::new(rhs));
+    }
+    Ok(lhs)
+  }
+
+  fn parse_unary(&mut self) -> Result<ConditionExpr, ConditionParseError> {
+    if matches!(self.peek(), Some(Token::Not | Token::LParen)) {
+      if self.depth == MAX_NESTING {
+        return Err(self.error("condition nested too deeply"));
+      }
+      self.depth += 1;
+      let expr = self.parse_nested();
+      self.depth -= 1;
+      return expr;
+    }
+    self.parse_comparison()
+  }
+
+  fn parse_nested(&mut self) -> Result<ConditionExpr, ConditionParseError> {
+    self.pos += 1;
+    if self.tokens[self.pos - 1].0 == Token::Not {
+      return Ok(ConditionExpr::Not(Box::new(self.parse_unary()?)));
+    }
+    let inner = self.parse_or()?;
+    if self.peek() != Some(&Token::RParen) {
+      return Err(self.error("expected ')'"));
+    }
+    self.pos += 1;
+    Ok(inner)
+  }
+
+  fn parse_comparison(&mut self) -> Result<ConditionExpr, ConditionParseError> {
+    let key = match self.peek() {
+      Some(Token::Word(w)) => parse_key(w).map_err(|m| self.error(m))?,
+      Some(t) => return Err(self.error(format!("expected a key, found {}", t.describe()))),
+      None if self.tokens.is_empty() => return Err(self.error("empty condition")),
+      None => return Err(self.error("expected a key")),
+    };
+    self.pos += 1;
+    let op = match self.peek() {
+      Some(Token::Op(op)) => Some(*op),
+      Some(Token::Word(w)) if w == "contains" => Some(CompareOp::Contains),
+      Some(Token::Match) => None,
+      Some(Token::Word(w)) if w == "matches" => None,
+      _ => return Ok(ConditionExpr::Truthy(key)),
+    };
+    let op_token = self.peek().map(Token::describe).unwrap_or_default();
+    self.pos += 1;
+    let value = match self.peek() {
+      Some(Token::Word(w)) | Some(Token::Quoted(w)) => w.clone(),
+      _ => return Err(self.error(format!("expected a value after {}", op_token))),
+    };
+    let expr = match op {
+      Some(op @ (CompareOp::Lt | CompareOp::Le | CompareOp::Gt | CompareOp::Ge)) => {
+        if value.parse::<f64>().is_err() {
+          return Err(self.error(format!("expected a number after {}", op_token)));
+        }
+        ConditionExpr::Compare { key, op, value }
+      }
+      Some(op) => ConditionExpr::Compare { key, op, value },
+      None => {
+        let regex = Regex::new(&value).map_err(|e| self.error(format!("invalid regex: {}", e)))?;
+        ConditionExpr::Matches { key, regex }
+      }
+    };
+    self.pos += 1;
+    Ok(expr)
+  }
+}
+
+/// Validates a key word and classifies it.
+fn parse_key(word: &str) -> Result<ConditionKey, String> {
+  let valid = |s: &str| {
+    !s.is_empty()
+      && s
+        .chars()
+        .all(|c| c.is_alphanumeric() || c == '_' || c == '.' || c == '-')
+  };
+  if word == "contains" || word == "matches" {
+    return Err(format!("expected a key, found operator '{}'", word));
+  }
+  if !valid(word) || word.strip_prefix("context.").is_some_and(str::is_empty) {
+    return Err(format!("invalid key '{}'", word));
+  }
+  Ok(match word {
+    "outcome" => ConditionKey::Outcome,
+    "preferred_label" => ConditionKey::PreferredLabel,
+    _ => ConditionKey::Context(word.to_string()),
+  })
+}
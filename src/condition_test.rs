@@ -0,0 +1,187 @@
+//! Tests for `condition`.
+
+use std::collections::HashMap;
+
+use crate::condition::{CompareOp, Condition, ConditionExpr, ConditionKey};
+use crate::types::{NodeOutcome, RunContext};
+
+fn ctx(pairs: &[(&str, &str)]) -> RunContext {
+  pairs
+    .iter()
+    .map(|(k, v)| (k.to_string(), v.to_string()))
+    .collect()
+}
+
+fn eval(cond: &str, context: &RunContext) -> bool {
+  Condition::parse(cond)
+    .unwrap_or_else(|e| panic!("parse {:?}: {}", cond, e))
+    .evaluate(&NodeOutcome::success("ok"), context)
+}
+
+fn parse_err(cond: &str) -> String {
+  match Condition::parse(cond) {
+    Ok(c) => panic!("expected parse error for {:?}, got {:?}", cond, c.expr()),
+    Err(e) => e.to_string(),
+  }
+}
+
+#[test]
+fn legacy_forms_still_evaluate() {
+  let c = ctx(&[("outcome", "Error"), ("has_tasks", "true")]);
+  assert!(eval("outcome=fail", &c));
+  assert!(eval("outcome!=success", &c));
+  assert!(eval("has_tasks=true", &c));
+  assert!(eval("has_tasks=TRUE", &c));
+  assert!(!eval("has_tasks=false", &c));
+}
+
+#[test]
+fn parses_comparison_into_ast() {
+  let cond = Condition::parse("context.tests_passed == true").unwrap();
+  assert_eq!(cond.as_str(), "context.tests_passed == true");
+  match cond.expr() {
+    ConditionExpr::Compare { key, op, value } => {
+      assert_eq!(
+        *key,
+        ConditionKey::Context("context.tests_passed".to_string())
+      );
+      assert_eq!(*op, CompareOp::Eq);
+      assert_eq!(value, "true");
+    }
+    other => panic!("unexpected expr {:?}", other),
+  }
+}
+
+#[test]
+fn boolean_operators_and_precedence() {
+  let c = ctx(&[("outcome", "Success"), ("a", "1"), ("b", "0")]);
+  assert!(eval("outcome=success && a=1", &c));
+  assert!(!eval("outcome=success && b=1", &c));
+  assert!(eval("b=1 || a=1", &c));
+  // && binds tighter than ||
+  assert!(eval("a=1 || b=1 && b=2", &c));
+  assert!(!eval("(a=1 || b=1) && b=2", &c));
+  assert!(eval("!(b=1)", &c));
+  assert!(eval("!!a", &c));
+}
+
+#[test]
+fn not_equal_on_any_key() {
+  let c = ctx(&[("branch", "main")]);
+  assert!(eval("branch != release", &c));
+  assert!(!eval("branch != main", &c));
+  assert!(eval("missing != x", &c));
+}
+
+#[test]
+fn numeric_comparisons() {
+  let c = ctx(&[("coverage", "81.5"), ("failures", "0"), ("name", "abc")]);
+  assert!(eval("coverage >= 80", &c));
+  assert!(eval("coverage > 81", &c));
+  assert!(!eval("coverage < 80", &c));
+  assert!(eval("failures <= 0", &c));
+  assert!(!eval("name < 5", &c), "non-numeric context value is false");
+  assert!(!eval("missing > -1", &c));
+}
+
+#[test]
+fn contains_and_regex() {
+  let c = ctx(&[("log", "error: test foo failed"), ("task", "bd-42")]);
+  assert!(eval("log contains failed", &c));
+  assert!(eval("log contains \"test foo\"", &c));
+  assert!(!eval("log contains passed", &c));
+  assert!(
+    eval("log contains FAILED", &c),
+    "contains ignores case like ="
+  );
+  assert!(eval("log contains \"Test Foo\"", &c));
+  assert!(
+    !eval("log matches FAILED", &c),
+    "matches keeps the regex's case"
+  );
+  assert!(eval("task matches '^bd-[0-9]+$'", &c));
+  assert!(eval("task =~ \"^bd-\"", &c));
+  assert!(!eval("task matches ^xyz", &c));
+}
+
+#[test]
+fn context_namespace_and_truthiness() {
+  let c = ctx(&[
+    ("has_tasks", "true"),
+    ("context.flag", "yes"),
+    ("zero", "0"),
+  ]);
+  assert!(eval("context.has_tasks=true", &c));
+  assert!(eval("context.flag", &c));
+  assert!(eval("has_tasks", &c));
+  assert!(!eval("zero", &c));
+  assert!(!eval("context.missing", &c));
+}
+
+#[test]
+fn preferred_label_key_reads_outcome() {
+  let mut outcome = NodeOutcome::success("ok");
+  outcome.preferred_label = Some("Approve".to_string());
+  let cond = Condition::parse("preferred_label=approve").unwrap();
+  assert!(cond.evaluate(&outcome, &HashMap::new()));
+}
+
+#[test]
+fn outcome_falls_back_to_outcome_status_without_context() {
+  let cond = Condition::parse("outcome=fail").unwrap();
+  assert!(cond.evaluate(&NodeOutcome::error("boom"), &HashMap::new()));
+}
+
+#[test]
+fn parse_errors_are_reported() {
+  assert!(parse_err("").contains("empty condition"));
+  assert!(parse_err("(a=1").contains("expected ')'"));
+  assert!(parse_err("a=").contains("expected a value"));
+  assert!(parse_err("a=1 & b=2").contains("expected '&&'"));
+  assert!(parse_err("a=1 b=2").contains("unexpected"));
+  assert!(parse_err("n > many").contains("expected a number"));
+  assert!(parse_err("x matches '('").contains("invalid regex"));
+  assert!(parse_err("x = \"open").contains("unterminated string"));
+  assert!(parse_err("&& a").contains("expected a key"));
+  assert!(parse_err("context.=1").contains("invalid key"));
+}
+
+#[test]
+fn parse_error_reports_column() {
+  let e = Condition::parse("a=1 &&").unwrap_err();
+  assert_eq!(e.offset, 6);
+  assert!(e.to_string().ends_with("at column 7"), "{}", e);
+}
+
+#[test]
+fn dot_parser_rejects_invalid_condition() {
+  let dot = r#"digraph G { start [shape=Mdiamond] exit [shape=Msquare] start -> exit [condition="outcome=success &&"] }"#;
+  let err = crate::dot_parser::parse_dot(dot).unwrap_err().to_string();
+  assert!(err.contains("start -> exit"), "{}", err);
+  assert!(err.contains("expected a key"), "{}", err);
+}
+
+#[test]
+fn context_keys_lists_context_reads_only() {
+  let c =
+    Condition::parse("outcome=success && (context.tests_passed || !ready) && preferred_label=Fix")
+      .unwrap();
+  assert_eq!(c.expr().context_keys(), ["context.tests_passed", "ready"]);
+}
+
+#[test]
+fn decided_by_outcome_only_when_context_cannot_change_it() {
+  let decided = |cond: &str| {
+    Condition::parse(cond)
+      .unwrap()
+      .expr()
+      .decided_by_outcome(&NodeOutcome::success("ok"))
+  };
+  assert_eq!(decided("outcome=success"), Some(true));
+  assert_eq!(decided("outcome=fail"), Some(false));
+  assert_eq!(decided("outcome!=success || ready"), None);
+  assert_eq!(decided("outcome=fail && ready"), Some(false));
+  assert_eq!(decided("outcome=success || ready"), Some(true));
+  assert_eq!(decided("!(outcome=retry)"), Some(true));
+  assert_eq!(decided("preferred_label=Fix"), None);
+}
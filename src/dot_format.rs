@@ -0,0 +1,460 @@
+//! Formats DOT source without dropping anything written in it.
+//!
+//! [crate::dot_writer::write_dot] writes a parsed graph, so it loses what the graph does not
+//! hold. [format_dot] instead lays out the syntax tree [crate::dot_parser] keeps while parsing,
+//! placing comments by their position: statements keep their order, and comments, subgraphs,
+//! `node [...]`/`edge [...]` defaults and HTML or escaped IDs are kept.
+//! The layout:
+//! - one statement per line, indented two spaces per block, without `;`;
+//! - attribute lists as `[k=v, k=v]`, one attribute per line when too long or commented;
+//! - at most one blank line between statements, where the source had one;
+//! - quoted IDs written bare when they need no quotes, and keywords in lowercase.
+//!
+//! A comment in the middle of a statement (outside an attribute list) has no place in this
+//! layout, so such files are reported as errors instead of formatted.
+
+use std::iter::Peekable;
+use std::ops::Range;
+
+use crate::dot_parser::{
+  AttrListSyntax, DotSyntax, EndpointSyntax, IdSyntax, ParseError, Pos, StmtKind, StmtSyntax,
+  error_at, parse_dot_syntax,
+};
+use crate::dot_writer::{MAX_LINE_WIDTH, quote_id};
+use tracing::instrument;
+
+/// Parses `source` and lays it out in canonical form, keeping comments, subgraphs, defaults and
+/// IDs as written. The result parses to the same graph as `source`.
+#[instrument(level = "trace", skip(source))]
+pub fn format_dot(source: &str) -> Result<String, Box<ParseError>> {
+  let (_, syntax) = parse_dot_syntax(source)?;
+  let lines = Lines {
+    source,
+    comments: &syntax.comments,
+  };
+  let file = lines.file(&syntax)?;
+  let mut out = String::with_capacity(source.len());
+  write_file(&mut out, &file);
+  Ok(out)
+}
+
+/// A DOT file: comments around the graph, its header and its statements.
+struct File<'a> {
+  leading: Vec<Item<'a>>,
+  header: String,
+  items: Vec<Item<'a>>,
+  /// Comment on the line of the closing `}`.
+  closing: Option<&'a str>,
+  trailing: Vec<Item<'a>>,
+}
+
+/// A line in a `{ ... }` block.
+enum Item<'a> {
+  Blank,
+  Comment(&'a str),
+  /// A statement and the comment after it on the same line.
+  Stmt(Stmt<'a>, Option<&'a str>),
+}
+
+enum Stmt<'a> {
+  /// `graph`, `node` or `edge` followed by attributes.
+  Defaults(String, Vec<Attr<'a>>),
+  /// `key=value`.
+  Assign(String, String),
+  /// A node or subgraph, or an edge chain like `a -> {b c}`, with attributes.
+  Chain(Vec<Endpoint<'a>>, Vec<Attr<'a>>),
+}
+
+enum Endpoint<'a> {
+  /// A node ID with its port, if any.
+  Node(String),
+  /// `subgraph name { ... }` or `{ ... }`, with the header if there is one.
+  Subgraph(Option<String>, Vec<Item<'a>>),
+}
+
+/// A line in an attribute list.
+enum Attr<'a> {
+  /// `key=value` and the comment after it on the same line.
+  Pair(String, String, Option<&'a str>),
+  Comment(&'a str),
+}
+
+/// Turns the parser's syntax tree into lines, placing each comment by its position.
+struct Lines<'a> {
+  source: &'a str,
+  comments: &'a [Range<usize>],
+}
+
+impl<'a> Lines<'a> {
+  fn text(&self, pos: Pos) -> &'a str {
+    &self.source[pos.range(self.source)]
+  }
+
+  /// Comments within `range`, in order.
+  fn comments_in(&self, range: Range<usize>) -> Peekable<impl Iterator<Item = Range<usize>>> {
+    self
+      .comments
+      .iter()
+      .filter(move |c| c.start >= range.start && c.end <= range.end)
+      .cloned()
+      .peekable()
+  }
+
+  /// Whether a blank line separates `end` (the end of the previous line, if any) and `start`.
+  fn blank_between(&self, end: Option<usize>, start: usize) -> bool {
+    end.is_some_and(|end| self.source[end..start].matches('\n').count() > 1)
+  }
+
+  /// Takes the next comment if it starts on the line where `end` is, before `next`.
+  fn same_line(
+    &self,
+    end: usize,
+    next: usize,
+    comments: &mut Peekable<impl Iterator<Item = Range<usize>>>,
+  ) -> Option<&'a str> {
+    let source = self.source;
+    comments
+      .next_if(|c| c.start < next && !source[end..c.start].contains('\n'))
+      .map(|c| &source[c])
+  }
+
+  fn misplaced(&self, comment: Range<usize>) -> Box<ParseError> {
+    error_at(
+      self.source,
+      comment,
+      "cannot format a comment inside a statement; move it to its own line",
+    )
+  }
+
+  fn file(&self, syntax: &DotSyntax) -> Result<File<'a>, Box<ParseError>> {
+    let pos = syntax.pos.range(self.source);
+    let body = syntax.body.range(self.source);
+    let mut leading = self.comment_lines(self.comments_in(0..pos.start), None);
+    if self.blank_between(
+      self.comments_in(0..pos.start).last().map(|c| c.end),
+      pos.start,
+    ) {
+      leading.push(Item::Blank);
+    }
+    if let Some(comment) = self.comments_in(pos.start..body.start).next() {
+      return Err(self.misplaced(comment));
+    }
+    let mut header = Vec::new();
+    if syntax.strict {
+      header.push("strict".to_string());
+    }
+    header.push("digraph".to_string());
+    header.extend(syntax.name.as_ref().map(|name| self.id(name)));
+    let mut after = self.comments_in(pos.end..self.source.len());
+    let closing = self.same_line(pos.end, self.source.len(), &mut after);
+    Ok(File {
+      leading,
+      header: header.join(" "),
+      items: self.block(&body, &syntax.stmts)?,
+      closing,
+      trailing: self.comment_lines(after, None),
+    })
+  }
+
+  /// Comments on their own lines, keeping blank lines between them.
+  fn comment_lines(
+    &self,
+    comments: impl Iterator<Item = Range<usize>>,
+    mut end: Option<usize>,
+  ) -> Vec<Item<'a>> {
+    let mut items = Vec::new();
+    for comment in comments {
+      if self.blank_between(end, comment.start) {
+        items.push(Item::Blank);
+      }
+      end = Some(comment.end);
+      items.push(Item::Comment(&self.source[comment]));
+    }
+    items
+  }
+
+  /// Lines of the block `body` (from `{` to `}`) holding `stmts`.
+  fn block(
+    &self,
+    body: &Range<usize>,
+    stmts: &[StmtSyntax],
+  ) -> Result<Vec<Item<'a>>, Box<ParseError>> {
+    let mut comments = self.comments_in(body.start + 1..body.end - 1);
+    let mut items = Vec::new();
+    let mut end = None;
+    for (i, stmt) in stmts.iter().enumerate() {
+      let range = stmt.pos.range(self.source);
+      let next = stmts
+        .get(i + 1)
+        .map_or(body.end, |s| s.pos.range(self.source).start);
+      while let Some(comment) = comments.next_if(|c| c.start < range.start) {
+        items.extend(self.comment_lines([comment.clone()].into_iter(), end));
+        end = Some(comment.end);
+      }
+      while comments.next_if(|c| c.start < range.end).is_some() {}
+      if self.blank_between(end, range.start) {
+        items.push(Item::Blank);
+      }
+      let trailing = self.same_line(range.end, next, &mut comments);
+      items.push(Item::Stmt(self.stmt(stmt)?, trailing));
+      end = Some(range.end);
+    }
+    items.extend(self.comment_lines(comments, end));
+    Ok(items)
+  }
+
+  /// A statement; comments inside it must be in one of its attribute lists or subgraphs.
+  fn stmt(&self, stmt: &StmtSyntax) -> Result<Stmt<'a>, Box<ParseError>> {
+    let (endpoints, lists): (&[EndpointSyntax], &[AttrListSyntax]) = match &stmt.kind {
+      StmtKind::Defaults(_, lists) => (&[], lists),
+      StmtKind::Assign(..) => (&[], &[]),
+      StmtKind::Chain(endpoints, lists) => (endpoints, lists),
+    };
+    let holders: Vec<Range<usize>> = lists
+      .iter()
+      .map(|list| list.pos)
+      .chain(endpoints.iter().filter_map(|endpoint| match endpoint {
+        EndpointSyntax::Subgraph(subgraph) => Some(subgraph.body),
+        EndpointSyntax::Node(..) => None,
+      }))
+      .map(|pos| pos.range(self.source))
+      .collect();
+    for comment in self.comments_in(stmt.pos.range(self.source)) {
+      if !holders
+        .iter()
+        .any(|h| h.start < comment.start && comment.end < h.end)
+      {
+        return Err(self.misplaced(comment));
+      }
+    }
+    Ok(match &stmt.kind {
+      StmtKind::Defaults(keyword, lists) => Stmt::Defaults(keyword.to_string(), self.attrs(lists)?),
+      StmtKind::Assign(key, value) => Stmt::Assign(self.id(key), self.id(value)),
+      StmtKind::Chain(endpoints, lists) => Stmt::Chain(
+        endpoints
+          .iter()
+          .map(|endpoint| self.endpoint(endpoint))
+          .collect::<Result<_, _>>()?,
+        self.attrs(lists)?,
+      ),
+    })
+  }
+
+  fn endpoint(&self, endpoint: &EndpointSyntax) -> Result<Endpoint<'a>, Box<ParseError>> {
+    Ok(match endpoint {
+      EndpointSyntax::Node(id, ports) => {
+        let mut node = self.id(id);
+        for port in ports {
+          node.push(':');
+          node.push_str(&self.id(port));
+        }
+        Endpoint::Node(node)
+      }
+      EndpointSyntax::Subgraph(subgraph) => {
+        let header = subgraph.keyword.then(|| match &subgraph.name {
+          Some(name) => format!("subgraph {}", self.id(name)),
+          None => "subgraph".to_string(),
+        });
+        let body = subgraph.body.range(self.source);
+        Endpoint::Subgraph(header, self.block(&body, &subgraph.stmts)?)
+      }
+    })
+  }
+
+  /// The attributes of `lists`, merged into one list with the comments inside them.
+  fn attrs(&self, lists: &[AttrListSyntax]) -> Result<Vec<Attr<'a>>, Box<ParseError>> {
+    let mut attrs = Vec::new();
+    for list in lists {
+      let range = list.pos.range(self.source);
+      let mut comments = self.comments_in(range.start + 1..range.end - 1);
+      let end = range.end;
+      for (i, attr) in list.attrs.iter().enumerate() {
+        let range = attr.pos.range(self.source);
+        let next = list
+          .attrs
+          .get(i + 1)
+          .map_or(end, |a| a.pos.range(self.source).start);
+        while let Some(comment) = comments.next_if(|c| c.start < range.start) {
+          attrs.push(Attr::Comment(&self.source[comment]));
+        }
+        if let Some(comment) = comments.next_if(|c| c.start < range.end) {
+          return Err(self.misplaced(comment));
+        }
+        let trailing = self.same_line(range.end, next, &mut comments);
+        attrs.push(Attr::Pair(
+          self.id(&attr.key),
+          self.id(&attr.value),
+          trailing,
+        ));
+      }
+      attrs.extend(comments.map(|comment| Attr::Comment(&self.source[comment])));
+    }
+    Ok(attrs)
+  }
+
+  /// An ID as written, with `+` concatenations spaced evenly.
+  fn id(&self, id: &IdSyntax) -> String {
+    match id.parts.as_slice() {
+      [single] => unquote(self.text(*single)),
+      parts => {
+        let parts: Vec<&str> = parts.iter().map(|&part| self.text(part)).collect();
+        parts.join(" + ")
+      }
+    }
+  }
+}
+
+/// `raw` without its quotes when they are not needed, else as written. Strings with escapes are
+/// kept as written, so sequences such as `\l` stay untouched.
+fn unquote(raw: &str) -> String {
+  match raw.strip_prefix('"').and_then(|r| r.strip_suffix('"')) {
+    Some(inner) if !inner.contains('\\') && quote_id(inner) == inner => inner.to_string(),
+    _ => raw.to_string(),
+  }
+}
+
+fn write_file(out: &mut String, file: &File) {
+  write_items(out, &file.leading, 0);
+  out.push_str(&file.header);
+  out.push_str(" {\n");
+  write_items(out, &file.items, 1);
+  out.push('}');
+  if let Some(comment) = file.closing {
+    out.push(' ');
+    out.push_str(comment.trim_end());
+  }
+  out.push('\n');
+  write_items(out, &file.trailing, 0);
+}
+
+fn indent(out: &mut String, depth: usize) {
+  out.push_str(&"  ".repeat(depth));
+}
+
+fn write_items(out: &mut String, items: &[Item], depth: usize) {
+  for item in items {
+    match item {
+      Item::Blank => {}
+      Item::Comment(comment) => {
+        indent(out, depth);
+        out.push_str(comment.trim_end());
+      }
+      Item::Stmt(stmt, trailing) => {
+        indent(out, depth);
+        write_stmt(out, stmt, depth);
+        if let Some(comment) = trailing {
+          out.push(' ');
+          out.push_str(comment.trim_end());
+        }
+      }
+    }
+    out.push('\n');
+  }
+}
+
+fn write_stmt(out: &mut String, stmt: &Stmt, depth: usize) {
+  match stmt {
+    Stmt::Defaults(keyword, attrs) => {
+      out.push_str(keyword);
+      write_attrs(out, attrs, depth);
+    }
+    Stmt::Assign(key, value) => {
+      out.push_str(key);
+      out.push('=');
+      out.push_str(value);
+    }
+    Stmt::Chain(endpoints, attrs) => {
+      for (i, endpoint) in endpoints.iter().enumerate() {
+        if i > 0 {
+          out.push_str(" -> ");
+        }
+        write_endpoint(out, endpoint, depth);
+      }
+      write_attrs(out, attrs, depth);
+    }
+  }
+}
+
+/// Writes a node, or a subgraph: on one line as `{a b}` when it only lists plain nodes, else as
+/// an indented block.
+fn write_endpoint(out: &mut String, endpoint: &Endpoint, depth: usize) {
+  let (header, items) = match endpoint {
+    Endpoint::Node(id) => return out.push_str(id),
+    Endpoint::Subgraph(header, items) => (header, items),
+  };
+  if let Some(header) = header {
+    out.push_str(header);
+    out.push(' ');
+  }
+  let plain: Option<Vec<&str>> = items
+    .iter()
+    .map(|item| match item {
+      Item::Stmt(Stmt::Chain(endpoints, attrs), None) if attrs.is_empty() => {
+        match endpoints.as_slice() {
+          [Endpoint::Node(id)] => Some(id.as_str()),
+          _ => None,
+        }
+      }
+      _ => None,
+    })
+    .collect();
+  match plain {
+    Some(ids) => {
+      out.push('{');
+      out.push_str(&ids.join(" "));
+      out.push('}');
+    }
+    None => {
+      out.push_str("{\n");
+      write_items(out, items, depth + 1);
+      indent(out, depth);
+      out.push('}');
+    }
+  }
+}
+
+/// Writes ` [k=v, ...]` on the current line if it fits and has no comments, else one attribute
+/// per line.
+fn write_attrs(out: &mut String, attrs: &[Attr], depth: usize) {
+  if attrs.is_empty() {
+    return;
+  }
+  let pairs: Option<Vec<String>> = attrs
+    .iter()
+    .map(|attr| match attr {
+      Attr::Pair(key, value, None) => Some(format!("{}={}", key, value)),
+      _ => None,
+    })
+    .collect();
+  let line_len = out.len() - out.rfind('\n').map_or(0, |i| i + 1);
+  if let Some(pairs) = pairs {
+    let one_line = format!(" [{}]", pairs.join(", "));
+    if line_len + one_line.len() <= MAX_LINE_WIDTH {
+      out.push_str(&one_line);
+      return;
+    }
+  }
+  out.push_str(" [\n");
+  let last_pair = attrs.iter().rposition(|a| matches!(a, Attr::Pair(..)));
+  for (i, attr) in attrs.iter().enumerate() {
+    indent(out, depth + 1);
+    match attr {
+      Attr::Pair(key, value, trailing) => {
+        out.push_str(key);
+        out.push('=');
+        out.push_str(value);
+        if Some(i) != last_pair {
+          out.push(',');
+        }
+        if let Some(comment) = trailing {
+          out.push(' ');
+          out.push_str(comment.trim_end());
+        }
+      }
+      Attr::Comment(comment) => out.push_str(comment.trim_end()),
+    }
+    out.push('\n');
+  }
+  indent(out, depth);
+  out.push(']');
+}
@@ -0,0 +1,162 @@
+//! Tests for `dot_format`.
+
+use crate::dot_format::format_dot;
+use crate::dot_parser::parse_dot;
+use crate::dot_writer::write_dot;
+use proptest::prelude::*;
+
+/// Asserts that `formatted` parses to the same graph as `source` and formats to itself.
+fn assert_same_pipeline(source: &str, formatted: &str) {
+  assert_eq!(
+    write_dot(&parse_dot(formatted).unwrap()),
+    write_dot(&parse_dot(source).unwrap()),
+    "{}",
+    formatted
+  );
+  assert_eq!(format_dot(formatted).unwrap(), formatted, "not idempotent");
+}
+
+#[test]
+fn format_dot_keeps_comments_subgraphs_defaults_and_ids() {
+  let dot = r#"// Build pipeline
+/* owned by the build team */
+
+Digraph "Build" {
+  rankdir="LR";   // left to right
+  node [shape=box]; edge [weight=1]
+  start [shape=Mdiamond] exit [shape=Msquare]
+
+
+  subgraph cluster_build { label="Build Stage"; node [shape=parallelogram]
+    # preprocessor line
+    compile; link }
+  report [label=<<b>Report</b>>, note="first\lsecond\l"]
+  start -> {compile link} -> report -> "exit" [condition="outcome=success"]
+} // end
+"#;
+  let formatted = format_dot(dot).unwrap();
+  assert_eq!(
+    formatted,
+    r#"// Build pipeline
+/* owned by the build team */
+
+digraph Build {
+  rankdir=LR // left to right
+  node [shape=box]
+  edge [weight=1]
+  start [shape=Mdiamond]
+  exit [shape=Msquare]
+
+  subgraph cluster_build {
+    label="Build Stage"
+    node [shape=parallelogram]
+    # preprocessor line
+    compile
+    link
+  }
+  report [label=<<b>Report</b>>, note="first\lsecond\l"]
+  start -> {compile link} -> report -> exit [condition="outcome=success"]
+} // end
+"#
+  );
+  assert_same_pipeline(dot, &formatted);
+}
+
+#[test]
+fn format_dot_breaks_long_and_commented_attribute_lists() {
+  let dot = r#"digraph G {
+  start [shape=Mdiamond]
+  work [prompt="Implement the feature described in the issue and add unit tests for it before review", max_retries=3]
+  fix [ // retried by hand
+    prompt="Fix it", max_retries=1 // once is enough
+  ]
+  start -> work -> fix
+}
+"#;
+  let formatted = format_dot(dot).unwrap();
+  assert!(
+    formatted.contains(
+      "  work [\n    prompt=\"Implement the feature described in the issue and add unit tests for it before review\",\n    max_retries=3\n  ]\n"
+    ),
+    "{}",
+    formatted
+  );
+  assert!(
+    formatted.contains(
+      "  fix [\n    // retried by hand\n    prompt=\"Fix it\",\n    max_retries=1 // once is enough\n  ]\n"
+    ),
+    "{}",
+    formatted
+  );
+  assert_same_pipeline(dot, &formatted);
+}
+
+#[test]
+fn format_dot_rejects_comment_inside_statement() {
+  let dot = "digraph G {\n  start [shape=Mdiamond]\n  start // first\n    -> exit\n}\n";
+  let err = format_dot(dot).unwrap_err();
+  assert!(
+    err.message.contains("comment inside a statement"),
+    "{}",
+    err
+  );
+  assert_eq!((err.line, err.column), (3, 9));
+  let err = format_dot("digraph G { subgraph s /* x */ { a } }").unwrap_err();
+  assert!(
+    err.message.contains("comment inside a statement"),
+    "{}",
+    err
+  );
+  let err = format_dot("digraph G { a [x /* y */ = 1] }").unwrap_err();
+  assert!(
+    err.message.contains("comment inside a statement"),
+    "{}",
+    err
+  );
+}
+
+#[test]
+fn format_dot_keeps_trailing_comments_with_their_statement() {
+  let dot = "digraph G { a; b // about b\n c [x=1, y=2 /* about y */] }";
+  let formatted = format_dot(dot).unwrap();
+  assert_eq!(
+    formatted,
+    "digraph G {\n  a\n  b // about b\n  c [\n    x=1,\n    y=2 /* about y */\n  ]\n}\n"
+  );
+  assert_same_pipeline(dot, &formatted);
+}
+
+#[test]
+fn format_dot_reports_parse_errors() {
+  let err = format_dot("digraph G { a -> }").unwrap_err();
+  assert!(err.message.contains("expected a target node"), "{}", err);
+}
+
+#[test]
+fn format_dot_round_trips_example_workflows() {
+  let dir = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("examples/workflows");
+  for entry in std::fs::read_dir(dir).unwrap() {
+    let path = entry.unwrap().path();
+    if path.extension().and_then(|e| e.to_str()) != Some("dot") {
+      continue;
+    }
+    let source = std::fs::read_to_string(&path).unwrap();
+    let formatted = format_dot(&source).unwrap();
+    assert_same_pipeline(&source, &formatted);
+  }
+}
+
+proptest! {
+  #[test]
+  fn format_dot_leaves_written_graphs_unchanged(
+    label in any::<String>(),
+    prompt in "[ -~\n\t]{0,120}",
+  ) {
+    let mut g = parse_dot("digraph G { start [shape=Mdiamond] exit [shape=Msquare] start -> work -> exit }").unwrap();
+    let work = g.nodes.get_mut("work").unwrap();
+    work.label = Some(label);
+    work.prompt = Some(prompt);
+    let written = write_dot(&g);
+    prop_assert_eq!(format_dot(&written).unwrap(), written);
+  }
+}
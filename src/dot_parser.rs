@@ -1,156 +1,670 @@
 //! Minimal DOT parser for Attractor pipeline graphs.
 //!
-//! Implements the subset defined in attractor-spec §2.
+//! Implements the subset defined in attractor-spec §2, plus the parts of the Graphviz
+//! grammar that ordinary pipelines use: quoted, numeric and HTML IDs, `+` concatenation,
+//! `node [...]`/`edge [...]` defaults, `key=value` graph attributes, `a -> {b c}` edges and
+//! subgraphs, whose labels become a derived `class` on the nodes inside them.
+//!
+//! Errors are [ParseError]s pointing at the offending source, and no input makes the parser panic.
 
-use crate::types::{AttractorEdge, AttractorGraph, AttractorNode};
+use crate::condition::Condition;
+use crate::types::{AttractorEdge, AttractorGraph, AttractorNode, Attributes};
 use std::collections::HashMap;
+use std::fmt;
+use std::ops::Range;
 use tracing::{info, instrument};
 
+/// Deepest subgraph nesting accepted, so hostile input cannot overflow the stack.
+const MAX_SUBGRAPH_DEPTH: usize = 64;
+
+/// Error from [parse_dot], pointing at the offending part of the source.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+  /// What went wrong, e.g. "expected '=', found ']'".
+  pub message: String,
+  /// What the parser was waiting for, when the error is an unexpected token.
+  pub expected: Option<String>,
+  /// What it found instead, e.g. "']'" or "end of input".
+  pub found: Option<String>,
+  /// Byte range of the offending text in the source.
+  pub span: Range<usize>,
+  /// 1-based line of `span.start`.
+  pub line: usize,
+  /// 1-based column (in characters) of `span.start`.
+  pub column: usize,
+  /// The offending source line with a caret under the error.
+  pub snippet: String,
+}
+
+impl fmt::Display for ParseError {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    write!(
+      f,
+      "{} at line {}, column {}\n{}",
+      self.message, self.line, self.column, self.snippet
+    )
+  }
+}
+
+impl std::error::Error for ParseError {}
+
+/// A parse failure found while reading, positioned by how much input was left at its start.
+/// [parse_dot] turns it into a [ParseError] once it has the whole source.
+#[derive(Debug)]
+pub(crate) struct SyntaxError {
+  message: String,
+  expected: Option<String>,
+  found: Option<String>,
+  pos: Pos,
+}
+
+/// Position of some text as (bytes of input left at its start, length in bytes). Every
+/// remainder the parser works on is a suffix of the source, so this locates it.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Pos {
+  remaining: usize,
+  len: usize,
+}
+
+impl Pos {
+  /// Byte range of the text in `source`, the text the parser was given.
+  pub(crate) fn range(self, source: &str) -> Range<usize> {
+    let start = source.len() - self.remaining;
+    start..start + self.len
+  }
+
+  /// Position of the text of `start` that is not in its suffix `end`, minus trailing space.
+  fn between(start: &str, end: &str) -> Self {
+    let text = &start[..start.len() - end.len()];
+    Self {
+      remaining: start.len(),
+      len: text.trim_end().len(),
+    }
+  }
+}
+
+impl SyntaxError {
+  /// An unexpected token: `expected` was wanted at the start of `at`.
+  fn expected(expected: impl Into<String>, at: &str) -> Self {
+    let at = at.trim_start();
+    let (found, len) = describe_token(at);
+    let expected = expected.into();
+    Self {
+      message: format!("expected {}, found {}", expected, found),
+      expected: Some(expected),
+      found: Some(found),
+      pos: Pos {
+        remaining: at.len(),
+        len,
+      },
+    }
+  }
+
+  /// A well-formed statement with an invalid value (e.g. a bad condition) at `pos`.
+  fn invalid(message: impl Into<String>, pos: Pos) -> Self {
+    Self {
+      message: message.into(),
+      expected: None,
+      found: None,
+      pos,
+    }
+  }
+
+  /// Places the error in `source`, the text the parser was given.
+  fn locate(self, source: &str) -> ParseError {
+    let start = source.len().saturating_sub(self.pos.remaining);
+    let end = (start + self.pos.len).min(source.len());
+    let line_start = source[..start].rfind('\n').map_or(0, |i| i + 1);
+    let line_end = source[start..]
+      .find('\n')
+      .map_or(source.len(), |i| start + i);
+    let line_text = &source[line_start..line_end];
+    let line = source[..start].matches('\n').count() + 1;
+    let before = &source[line_start..start];
+    let column = before.chars().count() + 1;
+    let gutter = line.to_string();
+    let padding: String = before
+      .chars()
+      .map(|c| if c == '\t' { '\t' } else { ' ' })
+      .collect();
+    let carets = source[start..end.min(line_end)].chars().count().max(1);
+    let snippet = format!(
+      "{gutter} | {line_text}\n{blank} | {padding}{carets}",
+      blank = " ".repeat(gutter.len()),
+      carets = "^".repeat(carets)
+    );
+    ParseError {
+      message: self.message,
+      expected: self.expected,
+      found: self.found,
+      span: start..end,
+      line,
+      column,
+      snippet,
+    }
+  }
+}
+
+/// Describes the token at the start of `s` for an error message, with its length in bytes.
+fn describe_token(s: &str) -> (String, usize) {
+  if s.is_empty() {
+    return ("end of input".to_string(), 0);
+  }
+  if s.starts_with("->") {
+    return ("'->'".to_string(), 2);
+  }
+  if s.starts_with('"') {
+    return ("a quoted string".to_string(), 1);
+  }
+  if let Some((word, rest)) = parse_identifier(s) {
+    return (format!("'{}'", word), s.len() - rest.len());
+  }
+  if let Some((number, _)) = parse_number(s) {
+    return (format!("'{}'", number), number.len());
+  }
+  let c = s.chars().next().unwrap_or(' ');
+  (format!("'{}'", c), c.len_utf8())
+}
+
 /// Parse a DOT source string into an AttractorGraph.
 #[instrument(level = "trace", skip(source))]
-pub fn parse_dot(source: &str) -> Result<AttractorGraph, String> {
-  let source = strip_comments(source);
-  let source = source.trim();
+pub fn parse_dot(source: &str) -> Result<AttractorGraph, Box<ParseError>> {
+  parse_dot_syntax(source).map(|(graph, _)| graph)
+}
 
-  if !source.starts_with("digraph") {
-    return Err("Expected 'digraph' at start".to_string());
-  }
+/// Parses DOT source into an AttractorGraph and the syntax tree it was read from.
+#[instrument(level = "trace", skip(source))]
+pub(crate) fn parse_dot_syntax(
+  source: &str,
+) -> Result<(AttractorGraph, DotSyntax), Box<ParseError>> {
+  let stripped = strip_comments(source);
+  let (graph, mut syntax) = parse_stripped(&stripped).map_err(|e| Box::new(e.locate(source)))?;
+  syntax.comments = comment_spans(source);
+  Ok((graph, syntax))
+}
 
-  let rest = source["digraph".len()..].trim_start();
-  let (_name, rest) = parse_identifier(rest).ok_or("Expected graph name")?;
-  let rest = rest.trim_start();
+/// Syntax tree of a DOT file as written, kept for laying the file out again (see
+/// [crate::dot_format]). Each part has its [Pos] in the source, and comments their byte ranges.
+#[derive(Debug)]
+pub(crate) struct DotSyntax {
+  /// From the first keyword to the closing `}`.
+  pub pos: Pos,
+  pub strict: bool,
+  pub name: Option<IdSyntax>,
+  /// From `{` to `}`.
+  pub body: Pos,
+  pub stmts: Vec<StmtSyntax>,
+  /// `//`, `/* */` and `#` comments (see [comment_spans]).
+  pub comments: Vec<Range<usize>>,
+}
+
+/// A statement, from its first token to its last (without a `;` after it).
+#[derive(Debug)]
+pub(crate) struct StmtSyntax {
+  pub pos: Pos,
+  pub kind: StmtKind,
+}
 
+#[derive(Debug)]
+pub(crate) enum StmtKind {
+  /// `graph`, `node` or `edge` followed by attribute lists.
+  Defaults(&'static str, Vec<AttrListSyntax>),
+  /// `key=value`.
+  Assign(IdSyntax, IdSyntax),
+  /// A node or subgraph, or an edge chain like `a -> {b c}`, with attribute lists.
+  Chain(Vec<EndpointSyntax>, Vec<AttrListSyntax>),
+}
+
+#[derive(Debug)]
+pub(crate) enum EndpointSyntax {
+  /// A node ID with its `:port` and `:compass` parts.
+  Node(IdSyntax, Vec<IdSyntax>),
+  Subgraph(SubgraphSyntax),
+}
+
+/// `subgraph [name] { ... }` or an anonymous `{ ... }`.
+#[derive(Debug)]
+pub(crate) struct SubgraphSyntax {
+  /// Whether it starts with the `subgraph` keyword.
+  pub keyword: bool,
+  pub name: Option<IdSyntax>,
+  /// From `{` to `}`.
+  pub body: Pos,
+  pub stmts: Vec<StmtSyntax>,
+}
+
+/// A `[...]` block, from `[` to `]`.
+#[derive(Debug)]
+pub(crate) struct AttrListSyntax {
+  pub pos: Pos,
+  pub attrs: Vec<AttrSyntax>,
+}
+
+/// `key=value` in an attribute list.
+#[derive(Debug)]
+pub(crate) struct AttrSyntax {
+  pub pos: Pos,
+  pub key: IdSyntax,
+  pub value: IdSyntax,
+}
+
+/// An ID as written: one part, or the quoted strings of a `+` concatenation.
+#[derive(Debug)]
+pub(crate) struct IdSyntax {
+  pub parts: Vec<Pos>,
+}
+
+/// Parses comment-free DOT source (see [strip_comments]).
+fn parse_stripped(source: &str) -> Result<(AttractorGraph, DotSyntax), SyntaxError> {
+  let source = source.trim_start();
+  let mut keyword_at = source;
+  let mut header = parse_identifier(keyword_at);
+  if let Some((keyword, rest)) = header
+    && keyword.eq_ignore_ascii_case("strict")
+  {
+    keyword_at = rest.trim_start();
+    header = parse_identifier(keyword_at);
+  }
+  let rest = match header {
+    Some((keyword, rest)) if keyword.eq_ignore_ascii_case("digraph") => rest,
+    _ => return Err(SyntaxError::expected("'digraph'", keyword_at)),
+  };
+  let mut rest = rest.trim_start();
+  let mut name = None;
+  let mut name_syntax = None;
+  if !rest.starts_with('{') {
+    let (graph_name, syntax, after_name) =
+      parse_value(rest).map_err(|_| SyntaxError::expected("graph name or '{'", rest))?;
+    name = Some(graph_name);
+    name_syntax = Some(syntax);
+    rest = after_name.trim_start();
+  }
+
+  let body_start = rest;
   let rest = rest
     .strip_prefix('{')
-    .ok_or("Expected '{' after graph name")?;
+    .ok_or_else(|| SyntaxError::expected("'{'", rest))?;
 
-  let mut graph = AttractorGraph {
-    goal: String::new(),
-    nodes: HashMap::new(),
-    edges: Vec::new(),
-    default_max_retry: 50,
-  };
+  let mut state = ParseState::new(AttractorGraph {
+    name,
+    ..Default::default()
+  });
 
-  let mut remaining = rest.trim();
-  while !remaining.is_empty() && !remaining.starts_with('}') {
-    remaining = parse_statement(remaining, &mut graph)?;
-    remaining = remaining.trim();
+  let mut stmts = Vec::new();
+  let mut remaining = rest.trim_start();
+  while !remaining.starts_with('}') {
+    if remaining.is_empty() {
+      return Err(SyntaxError::expected("'}' to close the graph", remaining));
+    }
+    let (stmt, rest) = parse_statement(remaining, &mut state)?;
+    stmts.push(stmt);
+    remaining = rest.trim_start().trim_start_matches(';').trim_start();
   }
+  let end = &remaining[1..];
 
+  let graph = state.finish()?;
   info!(
     nodes = graph.nodes.len(),
     edges = graph.edges.len(),
     "DOT parse complete"
   );
-  Ok(graph)
+  let syntax = DotSyntax {
+    pos: Pos::between(source, end),
+    strict: keyword_at.len() != source.len(),
+    name: name_syntax,
+    body: Pos::between(body_start, end),
+    stmts,
+    comments: Vec::new(),
+  };
+  Ok((graph, syntax))
+}
+
+/// Nodes and edges collected while parsing, with the `node [...]`/`edge [...]` defaults
+/// in effect. Attributes stay as raw lists until `finish` so later statements can add to them.
+struct ParseState {
+  graph: AttractorGraph,
+  /// Node ids and attributes in declaration order, with where each was last declared.
+  nodes: Vec<(String, AttrList, Pos)>,
+  node_index: HashMap<String, usize>,
+  edges: Vec<(String, String, AttrList, Pos)>,
+  node_defaults: AttrList,
+  edge_defaults: AttrList,
+  /// Subgraphs being parsed, innermost last.
+  subgraphs: Vec<Subgraph>,
+  /// Classes derived from the labels of the subgraphs each node belongs to.
+  derived_classes: HashMap<String, Vec<String>>,
+}
+
+/// A subgraph being parsed: the nodes mentioned in it and its own graph attributes.
+#[derive(Default)]
+struct Subgraph {
+  members: Vec<String>,
+  attrs: AttrList,
+}
+
+impl ParseState {
+  fn new(graph: AttractorGraph) -> Self {
+    Self {
+      graph,
+      nodes: Vec::new(),
+      node_index: HashMap::new(),
+      edges: Vec::new(),
+      node_defaults: Vec::new(),
+      edge_defaults: Vec::new(),
+      subgraphs: Vec::new(),
+      derived_classes: HashMap::new(),
+    }
+  }
+
+  /// Declares `id` at `pos` (taking the current node defaults if it is new) and merges
+  /// `attrs` into it.
+  fn declare_node(&mut self, id: &str, attrs: &[(String, String)], pos: Pos) {
+    let idx = match self.node_index.get(id) {
+      Some(&idx) => idx,
+      None => {
+        self
+          .nodes
+          .push((id.to_string(), self.node_defaults.clone(), pos));
+        self.node_index.insert(id.to_string(), self.nodes.len() - 1);
+        self.nodes.len() - 1
+      }
+    };
+    let node = &mut self.nodes[idx];
+    if !attrs.is_empty() {
+      merge_attrs(&mut node.1, attrs);
+      node.2 = pos;
+    }
+    if let Some(subgraph) = self.subgraphs.last_mut()
+      && !subgraph.members.iter().any(|m| m == id)
+    {
+      subgraph.members.push(id.to_string());
+    }
+  }
+
+  /// Applies graph attributes to the innermost subgraph, or to the graph at top level.
+  fn set_graph_attrs(&mut self, attrs: &[(String, String)]) -> Result<(), String> {
+    match self.subgraphs.last_mut() {
+      Some(subgraph) => merge_attrs(&mut subgraph.attrs, attrs),
+      None => {
+        apply_graph_attrs(attrs, &mut self.graph)?;
+        for (k, v) in attrs {
+          self.graph.attrs.insert(k, v);
+        }
+      }
+    }
+    Ok(())
+  }
+
+  /// Ends the innermost subgraph: gives its members the class derived from its label and
+  /// adds them to the enclosing subgraph. Returns the members.
+  fn end_subgraph(&mut self) -> Vec<String> {
+    let subgraph = self.subgraphs.pop().unwrap_or_default();
+    let class = subgraph
+      .attrs
+      .iter()
+      .find(|(k, _)| k == "label")
+      .map(|(_, label)| derive_class(label))
+      .filter(|class| !class.is_empty());
+    for id in &subgraph.members {
+      if let Some(class) = &class {
+        let classes = self.derived_classes.entry(id.clone()).or_default();
+        if !classes.contains(class) {
+          classes.insert(0, class.clone());
+        }
+      }
+      if let Some(parent) = self.subgraphs.last_mut()
+        && !parent.members.contains(id)
+      {
+        parent.members.push(id.clone());
+      }
+    }
+    subgraph.members
+  }
+
+  /// Adds an edge on top of the current edge defaults, declaring both endpoints.
+  fn add_edge(&mut self, from: &str, to: &str, attrs: &[(String, String)], pos: Pos) {
+    self.declare_node(from, &[], pos);
+    self.declare_node(to, &[], pos);
+    let mut edge_attrs = self.edge_defaults.clone();
+    merge_attrs(&mut edge_attrs, attrs);
+    self
+      .edges
+      .push((from.to_string(), to.to_string(), edge_attrs, pos));
+  }
+
+  /// Builds the nodes and edges into the graph.
+  fn finish(mut self) -> Result<AttractorGraph, SyntaxError> {
+    for (id, attrs, pos) in &self.nodes {
+      let mut node = parse_node_attrs(id, attrs).map_err(|e| SyntaxError::invalid(e, *pos))?;
+      for class in self.derived_classes.remove(id).unwrap_or_default() {
+        if !node.classes.contains(&class) {
+          node.classes.push(class);
+        }
+      }
+      self.graph.nodes.insert(id.clone(), node);
+    }
+    for (from, to, attrs, pos) in &self.edges {
+      let edge = build_edge(from, to, attrs).map_err(|e| SyntaxError::invalid(e, *pos))?;
+      self.graph.edges.push(edge);
+    }
+    Ok(self.graph)
+  }
 }
 
-/// Strips `//` and `/* */` style comments from DOT source.
+/// Sets each attribute in `attrs` on `into`, replacing an existing value for the same key.
+fn merge_attrs(into: &mut AttrList, attrs: &[(String, String)]) {
+  for (k, v) in attrs {
+    match into.iter_mut().find(|(existing, _)| existing == k) {
+      Some(entry) => entry.1 = v.clone(),
+      None => into.push((k.clone(), v.clone())),
+    }
+  }
+}
+
+/// Blanks out `//` and `/* */` comments and `#` preprocessor lines in DOT source. Comment
+/// text becomes spaces (newlines are kept), so byte offsets still match the original source.
+/// Comment markers inside quoted strings are kept.
 #[instrument(level = "trace", skip(s))]
 pub(crate) fn strip_comments(s: &str) -> String {
-  let mut out = String::new();
-  let mut i = 0;
-  let bytes = s.as_bytes();
-  while i < bytes.len() {
-    if i + 1 < bytes.len() && bytes[i] == b'/' && bytes[i + 1] == b'/' {
-      while i < bytes.len() && bytes[i] != b'\n' {
-        i += 1;
+  let mut out = String::with_capacity(s.len());
+  let mut copied = 0;
+  for span in comment_spans(s) {
+    out.push_str(&s[copied..span.start]);
+    for c in s[span.clone()].chars() {
+      if c == '\n' {
+        out.push('\n');
+      } else {
+        out.extend(std::iter::repeat_n(' ', c.len_utf8()));
       }
-      continue;
     }
-    if i + 1 < bytes.len() && bytes[i] == b'/' && bytes[i + 1] == b'*' {
-      i += 2;
-      while i + 1 < bytes.len() && !(bytes[i] == b'*' && bytes[i + 1] == b'/') {
-        i += 1;
-      }
-      if i + 1 < bytes.len() {
-        i += 2;
+    copied = span.end;
+  }
+  out.push_str(&s[copied..]);
+  out
+}
+
+/// Byte ranges of the `//` and `/* */` comments and `#` preprocessor lines in DOT source, in
+/// order. A line comment's range stops before its newline.
+pub(crate) fn comment_spans(s: &str) -> Vec<Range<usize>> {
+  let mut spans = Vec::new();
+  let mut chars = s.char_indices().peekable();
+  let mut in_string = false;
+  let mut line_start = true;
+  while let Some((i, c)) = chars.next() {
+    if in_string {
+      if c == '\\' {
+        chars.next();
+      } else if c == '"' {
+        in_string = false;
       }
       continue;
     }
-    out.push(bytes[i] as char);
-    i += 1;
+    let next = chars.peek().map(|&(_, n)| n);
+    let end = if (c == '/' && next == Some('/')) || (c == '#' && line_start) {
+      s[i..].find('\n').map_or(s.len(), |n| i + n)
+    } else if c == '/' && next == Some('*') {
+      s[i + 2..].find("*/").map_or(s.len(), |n| i + 2 + n + 2)
+    } else {
+      in_string = c == '"';
+      if c == '\n' {
+        line_start = true;
+      } else if !c.is_whitespace() {
+        line_start = false;
+      }
+      continue;
+    };
+    while chars.next_if(|&(j, _)| j < end).is_some() {}
+    spans.push(i..end);
   }
-  out
+  spans
 }
 
-/// Parses an identifier (alphanumeric + underscore) and returns it plus the remaining string.
+/// A [ParseError] with `message` for `span` of `source`, for checks made outside the parser.
+pub(crate) fn error_at(
+  source: &str,
+  span: Range<usize>,
+  message: impl Into<String>,
+) -> Box<ParseError> {
+  let pos = Pos {
+    remaining: source.len() - span.start,
+    len: span.len(),
+  };
+  Box::new(SyntaxError::invalid(message, pos).locate(source))
+}
+
+/// Parses an unquoted identifier (letters, digits, underscore, non-ASCII; not starting with a
+/// digit) and returns it plus the remaining string.
 #[instrument(level = "trace", skip(s))]
 pub(crate) fn parse_identifier(s: &str) -> Option<(&str, &str)> {
   let s = s.trim_start();
-  let start = s
-    .find(|c: char| c.is_ascii_alphabetic() || c == '_')
-    .unwrap_or(0);
-  let end = s[start..]
-    .find(|c: char| !c.is_ascii_alphanumeric() && c != '_')
-    .map(|i| start + i)
-    .unwrap_or(s.len());
-  if start < end {
-    Some((&s[start..end], &s[end..]))
-  } else {
-    None
+  let is_id_char = |c: char| c.is_ascii_alphanumeric() || c == '_' || !c.is_ascii();
+  if !s.starts_with(|c: char| is_id_char(c) && !c.is_ascii_digit()) {
+    return None;
   }
+  let end = s.find(|c: char| !is_id_char(c)).unwrap_or(s.len());
+  Some((&s[..end], &s[end..]))
 }
 
-/// Parses a single graph statement and updates `graph`. Returns the unconsumed remainder.
-#[instrument(level = "trace", skip(graph))]
-fn parse_statement<'a>(mut s: &'a str, graph: &mut AttractorGraph) -> Result<&'a str, String> {
-  s = s.trim_start();
-  if s.starts_with('}') {
-    return Ok(s);
-  }
-  if s.starts_with("graph") {
-    return parse_graph_attrs(s, graph);
-  }
-  if s.starts_with("node") {
-    return skip_attr_block(s);
-  }
-  if s.starts_with("edge") {
-    return skip_attr_block(s);
-  }
-  if s.starts_with("rankdir") {
-    return skip_assign(s);
+/// Parses a single graph statement and records it in `state`. Returns its syntax and the
+/// unconsumed remainder.
+#[instrument(level = "trace", skip(state))]
+fn parse_statement<'a>(
+  s: &'a str,
+  state: &mut ParseState,
+) -> Result<(StmtSyntax, &'a str), SyntaxError> {
+  let s = s.trim_start();
+  let (kind, rest) = parse_statement_kind(s, state)?;
+  let pos = Pos::between(s, rest);
+  Ok((StmtSyntax { pos, kind }, rest))
+}
+
+/// Parses the statement at the start of `s`, which is not whitespace (see [parse_statement]).
+fn parse_statement_kind<'a>(
+  s: &'a str,
+  state: &mut ParseState,
+) -> Result<(StmtKind, &'a str), SyntaxError> {
+  if let Some((keyword, rest)) = parse_identifier(s) {
+    match keyword.to_ascii_lowercase().as_str() {
+      "graph" => return parse_graph_attrs(s, state),
+      "node" => {
+        let (attrs, lists, rest) = parse_attr_lists(rest)?;
+        merge_attrs(&mut state.node_defaults, &attrs);
+        return Ok((StmtKind::Defaults("node", lists), rest));
+      }
+      "edge" => {
+        let (attrs, lists, rest) = parse_attr_lists(rest)?;
+        merge_attrs(&mut state.edge_defaults, &attrs);
+        return Ok((StmtKind::Defaults("edge", lists), rest));
+      }
+      "subgraph" => {
+        let (members, subgraph, rest) = parse_subgraph(s, state)?;
+        let from = EndpointSyntax::Subgraph(subgraph);
+        return parse_edge_stmt(members, from, s, rest, state);
+      }
+      _ => {}
+    }
   }
-  if s.starts_with("subgraph") {
-    return skip_subgraph(s);
+
+  if s.starts_with('{') {
+    let (members, subgraph, rest) = parse_subgraph(s, state)?;
+    let from = EndpointSyntax::Subgraph(subgraph);
+    return parse_edge_stmt(members, from, s, rest, state);
   }
 
-  let (id, rest) = parse_identifier(s).ok_or("Expected identifier")?;
+  let (id, id_syntax, rest) = parse_value(s).map_err(|e| match e.expected {
+    Some(_) => SyntaxError::expected("a statement", s),
+    None => e,
+  })?;
+  let id_pos = Pos::between(s, rest);
   let rest = rest.trim_start();
 
-  if rest.starts_with('[') {
-    let (attrs, rest) = parse_attr_block(rest)?;
-    let node = parse_node_attrs(id, &attrs)?;
-    graph.nodes.insert(id.to_string(), node);
-    return Ok(rest.trim_start().trim_start_matches(';'));
+  if let Some(value) = rest.strip_prefix('=') {
+    let (v, value_syntax, rest) = parse_value(value)?;
+    state
+      .set_graph_attrs(&[(id, v)])
+      .map_err(|e| SyntaxError::invalid(e, Pos::between(s, rest)))?;
+    return Ok((StmtKind::Assign(id_syntax, value_syntax), rest));
   }
 
-  if rest.starts_with("->") {
-    return parse_edge_stmt(id, rest, graph);
+  let (ports, rest) = parse_port(rest)?;
+  let from = EndpointSyntax::Node(id_syntax, ports);
+  if rest.trim_start().starts_with("->") {
+    return parse_edge_stmt(vec![id], from, s, rest, state);
   }
 
-  Ok(rest.trim_start().trim_start_matches(';'))
+  let (attrs, lists, rest) = parse_attr_lists(rest)?;
+  state.declare_node(&id, &attrs, id_pos);
+  Ok((StmtKind::Chain(vec![from], lists), rest))
 }
 
-/// Applies graph-level attributes (goal, default_max_retry) to an AttractorGraph.
+/// Applies graph-level attributes (goal, default_max_retry, retry_policy, retry targets) to an AttractorGraph.
 #[instrument(level = "trace", skip(attrs, graph))]
-pub(crate) fn apply_graph_attrs(attrs: &[(String, String)], graph: &mut AttractorGraph) {
+pub(crate) fn apply_graph_attrs(
+  attrs: &[(String, String)],
+  graph: &mut AttractorGraph,
+) -> Result<(), String> {
   for (k, v) in attrs {
     if k == "goal" {
       graph.goal = v.clone();
     } else if k == "default_max_retry" {
       graph.default_max_retry = v.parse().unwrap_or(50);
+    } else if k == "retry_target" {
+      graph.retry_target = Some(v.clone());
+    } else if k == "fallback_retry_target" {
+      graph.fallback_retry_target = Some(v.clone());
+    } else if k == "retry_policy" {
+      graph.default_retry_policy = v
+        .parse()
+        .map_err(|e| format!("Invalid graph retry_policy {:?}: {}", v, e))?;
     }
   }
+  Ok(())
 }
 
-/// Parses `graph [...]` attributes (e.g. goal, default_max_retry) and updates `graph`.
-#[instrument(level = "trace", skip(graph))]
-fn parse_graph_attrs<'a>(mut s: &'a str, graph: &mut AttractorGraph) -> Result<&'a str, String> {
-  s = s["graph".len()..].trim_start();
-  let (attrs, rest) = parse_attr_block(s)?;
-  apply_graph_attrs(&attrs, graph);
-  Ok(rest.trim_start().trim_start_matches(';'))
+/// Parses `graph [...]` attributes (e.g. goal, default_max_retry) and applies them to the
+/// graph, or to the enclosing subgraph.
+#[instrument(level = "trace", skip(state))]
+fn parse_graph_attrs<'a>(
+  s: &'a str,
+  state: &mut ParseState,
+) -> Result<(StmtKind, &'a str), SyntaxError> {
+  let (attrs, lists, rest) = parse_attr_lists(&s["graph".len()..])?;
+  state
+    .set_graph_attrs(&attrs)
+    .map_err(|e| SyntaxError::invalid(e, Pos::between(s, rest)))?;
+  Ok((StmtKind::Defaults("graph", lists), rest))
+}
+
+/// Derives a class name from a subgraph label: lowercased, spaces replaced by hyphens, and
+/// anything other than letters, digits, `-` and `_` dropped (e.g. "Build & Test" -> "build-test").
+#[instrument(level = "trace")]
+pub(crate) fn derive_class(label: &str) -> String {
+  let kept: String = label
+    .to_lowercase()
+    .chars()
+    .filter(|c| c.is_alphanumeric() || c.is_whitespace() || *c == '-' || *c == '_')
+    .collect();
+  kept.split_whitespace().collect::<Vec<_>>().join("-")
 }
 
 /// List of key-value attribute pairs from DOT `[key=value,...]` blocks.
@@ -177,78 +691,238 @@ pub(crate) fn extract_edge_attrs(
   (label, condition, weight)
 }
 
-/// Parses `[key=value,...]` and returns the attributes plus the remainder.
+/// Builds an `AttractorEdge` from its endpoints and attribute list, parsing its condition.
+#[instrument(level = "trace", skip(attrs))]
+pub(crate) fn build_edge(
+  from: &str,
+  to: &str,
+  attrs: &[(String, String)],
+) -> Result<AttractorEdge, String> {
+  let (label, condition, weight) = extract_edge_attrs(attrs);
+  let condition = condition
+    .map(|c| {
+      Condition::parse(&c).map_err(|e| {
+        format!(
+          "Invalid condition {:?} on edge {} -> {}: {}",
+          c, from, to, e
+        )
+      })
+    })
+    .transpose()?;
+  Ok(AttractorEdge {
+    from_node: from.to_string(),
+    to_node: to.to_string(),
+    label,
+    condition,
+    weight,
+    attrs: attrs.iter().cloned().collect(),
+  })
+}
+
+/// Parses zero or more `[key=value,...]` blocks and returns their attributes, their syntax and
+/// the remainder.
+#[instrument(level = "trace")]
+fn parse_attr_lists(s: &str) -> Result<(AttrList, Vec<AttrListSyntax>, &str), SyntaxError> {
+  let mut attrs = Vec::new();
+  let mut lists = Vec::new();
+  let mut rest = s.trim_start();
+  while rest.starts_with('[') {
+    let (block, syntax, after) = parse_attr_block(rest)?;
+    attrs.extend(block);
+    lists.push(syntax);
+    rest = after;
+  }
+  Ok((attrs, lists, rest))
+}
+
+/// Parses `[key=value,...]` and returns the attributes, their syntax and the remainder.
+/// Attributes may be separated by `,`, `;` or whitespace.
 #[instrument(level = "trace")]
-fn parse_attr_block(s: &str) -> Result<(AttrList, &str), String> {
-  let s = s.trim_start().strip_prefix('[').ok_or("Expected '['")?;
+fn parse_attr_block(s: &str) -> Result<(AttrList, AttrListSyntax, &str), SyntaxError> {
+  let s = s.trim_start();
+  let mut remaining = s
+    .strip_prefix('[')
+    .ok_or_else(|| SyntaxError::expected("'['", s))?
+    .trim_start();
   let mut attrs = Vec::new();
-  let mut remaining = s.trim_start();
+  let mut syntax = Vec::new();
   while !remaining.starts_with(']') {
-    let (k, rest) = parse_identifier(remaining).ok_or("Expected attribute key")?;
-    let rest = rest.trim_start().strip_prefix('=').ok_or("Expected '='")?;
-    let (v, rest) = parse_value(rest.trim_start())?;
-    attrs.push((k.to_string(), v));
-    remaining = rest.trim_start().trim_start_matches(',');
+    if remaining.is_empty() {
+      return Err(SyntaxError::expected(
+        "']' to close the attribute list",
+        remaining,
+      ));
+    }
+    let (k, key, rest) = parse_value(remaining).map_err(|e| match e.expected {
+      Some(_) => SyntaxError::expected("an attribute name or ']'", remaining),
+      None => e,
+    })?;
+    let rest = rest.trim_start();
+    let rest = rest
+      .strip_prefix('=')
+      .ok_or_else(|| SyntaxError::expected(format!("'=' after attribute '{}'", k), rest))?;
+    let (v, value, rest) = parse_value(rest)?;
+    attrs.push((k, v));
+    syntax.push(AttrSyntax {
+      pos: Pos::between(remaining, rest),
+      key,
+      value,
+    });
+    remaining = rest
+      .trim_start()
+      .trim_start_matches([',', ';'])
+      .trim_start();
   }
-  let rest = remaining[1..].trim_start();
-  Ok((attrs, rest))
+  let rest = &remaining[1..];
+  let list = AttrListSyntax {
+    pos: Pos::between(s, rest),
+    attrs: syntax,
+  };
+  Ok((attrs, list, rest.trim_start()))
 }
 
-/// Unescapes DOT quoted string escape sequences (\\n, \\t, \\\", \\\\).
+/// Unescapes DOT quoted string escape sequences (\\n, \\t, \\\", \\\\) and drops
+/// backslash-newline line continuations. Other escapes (e.g. `\l`) are kept as written.
 #[instrument(level = "trace", skip(s))]
 pub(crate) fn unescape_quoted_string(s: &str) -> String {
-  s.replace("\\n", "\n")
-    .replace("\\t", "\t")
-    .replace("\\\"", "\"")
-    .replace("\\\\", "\\")
+  let mut out = String::with_capacity(s.len());
+  let mut chars = s.chars();
+  while let Some(c) = chars.next() {
+    if c != '\\' {
+      out.push(c);
+      continue;
+    }
+    match chars.next() {
+      Some('n') => out.push('\n'),
+      Some('t') => out.push('\t'),
+      Some('"') => out.push('"'),
+      Some('\\') => out.push('\\'),
+      Some('\n') => {}
+      Some(other) => {
+        out.push('\\');
+        out.push(other);
+      }
+      None => out.push('\\'),
+    }
+  }
+  out
 }
 
-/// Parses a quoted string, number, or identifier value and returns it plus the remainder.
+/// Parses a DOT ID used as a value: a quoted string (with `+` concatenation), an HTML
+/// string `<...>`, a number, or an identifier. Returns it, its syntax and the remainder.
 #[instrument(level = "trace", skip(s))]
-pub(crate) fn parse_value(s: &str) -> Result<(String, &str), String> {
+pub(crate) fn parse_value(s: &str) -> Result<(String, IdSyntax, &str), SyntaxError> {
   let s = s.trim_start();
   if s.starts_with('"') {
-    let mut end = 1;
-    while end < s.len() {
-      let c = s.as_bytes()[end];
-      if c == b'\\' && end + 1 < s.len() {
-        end += 2;
-        continue;
-      }
-      if c == b'"' {
-        break;
+    let (mut value, mut rest) = parse_quoted(s)?;
+    let mut parts = vec![Pos::between(s, rest)];
+    while let Some(after_plus) = rest.trim_start().strip_prefix('+') {
+      let after_plus = after_plus.trim_start();
+      if !after_plus.starts_with('"') {
+        return Err(SyntaxError::expected(
+          "a quoted string after '+'",
+          after_plus,
+        ));
       }
-      end += 1;
+      let (next, after) = parse_quoted(after_plus)?;
+      parts.push(Pos::between(after_plus, after));
+      value.push_str(&next);
+      rest = after;
     }
-    let v = unescape_quoted_string(&s[1..end]);
-    Ok((v, s[end + 1..].trim_start()))
-  } else if let Some((num, rest)) = parse_number(s) {
-    Ok((num, rest))
+    Ok((value, IdSyntax { parts }, rest.trim_start()))
   } else {
-    let (id, rest) = parse_identifier(s).ok_or("Expected value")?;
-    Ok((id.to_string(), rest))
+    let (value, rest) = if s.starts_with('<') {
+      parse_html(s)?
+    } else if let Some((num, rest)) = parse_number(s) {
+      (num, rest)
+    } else {
+      let (id, rest) = parse_identifier(s).ok_or_else(|| SyntaxError::expected("a value", s))?;
+      (id.to_string(), rest)
+    };
+    let parts = vec![Pos::between(s, rest)];
+    Ok((value, IdSyntax { parts }, rest))
   }
 }
 
-/// Parses an optional decimal number and returns it plus the remainder.
+/// Parses a double-quoted string starting at `s` and returns its unescaped contents.
+fn parse_quoted(s: &str) -> Result<(String, &str), SyntaxError> {
+  let body = &s[1..];
+  let mut escaped = false;
+  for (i, c) in body.char_indices() {
+    match c {
+      _ if escaped => escaped = false,
+      '\\' => escaped = true,
+      '"' => return Ok((unescape_quoted_string(&body[..i]), &body[i + 1..])),
+      _ => {}
+    }
+  }
+  Err(SyntaxError::invalid(
+    "unterminated quoted string",
+    Pos {
+      remaining: s.len(),
+      len: 1,
+    },
+  ))
+}
+
+/// Parses an HTML-like string `<...>` with balanced angle brackets and returns its contents.
+fn parse_html(s: &str) -> Result<(String, &str), SyntaxError> {
+  let mut depth = 0;
+  for (i, c) in s.char_indices() {
+    match c {
+      '<' => depth += 1,
+      '>' => {
+        depth -= 1;
+        if depth == 0 {
+          return Ok((s[1..i].to_string(), s[i + 1..].trim_start()));
+        }
+      }
+      _ => {}
+    }
+  }
+  Err(SyntaxError::invalid(
+    "unterminated HTML string",
+    Pos {
+      remaining: s.len(),
+      len: 1,
+    },
+  ))
+}
+
+/// Parses an optional DOT numeral (`-?(.[0-9]+|[0-9]+(.[0-9]*)?)`) and returns it plus the remainder.
 #[instrument(level = "trace", skip(s))]
 pub(crate) fn parse_number(s: &str) -> Option<(String, &str)> {
   let s = s.trim_start();
+  let bytes = s.as_bytes();
   let mut end = 0;
-  if end < s.len() && s.as_bytes()[end] == b'-' {
+  if bytes.first() == Some(&b'-') {
     end += 1;
   }
-  while end < s.len() && s.as_bytes()[end].is_ascii_digit() {
+  let digits_start = end;
+  while end < bytes.len() && bytes[end].is_ascii_digit() {
     end += 1;
   }
-  if end > 0 {
+  let mut has_digits = end > digits_start;
+  if end < bytes.len() && bytes[end] == b'.' {
+    let fraction_start = end + 1;
+    let mut fraction_end = fraction_start;
+    while fraction_end < bytes.len() && bytes[fraction_end].is_ascii_digit() {
+      fraction_end += 1;
+    }
+    if has_digits || fraction_end > fraction_start {
+      has_digits = true;
+      end = fraction_end;
+    }
+  }
+  if has_digits {
     Some((s[..end].to_string(), &s[end..]))
   } else {
     None
   }
 }
 
-/// Builds an `AttractorNode` from a node id and its attribute list.
+/// Builds an `AttractorNode` from a node id and its attribute list. Every attribute is kept in
+/// `attrs`; the ones Attractor knows are also parsed into fields.
 #[instrument(level = "trace", skip(attrs))]
 pub(crate) fn parse_node_attrs(
   id: &str,
@@ -260,7 +934,12 @@ pub(crate) fn parse_node_attrs(
   let mut prompt = None;
   let mut command = None;
   let mut goal_gate = false;
-  let mut max_retries = 0u32;
+  let mut max_retries = None;
+  let mut retry_policy = None;
+  let mut retry_target = None;
+  let mut fallback_retry_target = None;
+  let all: Attributes = attrs.iter().cloned().collect();
+  let classes = all.get_list("class").unwrap_or_default();
 
   for (k, v) in attrs {
     match k.as_str() {
@@ -270,7 +949,15 @@ pub(crate) fn parse_node_attrs(
       "prompt" => prompt = Some(v.clone()),
       "command" => command = Some(v.clone()),
       "goal_gate" => goal_gate = v.eq_ignore_ascii_case("true"),
-      "max_retries" => max_retries = v.parse().unwrap_or(0),
+      "max_retries" => max_retries = v.parse().ok(),
+      "retry_target" => retry_target = Some(v.clone()),
+      "fallback_retry_target" => fallback_retry_target = Some(v.clone()),
+      "retry_policy" => {
+        retry_policy = Some(
+          v.parse()
+            .map_err(|e| format!("Invalid retry_policy {:?} on node {}: {}", v, id, e))?,
+        )
+      }
       _ => {}
     }
   }
@@ -286,6 +973,11 @@ pub(crate) fn parse_node_attrs(
     command,
     goal_gate,
     max_retries,
+    retry_policy,
+    retry_target,
+    fallback_retry_target,
+    classes,
+    attrs: all,
   })
 }
 
@@ -309,101 +1001,132 @@ pub(crate) fn resolve_handler_from_shape(shape: &str) -> Option<String> {
   )
 }
 
-/// Parses an edge statement `id -> target [attrs]` and adds edges to `graph`.
-#[instrument(level = "trace", skip(graph))]
+/// Parses the `-> target ...` part of an edge statement that starts at `stmt` with endpoint
+/// `from`, then adds an edge for every pair of nodes in consecutive endpoints (a `{b c}` group
+/// fans out). Without `->` this only consumes a trailing attribute list.
+#[instrument(level = "trace", skip(state, from_syntax))]
 fn parse_edge_stmt<'a>(
-  from: &str,
+  from: Vec<String>,
+  from_syntax: EndpointSyntax,
+  stmt: &'a str,
   mut s: &'a str,
-  graph: &mut AttractorGraph,
-) -> Result<&'a str, String> {
-  let mut targets = Vec::new();
-  s = s["->".len()..].trim_start();
-  loop {
-    let (to, rest) = parse_identifier(s).ok_or("Expected target node")?;
-    targets.push(to.to_string());
+  state: &mut ParseState,
+) -> Result<(StmtKind, &'a str), SyntaxError> {
+  let mut endpoints = vec![from];
+  let mut syntax = vec![from_syntax];
+  while let Some(rest) = s.trim_start().strip_prefix("->") {
     let rest = rest.trim_start();
-    if rest.starts_with('[') {
-      let (attrs, rest) = parse_attr_block(rest)?;
-      let (label, condition, weight) = extract_edge_attrs(&attrs);
-      let mut prev = from;
-      for t in &targets {
-        graph.edges.push(AttractorEdge {
-          from_node: prev.to_string(),
-          to_node: t.clone(),
-          label: label.clone(),
-          condition: condition.clone(),
-          weight,
-        });
-        prev = t;
-      }
-      return Ok(rest.trim_start().trim_start_matches(';'));
-    }
-    if !rest.starts_with("->") {
-      let mut prev = from;
-      for t in &targets {
-        graph.edges.push(AttractorEdge {
-          from_node: prev.to_string(),
-          to_node: t.clone(),
-          label: None,
-          condition: None,
-          weight: 0,
-        });
-        prev = t;
+    let (to, to_syntax, rest) = if rest.starts_with('{')
+      || parse_identifier(rest).is_some_and(|(k, _)| k.eq_ignore_ascii_case("subgraph"))
+    {
+      let (members, subgraph, rest) = parse_subgraph(rest, state)?;
+      (members, EndpointSyntax::Subgraph(subgraph), rest)
+    } else {
+      let (id, id_syntax, after) = parse_value(rest).map_err(|e| match e.expected {
+        Some(_) => SyntaxError::expected("a target node after '->'", rest),
+        None => e,
+      })?;
+      let (ports, after) = parse_port(after)?;
+      (vec![id], EndpointSyntax::Node(id_syntax, ports), after)
+    };
+    endpoints.push(to);
+    syntax.push(to_syntax);
+    s = rest;
+  }
+  let (attrs, lists, rest) = parse_attr_lists(s)?;
+  let pos = Pos::between(stmt, rest);
+  for pair in endpoints.windows(2) {
+    for from in &pair[0] {
+      for to in &pair[1] {
+        state.add_edge(from, to, &attrs, pos);
       }
-      return Ok(rest);
     }
-    s = rest["->".len()..].trim_start();
   }
+  Ok((StmtKind::Chain(syntax, lists), rest))
 }
 
-/// Skips a balanced `[...]` attribute block and returns the remainder.
-#[instrument(level = "trace")]
-fn skip_attr_block(s: &str) -> Result<&str, String> {
+/// Parses `subgraph [name] { ... }` or an anonymous `{ ... }` block. Statements inside see the
+/// enclosing node/edge defaults, and defaults set inside end with the block. Returns the
+/// ids of the nodes in the subgraph (usable as an edge endpoint), its syntax and the remainder.
+#[instrument(level = "trace", skip(state))]
+fn parse_subgraph<'a>(
+  s: &'a str,
+  state: &mut ParseState,
+) -> Result<(Vec<String>, SubgraphSyntax, &'a str), SyntaxError> {
   let s = s.trim_start();
-  let idx = s.find('[').ok_or("Expected '['")?;
-  let mut depth = 0;
-  let _i = idx;
-  for (j, c) in s[idx..].chars().enumerate() {
-    match c {
-      '[' => depth += 1,
-      ']' => {
-        depth -= 1;
-        if depth == 0 {
-          return Ok(&s[idx + j + 1..]);
-        }
-      }
-      _ => {}
+  let mut rest = s;
+  let mut keyword = false;
+  let mut name = None;
+  if let Some((word, after)) = parse_identifier(rest)
+    && word.eq_ignore_ascii_case("subgraph")
+  {
+    keyword = true;
+    rest = after.trim_start();
+    if !rest.starts_with('{') {
+      let (_, name_syntax, after_name) =
+        parse_value(rest).map_err(|_| SyntaxError::expected("a subgraph name or '{'", rest))?;
+      name = Some(name_syntax);
+      rest = after_name.trim_start();
     }
   }
-  Err("Unclosed attribute block".to_string())
-}
-
-/// Skips an assignment `key=value` and returns the remainder.
-/// Parses the value correctly so we don't consume the rest of the file when there's no semicolon.
-#[instrument(level = "trace")]
-fn skip_assign(s: &str) -> Result<&str, String> {
-  let eq = s.find('=').ok_or("Expected '='")?;
-  let rest = s[eq + 1..].trim_start();
-  let (_, after_value) = parse_value(rest)?;
-  Ok(after_value.trim_start().trim_start_matches(';'))
-}
+  let body_start = rest;
+  let mut remaining = rest
+    .strip_prefix('{')
+    .ok_or_else(|| SyntaxError::expected("'{'", rest))?;
+  if state.subgraphs.len() == MAX_SUBGRAPH_DEPTH {
+    return Err(SyntaxError::invalid(
+      format!("subgraphs nested more than {} deep", MAX_SUBGRAPH_DEPTH),
+      Pos::between(rest, remaining),
+    ));
+  }
 
-/// Skips a balanced `{...}` subgraph and returns the remainder.
-#[instrument(level = "trace")]
-fn skip_subgraph(s: &str) -> Result<&str, String> {
-  let start = s.find('{').ok_or("Expected '{'")?;
-  let mut depth = 0;
-  for (i, c) in s[start..].chars().enumerate() {
-    match c {
-      '{' => depth += 1,
-      '}' => {
-        depth -= 1;
-        if depth == 0 {
-          return Ok(&s[start + i + 1..]);
-        }
+  let node_defaults = state.node_defaults.clone();
+  let edge_defaults = state.edge_defaults.clone();
+  state.subgraphs.push(Subgraph::default());
+  let mut stmts = Vec::new();
+  let body = (|| {
+    loop {
+      remaining = remaining.trim_start().trim_start_matches(';').trim_start();
+      if let Some(after) = remaining.strip_prefix('}') {
+        return Ok(after);
       }
-      _ => {}
+      if remaining.is_empty() {
+        return Err(SyntaxError::expected(
+          "'}' to close the subgraph",
+          remaining,
+        ));
+      }
+      let (stmt, rest) = parse_statement(remaining, state)?;
+      stmts.push(stmt);
+      remaining = rest;
     }
+  })();
+  let members = state.end_subgraph();
+  state.node_defaults = node_defaults;
+  state.edge_defaults = edge_defaults;
+  let rest = body?;
+  let syntax = SubgraphSyntax {
+    keyword,
+    name,
+    body: Pos::between(body_start, rest),
+    stmts,
+  };
+  Ok((members, syntax, rest))
+}
+
+/// Parses an optional `:port` or `:port:compass` suffix after a node id. Returns the syntax of
+/// its parts and the remainder.
+fn parse_port(s: &str) -> Result<(Vec<IdSyntax>, &str), SyntaxError> {
+  let mut parts = Vec::new();
+  let mut rest = s.trim_start();
+  for _ in 0..2 {
+    let Some(port) = rest.strip_prefix(':') else {
+      break;
+    };
+    let (_, part, after) =
+      parse_value(port).map_err(|_| SyntaxError::expected("a port name after ':'", port))?;
+    parts.push(part);
+    rest = after.trim_start();
   }
-  Err("Unclosed subgraph".to_string())
+  Ok((parts, rest))
 }
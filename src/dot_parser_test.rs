@@ -1,11 +1,14 @@
 //! Tests for `dot_parser`.
 
 use crate::dot_parser::{
-  apply_graph_attrs, extract_edge_attrs, parse_dot, parse_identifier, parse_node_attrs,
-  parse_number, parse_value, resolve_handler_from_shape, strip_comments, unescape_quoted_string,
+  apply_graph_attrs, derive_class, extract_edge_attrs, parse_dot, parse_identifier,
+  parse_node_attrs, parse_number, parse_value, resolve_handler_from_shape, strip_comments,
+  unescape_quoted_string,
 };
-use crate::types::AttractorGraph;
+use crate::types::{AttractorGraph, Backoff, OutcomeStatus};
+use proptest::prelude::*;
 use std::collections::HashMap;
+use std::time::Duration;
 
 #[test]
 fn parse_default_max_retry() {
@@ -44,7 +47,10 @@ fn parse_edge_with_label_condition_weight() {
     .find(|e| e.from_node == "start" && e.to_node == "exit")
     .unwrap();
   assert_eq!(e.label.as_deref(), Some("ok"));
-  assert_eq!(e.condition.as_deref(), Some("outcome=Success"));
+  assert_eq!(
+    e.condition.as_ref().map(|c| c.as_str()),
+    Some("outcome=Success")
+  );
   assert_eq!(e.weight, 10);
 }
 
@@ -64,7 +70,7 @@ fn parse_rankdir_with_semicolon() {
 }
 
 #[test]
-fn parse_subgraph_skipped() {
+fn parse_subgraph_keeps_nodes() {
   let dot = r#"
     digraph G {
       start [shape=Mdiamond]
@@ -76,6 +82,8 @@ fn parse_subgraph_skipped() {
   let g = parse_dot(dot).unwrap();
   assert!(g.nodes.contains_key("start"));
   assert!(g.nodes.contains_key("exit"));
+  assert_eq!(g.nodes.get("x").unwrap().label.as_deref(), Some("inner"));
+  assert!(g.nodes.get("x").unwrap().classes.is_empty());
 }
 
 #[test]
@@ -91,7 +99,7 @@ fn parse_node_goal_gate_max_retries() {
   let g = parse_dot(dot).unwrap();
   let run = g.nodes.get("run").unwrap();
   assert!(run.goal_gate);
-  assert_eq!(run.max_retries, 3);
+  assert_eq!(run.max_retries, Some(3));
 }
 
 #[test]
@@ -205,13 +213,16 @@ fn parse_simple_dot() {
 fn err_no_digraph() {
   let r = parse_dot("graph foo { }");
   assert!(r.is_err());
-  assert!(r.unwrap_err().contains("digraph"));
+  assert!(r.unwrap_err().to_string().contains("digraph"));
 }
 
 #[test]
 fn strip_comments_removes_line_and_block() {
   let s = "a // line\nb /* block */ c";
-  assert_eq!(strip_comments(s), "a \nb  c");
+  assert_eq!(
+    strip_comments(s),
+    format!("a {}\nb {} c", " ".repeat(7), " ".repeat(11))
+  );
 }
 
 #[test]
@@ -264,6 +275,11 @@ fn apply_graph_attrs_sets_goal_and_max_retry() {
     nodes: HashMap::new(),
     edges: vec![],
     default_max_retry: 50,
+    default_retry_policy: Default::default(),
+    retry_target: None,
+    fallback_retry_target: None,
+    name: None,
+    attrs: Default::default(),
   };
   apply_graph_attrs(
     &[
@@ -271,11 +287,43 @@ fn apply_graph_attrs_sets_goal_and_max_retry() {
       ("default_max_retry".to_string(), "100".to_string()),
     ],
     &mut g,
-  );
+  )
+  .unwrap();
   assert_eq!(g.goal, "test");
   assert_eq!(g.default_max_retry, 100);
 }
 
+#[test]
+fn parse_retry_policy_on_graph_and_node() {
+  let dot = r#"
+    digraph G {
+      graph [retry_policy="fixed delay=1s"]
+      start [shape=Mdiamond]
+      exit [shape=Msquare]
+      push [type=exec, command="true", retry_policy="exponential, delay=100ms, retry_on=retry|error"]
+      other [type=exec, command="true"]
+      start -> push
+      push -> other
+      other -> exit
+    }
+  "#;
+  let g = parse_dot(dot).unwrap();
+  assert_eq!(g.default_retry_policy.backoff, Backoff::Fixed);
+  let push = g.retry_policy_for("push");
+  assert_eq!(push.backoff, Backoff::Exponential);
+  assert_eq!(push.delay, Duration::from_millis(100));
+  assert_eq!(push.retry_on, [OutcomeStatus::Retry, OutcomeStatus::Error]);
+  assert_eq!(g.retry_policy_for("other").delay, Duration::from_secs(1));
+}
+
+#[test]
+fn parse_invalid_retry_policy_is_error() {
+  let dot = r#"digraph G { start [shape=Mdiamond] exit [shape=Msquare] push [retry_policy="sometimes"] start -> exit }"#;
+  let err = parse_dot(dot).unwrap_err().to_string();
+  assert!(err.contains("retry_policy"), "{}", err);
+  assert!(err.contains("push"), "{}", err);
+}
+
 #[test]
 fn extract_edge_attrs_gets_label_condition_weight() {
   let attrs = vec![
@@ -291,10 +339,10 @@ fn extract_edge_attrs_gets_label_condition_weight() {
 
 #[test]
 fn parse_value_parses_quoted_and_number() {
-  let (v, rest) = parse_value("\"hello\"").unwrap();
+  let (v, _, rest) = parse_value("\"hello\"").unwrap();
   assert_eq!(v, "hello");
   assert!(rest.is_empty() || rest.starts_with(' '));
-  let (v, _) = parse_value("123 ").unwrap();
+  let (v, _, _) = parse_value("123 ").unwrap();
   assert_eq!(v, "123");
 }
 
@@ -348,3 +396,398 @@ fn parse_pre_push_dot_all_nodes() {
   assert!(ast.nodes.contains_key("fix_test_coverage"));
   assert!(ast.nodes.contains_key("fix_test_coverage_git_commit"));
 }
+
+#[test]
+fn parse_quoted_and_numeric_ids() {
+  let dot = r#"
+    strict digraph "Release Pipeline" {
+      "start" [shape=Mdiamond]
+      "run tests" [label="Run"]
+      42 [label=<<b>Deploy</b>>]
+      exit [shape=Msquare]
+      start -> "run tests" -> 42 -> exit
+    }
+  "#;
+  let g = parse_dot(dot).unwrap();
+  assert_eq!(
+    g.nodes.get("run tests").unwrap().label.as_deref(),
+    Some("Run")
+  );
+  assert_eq!(
+    g.nodes.get("42").unwrap().label.as_deref(),
+    Some("<b>Deploy</b>")
+  );
+  assert!(
+    g.edges
+      .iter()
+      .any(|e| e.from_node == "run tests" && e.to_node == "42")
+  );
+}
+
+#[test]
+fn parse_default_blocks_apply_to_later_statements() {
+  let dot = r#"
+    digraph G {
+      start [shape=Mdiamond]
+      node [shape=parallelogram, max_retries=2]
+      edge [weight=5]
+      a
+      b [max_retries=7]
+      exit [shape=Msquare]
+      start -> a -> b
+      b -> exit [weight=1]
+    }
+  "#;
+  let g = parse_dot(dot).unwrap();
+  assert_eq!(g.nodes.get("start").unwrap().shape, "Mdiamond");
+  let a = g.nodes.get("a").unwrap();
+  assert_eq!(a.handler_type.as_deref(), Some("tool"));
+  assert_eq!(a.max_retries, Some(2));
+  assert_eq!(g.nodes.get("b").unwrap().max_retries, Some(7));
+  assert_eq!(g.nodes.get("exit").unwrap().shape, "Msquare");
+  let weights: Vec<i32> = g.edges.iter().map(|e| e.weight).collect();
+  assert_eq!(weights, [5, 5, 1]);
+}
+
+#[test]
+fn parse_top_level_graph_attrs() {
+  let dot = r#"
+    digraph G {
+      goal = "Ship " + "it";
+      default_max_retry = 3
+      start [shape=Mdiamond] exit [shape=Msquare]
+      start -> exit
+    }
+  "#;
+  let g = parse_dot(dot).unwrap();
+  assert_eq!(g.goal, "Ship it");
+  assert_eq!(g.default_max_retry, 3);
+  assert!(!g.nodes.contains_key("goal"));
+}
+
+#[test]
+fn parse_edge_groups_fan_out() {
+  let dot = r#"
+    digraph G {
+      start [shape=Mdiamond]
+      exit [shape=Msquare]
+      start -> {lint; test} [label="check"]
+      {lint test} -> exit
+    }
+  "#;
+  let g = parse_dot(dot).unwrap();
+  let pairs: Vec<(&str, &str)> = g
+    .edges
+    .iter()
+    .map(|e| (e.from_node.as_str(), e.to_node.as_str()))
+    .collect();
+  assert_eq!(
+    pairs,
+    [
+      ("start", "lint"),
+      ("start", "test"),
+      ("lint", "exit"),
+      ("test", "exit")
+    ]
+  );
+  assert_eq!(g.edges[1].label.as_deref(), Some("check"));
+  assert!(g.nodes.contains_key("lint") && g.nodes.contains_key("test"));
+}
+
+#[test]
+fn parse_repeated_node_merges_attrs() {
+  let dot = r#"
+    digraph G {
+      start [shape=Mdiamond] exit [shape=Msquare]
+      start -> work:out:e -> exit
+      work [label="Work"][prompt="Do it"; goal_gate=true]
+    }
+  "#;
+  let g = parse_dot(dot).unwrap();
+  let work = g.nodes.get("work").unwrap();
+  assert_eq!(work.label.as_deref(), Some("Work"));
+  assert_eq!(work.prompt.as_deref(), Some("Do it"));
+  assert!(work.goal_gate);
+  assert_eq!(g.edges.len(), 2);
+}
+
+#[test]
+fn parse_keyword_prefixed_node_ids() {
+  let dot = r#"digraph G { start [shape=Mdiamond] node_a [label="A"] edge_case [] graph_check [] exit [shape=Msquare] start -> node_a -> edge_case -> graph_check -> exit }"#;
+  let g = parse_dot(dot).unwrap();
+  assert_eq!(g.nodes.get("node_a").unwrap().label.as_deref(), Some("A"));
+  assert!(g.nodes.contains_key("edge_case"));
+  assert!(g.nodes.contains_key("graph_check"));
+  assert_eq!(g.edges.len(), 4);
+}
+
+#[test]
+fn parse_unterminated_string_is_error() {
+  assert!(parse_value("\"never closed").is_err());
+  assert!(parse_value("<<b>open").is_err());
+  assert!(parse_dot("digraph G { a [label=\"oops] }").is_err());
+}
+
+#[test]
+fn strip_comments_keeps_markers_in_strings() {
+  let s = "# preprocessor\na [command=\"curl http://x /* y */\"] // gone\n";
+  assert_eq!(
+    strip_comments(s),
+    format!(
+      "{}\na [command=\"curl http://x /* y */\"] {}\n",
+      " ".repeat(14),
+      " ".repeat(7)
+    )
+  );
+  assert_eq!(strip_comments("a [label=\"é\"]"), "a [label=\"é\"]");
+}
+
+#[test]
+fn parse_number_accepts_decimals_only_with_digits() {
+  assert_eq!(parse_number("1.5]").unwrap().0, "1.5");
+  assert_eq!(parse_number("-.25").unwrap().0, "-.25");
+  assert!(parse_number("->").is_none());
+  assert!(parse_identifier("9lives").is_none());
+}
+
+#[test]
+fn parse_subgraph_scoped_defaults_and_classes() {
+  let dot = r#"
+    digraph G {
+      goal = "Ship"
+      start [shape=Mdiamond]
+      subgraph cluster_build {
+        label = "Build Stage"
+        node [shape=parallelogram]
+        edge [weight=3]
+        compile -> package
+        subgraph cluster_checks {
+          graph [label="Checks!"]
+          lint [class="fast, style"]
+        }
+      }
+      deploy
+      exit [shape=Msquare]
+      start -> compile
+      lint -> deploy -> exit
+    }
+  "#;
+  let g = parse_dot(dot).unwrap();
+  assert_eq!(g.goal, "Ship");
+  let compile = g.nodes.get("compile").unwrap();
+  assert_eq!(compile.handler_type.as_deref(), Some("tool"));
+  assert_eq!(compile.classes, ["build-stage"]);
+  assert_eq!(
+    g.nodes.get("lint").unwrap().classes,
+    ["fast", "style", "build-stage", "checks"]
+  );
+  let deploy = g.nodes.get("deploy").unwrap();
+  assert_eq!(deploy.shape, "box");
+  assert!(deploy.classes.is_empty());
+  let weight = |from: &str| g.edges.iter().find(|e| e.from_node == from).unwrap().weight;
+  assert_eq!(weight("compile"), 3);
+  assert_eq!(weight("lint"), 0);
+}
+
+#[test]
+fn parse_subgraph_as_edge_endpoint() {
+  let dot = r#"
+    digraph G {
+      start [shape=Mdiamond] exit [shape=Msquare]
+      start -> subgraph checks { label="Checks"; lint test } -> exit
+    }
+  "#;
+  let g = parse_dot(dot).unwrap();
+  assert_eq!(g.edges.len(), 4);
+  assert_eq!(g.nodes.get("test").unwrap().classes, ["checks"]);
+  assert!(g.nodes.get("start").unwrap().classes.is_empty());
+}
+
+#[test]
+fn parse_unclosed_subgraph_is_error() {
+  let e = parse_dot("digraph G { subgraph cluster_a { a -> b ").unwrap_err();
+  assert_eq!(e.expected.as_deref(), Some("'}' to close the subgraph"));
+  assert_eq!(e.found.as_deref(), Some("end of input"));
+}
+
+#[test]
+fn derive_class_from_label() {
+  assert_eq!(derive_class("Loop A"), "loop-a");
+  assert_eq!(derive_class("  Build & Test  "), "build-test");
+  assert_eq!(derive_class("review_gate"), "review_gate");
+}
+
+#[test]
+fn parse_error_has_position_and_snippet() {
+  let dot = "digraph G {\n  start [shape=Mdiamond]\n  run [label]\n}";
+  let e = parse_dot(dot).unwrap_err();
+  assert_eq!(e.expected.as_deref(), Some("'=' after attribute 'label'"));
+  assert_eq!(e.found.as_deref(), Some("']'"));
+  assert_eq!((e.line, e.column), (3, 13));
+  assert_eq!(&dot[e.span.clone()], "]");
+  assert_eq!(e.snippet, "3 |   run [label]\n  |             ^");
+  assert!(
+    e.to_string()
+      .starts_with("expected '=' after attribute 'label', found ']' at line 3, column 13\n")
+  );
+}
+
+#[test]
+fn parse_error_columns_count_comments_and_characters() {
+  let dot = "digraph G { /* é */ a -> ; }";
+  let e = parse_dot(dot).unwrap_err();
+  assert_eq!(e.expected.as_deref(), Some("a target node after '->'"));
+  assert_eq!(e.found.as_deref(), Some("';'"));
+  assert_eq!(e.column, 26);
+  assert_eq!(&dot[e.span], ";");
+}
+
+#[test]
+fn parse_error_for_unterminated_string_points_at_quote() {
+  let dot = "digraph G {\n  a [label=\"oops]\n}";
+  let e = parse_dot(dot).unwrap_err();
+  assert_eq!(e.message, "unterminated quoted string");
+  assert_eq!((e.line, e.column), (2, 12));
+}
+
+#[test]
+fn parse_error_for_missing_close_brace() {
+  let e = parse_dot("digraph G { a -> b").unwrap_err();
+  assert_eq!(e.found.as_deref(), Some("end of input"));
+  assert_eq!(e.span, 18..18);
+}
+
+#[test]
+fn parse_error_for_invalid_value_points_at_statement() {
+  let dot = "digraph G {\n  start -> exit\n  push [retry_policy=\"sometimes\"]\n}";
+  let e = parse_dot(dot).unwrap_err();
+  assert!(e.message.contains("retry_policy"), "{}", e);
+  assert_eq!(e.expected, None);
+  assert_eq!(e.line, 3);
+  assert_eq!(&dot[e.span], "push");
+
+  let dot = "digraph G {\n  a -> b [condition=\"x &&\"]\n}";
+  let e = parse_dot(dot).unwrap_err();
+  assert_eq!(&dot[e.span], "a -> b [condition=\"x &&\"]");
+}
+
+#[test]
+fn parse_rejects_deep_nesting_without_overflow() {
+  let deep = format!("digraph G {{ {} }}", "{".repeat(10_000));
+  assert!(parse_dot(&deep).unwrap_err().message.contains("nested"));
+  let cond = format!(
+    "digraph G {{ a -> b [condition=\"{}\"] }}",
+    "(".repeat(10_000)
+  );
+  assert!(parse_dot(&cond).unwrap_err().message.contains("nested"));
+}
+
+/// Checks that parsing `input` returns rather than panics, and that errors point into it.
+fn parses_without_panic(input: &str) {
+  if let Err(e) = parse_dot(input) {
+    assert!(e.span.start <= e.span.end && e.span.end <= input.len());
+    assert!(input.is_char_boundary(e.span.start) && input.is_char_boundary(e.span.end));
+    assert!(e.line >= 1 && e.column >= 1);
+  }
+}
+
+#[test]
+fn parse_never_panics_on_corpus() {
+  for input in [
+    "",
+    "digraph",
+    "digraph {",
+    "strict",
+    "strict digraph",
+    "digraph G { a [",
+    "digraph G { a [b",
+    "digraph G { a [b=",
+    "digraph G { a -> }",
+    "digraph G { a -> { }",
+    "digraph G { a: }",
+    "digraph G { \"",
+    "digraph G { a [label=\"x\" + ] }",
+    "digraph G { a [label=<<b>] }",
+    "digraph G { - }",
+    "digraph G { -. }",
+    "digraph G { subgraph }",
+    "digraph G { subgraph s",
+    "digraph G { a = }",
+    "digraph G { }}}",
+    "digraph G { é -> ü [label=\"ñ\\\"\"] }",
+    "digraph G { /* unterminated",
+    "# only a comment",
+    "digraph G { a [retry_policy=\"exponential delay=\"] }",
+    "digraph G { a -> b [condition=\"!\"] }",
+  ] {
+    parses_without_panic(input);
+  }
+}
+
+/// A well-formed pipeline used as the seed for mutation properties.
+const SEED: &str = r#"strict digraph "P" {
+  graph [goal="Ship" + " it", retry_policy="fixed delay=1s"]
+  node [shape=box]; edge [weight=2]
+  start [shape=Mdiamond]  // entry
+  subgraph cluster_a { label="Build"; compile -> test:out [condition="outcome=success && x >= 1"] }
+  test -> {lint deploy} [label=<<i>go</i>>]
+  deploy -> exit; exit [shape=Msquare] /* done */
+}"#;
+
+proptest! {
+  #[test]
+  fn parse_never_panics_on_arbitrary_text(input in any::<String>()) {
+    parses_without_panic(&input);
+  }
+
+  #[test]
+  fn parse_never_panics_on_dot_like_text(
+    input in r#"(digraph|strict|subgraph|node|edge|graph|->|[a-z0-9 "<>{}\[\];=,:+\-/*#\n\\.é]){0,40}"#
+  ) {
+    parses_without_panic(&input);
+  }
+
+  #[test]
+  fn parse_never_panics_on_mutated_pipeline(cut in 0..SEED.len(), insert in any::<char>()) {
+    let cut = (0..=cut).rev().find(|&i| SEED.is_char_boundary(i)).unwrap_or(0);
+    parses_without_panic(&SEED[..cut]);
+    parses_without_panic(&format!("{}{}{}", &SEED[..cut], insert, &SEED[cut..]));
+    parses_without_panic(&format!("{}{}", &SEED[..cut], &SEED[(cut + 1).min(SEED.len())..]));
+  }
+}
+
+#[test]
+fn parse_mutation_seed_is_valid() {
+  let g = parse_dot(SEED).unwrap();
+  assert_eq!(g.goal, "Ship it");
+  assert_eq!(g.edges.len(), 4);
+}
+
+#[test]
+fn parse_keeps_all_attributes_in_order() {
+  let dot = r#"
+    digraph G {
+      node [fidelity=full]
+      start [shape=Mdiamond] exit [shape=Msquare]
+      review [timeout="15m", thread_id=loop_a, label="Review", goal_gate=true]
+      start -> review [label="go", loop_restart=true]
+      review -> exit
+    }
+  "#;
+  let g = parse_dot(dot).unwrap();
+  let review = g.nodes.get("review").unwrap();
+  let keys: Vec<&str> = review.attrs.iter().map(|(k, _)| k).collect();
+  assert_eq!(
+    keys,
+    ["fidelity", "timeout", "thread_id", "label", "goal_gate"]
+  );
+  assert_eq!(
+    review.attrs.get_duration("timeout"),
+    Ok(Some(Duration::from_secs(900)))
+  );
+  assert_eq!(review.attrs.get("thread_id"), Some("loop_a"));
+  assert_eq!(review.attrs.get_bool("goal_gate"), Ok(Some(true)));
+  let edge = &g.edges[0];
+  assert_eq!(edge.attrs.get("label"), Some("go"));
+  assert_eq!(edge.attrs.get_bool("loop_restart"), Ok(Some(true)));
+}
@@ -0,0 +1,215 @@
+//! Writes an AttractorGraph back to canonical DOT.
+//!
+//! The output is stable so formatted pipelines give small diffs:
+//! - graph attributes first, in the order written;
+//! - nodes in breadth-first order from the start node along edges, then any others by id;
+//! - edges in their original order, one per line;
+//! - attributes of each node and edge in the order written, with typed fields (label,
+//!   prompt, condition, ...) taking precedence over the raw value.
+//!
+//! IDs are left bare when the parser reads them back unchanged, and quoted otherwise.
+//! Comments, subgraphs and `node [...]`/`edge [...]` defaults are not in the graph, so they are
+//! not written: defaults are written on each node and edge, and subgraph classes as `class`. To
+//! lay out DOT source while keeping them, use [crate::dot_format::format_dot].
+
+use std::borrow::Cow;
+use std::collections::{HashSet, VecDeque};
+
+use crate::dot_parser::resolve_handler_from_shape;
+use crate::types::{AttractorEdge, AttractorGraph, AttractorNode, Attributes};
+use tracing::instrument;
+
+/// Attribute lists longer than this are written one attribute per line.
+pub(crate) const MAX_LINE_WIDTH: usize = 100;
+
+/// Keywords that must be quoted when used as IDs.
+const KEYWORDS: [&str; 6] = ["digraph", "edge", "graph", "node", "strict", "subgraph"];
+
+/// Writes `graph` as canonical DOT.
+#[instrument(level = "trace", skip(graph))]
+pub fn write_dot(graph: &AttractorGraph) -> String {
+  let mut out = String::from("digraph ");
+  if let Some(name) = &graph.name {
+    out.push_str(&quote_id(name));
+    out.push(' ');
+  }
+  out.push_str("{\n");
+
+  let graph_attrs = graph_attributes(graph);
+  if !graph_attrs.is_empty() {
+    write_statement(&mut out, "graph", &graph_attrs);
+    out.push('\n');
+  }
+  for node in node_order(graph) {
+    write_statement(&mut out, &quote_id(&node.id), &node_attributes(node));
+  }
+  if !graph.edges.is_empty() {
+    out.push('\n');
+  }
+  for edge in &graph.edges {
+    let head = format!(
+      "{} -> {}",
+      quote_id(&edge.from_node),
+      quote_id(&edge.to_node)
+    );
+    write_statement(&mut out, &head, &edge_attributes(edge));
+  }
+  out.push_str("}\n");
+  out
+}
+
+/// Returns `id` as written in DOT: bare if it is a plain identifier or number, else quoted.
+#[instrument(level = "trace")]
+pub fn quote_id(id: &str) -> Cow<'_, str> {
+  let identifier = id.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_')
+    && id.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+    && !KEYWORDS.iter().any(|k| id.eq_ignore_ascii_case(k));
+  let number = {
+    let digits = id.strip_prefix('-').unwrap_or(id);
+    !digits.is_empty()
+      && digits.chars().all(|c| c.is_ascii_digit() || c == '.')
+      && digits.chars().filter(|&c| c == '.').count() <= 1
+      && digits != "."
+  };
+  if identifier || number {
+    return Cow::Borrowed(id);
+  }
+  let mut quoted = String::with_capacity(id.len() + 2);
+  quoted.push('"');
+  for c in id.chars() {
+    match c {
+      '"' => quoted.push_str("\\\""),
+      '\\' => quoted.push_str("\\\\"),
+      '\n' => quoted.push_str("\\n"),
+      '\t' => quoted.push_str("\\t"),
+      _ => quoted.push(c),
+    }
+  }
+  quoted.push('"');
+  Cow::Owned(quoted)
+}
+
+/// Writes `head [k=v, ...]`, or just `head` without attributes, as one indented statement.
+fn write_statement(out: &mut String, head: &str, attrs: &Attributes) {
+  out.push_str("  ");
+  out.push_str(head);
+  let pairs: Vec<String> = attrs
+    .iter()
+    .map(|(k, v)| format!("{}={}", quote_id(k), quote_id(v)))
+    .collect();
+  if !pairs.is_empty() {
+    let one_line = format!(" [{}]", pairs.join(", "));
+    if head.len() + one_line.len() + 2 <= MAX_LINE_WIDTH {
+      out.push_str(&one_line);
+    } else {
+      out.push_str(" [\n    ");
+      out.push_str(&pairs.join(",\n    "));
+      out.push_str("\n  ]");
+    }
+  }
+  out.push('\n');
+}
+
+/// Nodes breadth-first from the start node along edges (in edge order), then the rest by id.
+pub(crate) fn node_order(graph: &AttractorGraph) -> Vec<&AttractorNode> {
+  let mut order = Vec::with_capacity(graph.nodes.len());
+  let mut seen = HashSet::new();
+  let mut queue: VecDeque<&str> = graph
+    .find_start()
+    .map(|n| n.id.as_str())
+    .into_iter()
+    .collect();
+  while let Some(id) = queue.pop_front() {
+    let Some(node) = graph.nodes.get(id) else {
+      continue;
+    };
+    if !seen.insert(id) {
+      continue;
+    }
+    order.push(node);
+    queue.extend(graph.outgoing_edges(id).iter().map(|e| e.to_node.as_str()));
+  }
+  let mut rest: Vec<&AttractorNode> = graph
+    .nodes
+    .values()
+    .filter(|n| !seen.contains(n.id.as_str()))
+    .collect();
+  rest.sort_by(|a, b| a.id.cmp(&b.id));
+  order.extend(rest);
+  order
+}
+
+/// Graph attributes as written, plus typed fields that were set without an attribute.
+pub(crate) fn graph_attributes(graph: &AttractorGraph) -> Attributes {
+  let mut attrs = graph.attrs.clone();
+  if !graph.goal.is_empty() || attrs.contains_key("goal") {
+    attrs.insert("goal", graph.goal.as_str());
+  }
+  if graph.default_max_retry != 50 || attrs.contains_key("default_max_retry") {
+    attrs.insert("default_max_retry", graph.default_max_retry.to_string());
+  }
+  set_optional(&mut attrs, "retry_target", graph.retry_target.as_deref());
+  set_optional(
+    &mut attrs,
+    "fallback_retry_target",
+    graph.fallback_retry_target.as_deref(),
+  );
+  attrs
+}
+
+/// Node attributes as written, with the typed fields' current values.
+pub(crate) fn node_attributes(node: &AttractorNode) -> Attributes {
+  let mut attrs = node.attrs.clone();
+  if node.shape != "box" || attrs.contains_key("shape") {
+    attrs.insert("shape", node.shape.as_str());
+  }
+  let implied_type = resolve_handler_from_shape(&node.shape);
+  match &node.handler_type {
+    Some(t) if attrs.contains_key("type") || Some(t) != implied_type.as_ref() => {
+      attrs.insert("type", t.as_str());
+    }
+    _ => {}
+  }
+  match node.label.as_deref() {
+    Some(label) if label != node.id || attrs.contains_key("label") => {
+      attrs.insert("label", label);
+    }
+    _ => {}
+  }
+  set_optional(&mut attrs, "prompt", node.prompt.as_deref());
+  set_optional(&mut attrs, "command", node.command.as_deref());
+  if node.goal_gate || attrs.contains_key("goal_gate") {
+    attrs.insert("goal_gate", node.goal_gate.to_string());
+  }
+  let max_retries = node.max_retries.map(|n| n.to_string());
+  set_optional(&mut attrs, "max_retries", max_retries.as_deref());
+  set_optional(&mut attrs, "retry_target", node.retry_target.as_deref());
+  set_optional(
+    &mut attrs,
+    "fallback_retry_target",
+    node.fallback_retry_target.as_deref(),
+  );
+  if !node.classes.is_empty() {
+    attrs.insert("class", node.classes.join(","));
+  }
+  attrs
+}
+
+/// Edge attributes as written, with the typed fields' current values.
+pub(crate) fn edge_attributes(edge: &AttractorEdge) -> Attributes {
+  let mut attrs = edge.attrs.clone();
+  set_optional(&mut attrs, "label", edge.label.as_deref());
+  let condition = edge.condition.as_ref().map(|c| c.as_str());
+  set_optional(&mut attrs, "condition", condition);
+  if edge.weight != 0 || attrs.contains_key("weight") {
+    attrs.insert("weight", edge.weight.to_string());
+  }
+  attrs
+}
+
+/// Sets `key` to `value` when there is one, keeping the attribute's position.
+fn set_optional(attrs: &mut Attributes, key: &str, value: Option<&str>) {
+  if let Some(value) = value {
+    attrs.insert(key, value);
+  }
+}
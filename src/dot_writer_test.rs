@@ -0,0 +1,165 @@
+//! Tests for `dot_writer`.
+
+use crate::dot_parser::{parse_dot, parse_value};
+use crate::dot_writer::{quote_id, write_dot};
+use crate::types::AttractorGraph;
+use proptest::prelude::*;
+
+/// Node and edge data that must survive a write/parse round trip.
+fn summary(g: &AttractorGraph) -> (Vec<String>, Vec<String>, String) {
+  let mut nodes: Vec<String> = g
+    .nodes
+    .values()
+    .map(|n| {
+      format!(
+        "{} {} {:?} {:?} {:?} {:?} {} {:?} {:?} {:?}",
+        n.id,
+        n.shape,
+        n.handler_type,
+        n.label,
+        n.prompt,
+        n.command,
+        n.goal_gate,
+        n.max_retries,
+        n.retry_target,
+        n.classes
+      )
+    })
+    .collect();
+  nodes.sort();
+  let edges = g
+    .edges
+    .iter()
+    .map(|e| {
+      format!(
+        "{} {} {:?} {:?} {}",
+        e.from_node,
+        e.to_node,
+        e.label,
+        e.condition.as_ref().map(|c| c.as_str()),
+        e.weight
+      )
+    })
+    .collect();
+  (nodes, edges, format!("{} {}", g.goal, g.default_max_retry))
+}
+
+#[test]
+fn write_dot_emits_canonical_layout() {
+  let dot = r#"
+    digraph Simple {
+      rankdir=LR
+      graph [goal="Run tests"]
+      exit [shape=Msquare]
+      report [label="Report", prompt="Say \"done\"\nthen stop"]
+      start [shape=Mdiamond]
+      start -> run -> report -> exit
+      run -> start [condition="outcome=fail", weight=2]
+    }
+  "#;
+  let out = write_dot(&parse_dot(dot).unwrap());
+  assert_eq!(
+    out,
+    r#"digraph Simple {
+  graph [rankdir=LR, goal="Run tests"]
+
+  start [shape=Mdiamond]
+  run
+  report [label=Report, prompt="Say \"done\"\nthen stop"]
+  exit [shape=Msquare]
+
+  start -> run
+  run -> report
+  report -> exit
+  run -> start [condition="outcome=fail", weight=2]
+}
+"#
+  );
+}
+
+#[test]
+fn write_dot_breaks_long_attribute_lists() {
+  let dot = r#"digraph { start [shape=Mdiamond] work [prompt="Implement the feature described in the issue and add unit tests for it before review", max_retries=3] start -> work }"#;
+  let out = write_dot(&parse_dot(dot).unwrap());
+  assert!(out.starts_with("digraph {\n"), "{}", out);
+  assert!(
+    out.contains(
+      "  work [\n    prompt=\"Implement the feature described in the issue and add unit tests for it before review\",\n    max_retries=3\n  ]\n"
+    ),
+    "{}",
+    out
+  );
+}
+
+#[test]
+fn write_dot_flattens_subgraphs_and_defaults() {
+  let dot = r#"
+    digraph G {
+      start [shape=Mdiamond]
+      subgraph cluster_b { label="Build Stage"; node [shape=parallelogram]; compile }
+      start -> compile
+    }
+  "#;
+  let out = write_dot(&parse_dot(dot).unwrap());
+  assert!(
+    out.contains("  compile [shape=parallelogram, class=\"build-stage\"]\n"),
+    "{}",
+    out
+  );
+}
+
+#[test]
+fn write_dot_writes_typed_fields_set_in_code() {
+  let mut g = parse_dot("digraph G { start [shape=Mdiamond] start -> a }").unwrap();
+  g.goal = "Ship".to_string();
+  let a = g.nodes.get_mut("a").unwrap();
+  a.prompt = Some("Do it".to_string());
+  a.goal_gate = true;
+  g.edges[0].weight = 3;
+  let out = write_dot(&g);
+  assert!(out.contains("  graph [goal=Ship]\n"), "{}", out);
+  assert!(
+    out.contains("  a [prompt=\"Do it\", goal_gate=true]\n"),
+    "{}",
+    out
+  );
+  assert!(out.contains("  start -> a [weight=3]\n"), "{}", out);
+}
+
+#[test]
+fn quote_id_leaves_plain_ids_bare() {
+  assert_eq!(quote_id("run_tests"), "run_tests");
+  assert_eq!(quote_id("-1.5"), "-1.5");
+  assert_eq!(quote_id("run tests"), "\"run tests\"");
+  assert_eq!(quote_id("node"), "\"node\"");
+  assert_eq!(quote_id(""), "\"\"");
+  assert_eq!(quote_id("9lives"), "\"9lives\"");
+  assert_eq!(quote_id("a\\b\"c\n"), r#""a\\b\"c\n""#);
+}
+
+proptest! {
+  #[test]
+  fn quote_id_round_trips_through_parser(id in any::<String>()) {
+    let quoted = quote_id(&id);
+    let (parsed, _, rest) = parse_value(&quoted).unwrap();
+    prop_assert_eq!(parsed, id.clone());
+    prop_assert!(rest.is_empty());
+  }
+
+  #[test]
+  fn write_dot_round_trips_node_and_edge_values(
+    label in any::<String>(),
+    prompt in "[ -~\n\t]{0,40}",
+    condition_value in "[a-z]{1,8}",
+  ) {
+    let mut g = parse_dot("digraph G { start [shape=Mdiamond] exit [shape=Msquare] start -> work -> exit }").unwrap();
+    let work = g.nodes.get_mut("work").unwrap();
+    work.label = Some(label);
+    work.prompt = Some(prompt);
+    g.edges[1].condition = Some(format!("outcome={}", condition_value).parse().unwrap());
+    let written = write_dot(&g);
+    let reparsed = parse_dot(&written).unwrap();
+    prop_assert_eq!(summary(&reparsed), summary(&g));
+    prop_assert_eq!(write_dot(&reparsed), written);
+  }
+}
@@ -1,7 +1,9 @@
 //! Load execution.log.json and derive resume state (for --resume when log is single source).
 
-use crate::types::{ExecutionLog, ResumeState};
-use std::path::Path;
+use crate::types::{ExecutionLog, ExecutionStepEntry, NodeOutcome, ResumeState};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
 
 /// Default filename for execution log under a run directory.
 pub const EXECUTION_LOG_FILENAME: &str = "execution.log.json";
@@ -29,6 +31,48 @@ pub fn write_execution_log_partial(path: &Path, log: &ExecutionLog) -> Result<()
   std::fs::write(path, json)
 }
 
+/// Shared step log for one run, cloned into every compiled node that records steps.
+/// Assigns step numbers in recording order; when a path is set, rewrites the partial log
+/// after each step so a crashed run can be resumed.
+#[derive(Clone)]
+pub struct ExecutionLogSink {
+  /// Log being built (steps so far; `finished_at` stays `None` until the runner finishes it).
+  log: Arc<Mutex<ExecutionLog>>,
+  /// Where to persist the partial log after each step.
+  path: Option<PathBuf>,
+}
+
+impl ExecutionLogSink {
+  /// Creates a sink that continues `log` (e.g. steps loaded for resume) and persists to `path`.
+  pub fn new(log: ExecutionLog, path: Option<PathBuf>) -> Self {
+    Self {
+      log: Arc::new(Mutex::new(log)),
+      path,
+    }
+  }
+
+  /// Appends `entry`, overwriting its `step` with the next 1-based index, and persists the partial log.
+  /// Returns the assigned step index.
+  pub fn record(&self, mut entry: ExecutionStepEntry) -> Result<u32, std::io::Error> {
+    let mut log = self.log.lock().unwrap_or_else(|e| e.into_inner());
+    let step = (log.steps.len() + 1) as u32;
+    entry.step = step;
+    if entry.branch.is_none() {
+      log.completed_nodes = entry.completed_nodes_after.clone();
+    }
+    log.steps.push(entry);
+    if let Some(ref path) = self.path {
+      write_execution_log_partial(path, &log)?;
+    }
+    Ok(step)
+  }
+
+  /// Returns a copy of the log recorded so far.
+  pub fn snapshot(&self) -> ExecutionLog {
+    self.log.lock().unwrap_or_else(|e| e.into_inner()).clone()
+  }
+}
+
 /// Resume state derived from an execution log (no checkpoint.json).
 pub struct ResumeFromLog {
   /// State to pass to runner (context, current_node_id, completed_nodes).
@@ -40,6 +84,7 @@ pub struct ResumeFromLog {
 /// Derives resume state from a loaded execution log.
 /// - If `finished_at` is set: run completed; returns state with `current_node_id` set to `exit_node_id` (so runner returns already_completed) and `already_completed: true`.
 /// - If partial (no `finished_at`): returns state from last step and `already_completed: false`.
+///   Parallel branch steps are skipped, so a run stopped inside a parallel node reruns it.
 /// - If log has no steps and no finished_at: returns None.
 pub fn resume_state_from_log(
   log: &ExecutionLog,
@@ -60,11 +105,12 @@ pub fn resume_state_from_log(
           .unwrap_or_default(),
         current_node_id,
         completed_nodes: log.completed_nodes.clone(),
+        node_outcomes: node_outcomes_from_steps(&log.steps),
       },
       already_completed: true,
     });
   }
-  let last = log.steps.last()?;
+  let last = log.steps.iter().rev().find(|s| s.branch.is_none())?;
   let current_node_id = last
     .next_node_id
     .clone()
@@ -75,15 +121,26 @@ pub fn resume_state_from_log(
       context: last.context_after.clone(),
       current_node_id,
       completed_nodes: last.completed_nodes_after.clone(),
+      node_outcomes: node_outcomes_from_steps(&log.steps),
     },
     already_completed: false,
   })
 }
 
+/// Latest outcome per node over the logged steps (goal gate state for resume).
+fn node_outcomes_from_steps(steps: &[ExecutionStepEntry]) -> HashMap<String, NodeOutcome> {
+  steps
+    .iter()
+    .filter(|s| s.branch.is_none())
+    .map(|s| (s.node_id.clone(), s.outcome.clone()))
+    .collect()
+}
+
 #[cfg(test)]
 mod tests {
   use super::{
-    EXECUTION_LOG_FILENAME, load_execution_log, resume_state_from_log, write_execution_log_partial,
+    EXECUTION_LOG_FILENAME, ExecutionLogSink, load_execution_log, resume_state_from_log,
+    write_execution_log_partial,
   };
   use crate::types::{ExecutionLog, ExecutionStepEntry, NodeOutcome};
   use std::collections::HashMap;
@@ -120,6 +177,42 @@ mod tests {
     assert_eq!(loaded.steps.len(), 1);
   }
 
+  #[test]
+  fn execution_log_sink_numbers_steps_and_persists_partial_log() {
+    let dir = tempfile::tempdir().expect("tempdir");
+    let path = dir.path().join("execution.log.json");
+    let sink = ExecutionLogSink::new(
+      ExecutionLog {
+        version: 1,
+        goal: "sink".to_string(),
+        started_at: "2026-02-14T10:00:00Z".to_string(),
+        finished_at: None,
+        final_status: "in_progress".to_string(),
+        completed_nodes: vec![],
+        steps: vec![],
+      },
+      Some(path.clone()),
+    );
+    for (i, node) in ["start", "exit"].iter().enumerate() {
+      let entry = ExecutionStepEntry::new(
+        0,
+        *node,
+        None,
+        HashMap::new(),
+        NodeOutcome::success("ok"),
+        HashMap::new(),
+        None,
+        vec![node.to_string()],
+      );
+      assert_eq!(sink.record(entry).expect("record"), i as u32 + 1);
+    }
+    let loaded = load_execution_log(&path).expect("load");
+    assert_eq!(loaded.steps.len(), 2);
+    assert_eq!(loaded.steps[1].step, 2);
+    assert_eq!(loaded.completed_nodes, vec!["exit"]);
+    assert_eq!(sink.snapshot().steps.len(), 2);
+  }
+
   #[test]
   fn load_execution_log_roundtrip_and_resume_state() {
     let mut ctx: HashMap<String, String> = HashMap::new();
@@ -155,4 +248,37 @@ mod tests {
     assert_eq!(r.resume_state.current_node_id, "exit");
     assert_eq!(r.resume_state.completed_nodes, vec!["start", "exit"]);
   }
+
+  #[test]
+  fn resume_state_skips_parallel_branch_steps() {
+    let step = |node: &str, next: &str, branch: Option<&str>| {
+      ExecutionStepEntry::new(
+        0,
+        node,
+        None,
+        HashMap::new(),
+        NodeOutcome::success("ok"),
+        HashMap::new(),
+        Some(next.to_string()),
+        vec![node.to_string()],
+      )
+      .with_branch(branch.map(String::from))
+    };
+    let log = ExecutionLog {
+      version: 1,
+      goal: "parallel".to_string(),
+      started_at: "2026-02-14T10:00:00Z".to_string(),
+      finished_at: None,
+      final_status: "in_progress".to_string(),
+      completed_nodes: vec![],
+      steps: vec![
+        step("start", "fork", None),
+        step("lint", "join", Some("lint")),
+      ],
+    };
+    let r = resume_state_from_log(&log, Some("exit")).expect("resume state");
+    assert_eq!(r.resume_state.current_node_id, "fork");
+    assert_eq!(r.resume_state.completed_nodes, vec!["start"]);
+    assert!(!r.resume_state.node_outcomes.contains_key("lint"));
+  }
 }
@@ -0,0 +1,353 @@
+//! Node handlers and the registry that picks one for each node by its handler type.
+//!
+//! [HandlerRegistry::default] holds the built-in handlers: `start` and `exit` (pass-through),
+//! `conditional` (routes on its edge conditions only, see [ConditionalHandler]), `exec` (runs
+//! the node's `command`), `tool` (runs a program with JSON arguments, see [crate::tool]),
+//! `codergen` (runs the agent command with the node's `prompt`), `wait.human` (asks on the
+//! console which outgoing edge to take, see [WaitHumanHandler]), and `parallel` /
+//! `parallel.fan_in` (see [crate::parallel]). Callers add their own with
+//! [HandlerRegistry::register] and pass the registry to a run through
+//! [crate::RunOptions::handlers]:
+//!
+//! ```
+//! use async_trait::async_trait;
+//! use streamweave_attractor::handler::{Handler, HandlerRegistry, HandlerServices};
+//! use streamweave_attractor::types::RunContext;
+//! use streamweave_attractor::{AttractorNode, NodeOutcome};
+//!
+//! struct Notify;
+//!
+//! #[async_trait]
+//! impl Handler for Notify {
+//!   async fn execute(
+//!     &self,
+//!     node: &AttractorNode,
+//!     _context: &RunContext,
+//!     _services: &HandlerServices,
+//!   ) -> NodeOutcome {
+//!     NodeOutcome::success(format!("notified for {}", node.id))
+//!   }
+//! }
+//!
+//! let mut handlers = HandlerRegistry::default();
+//! handlers.register("notify", Notify);
+//! assert!(handlers.handler_types().contains(&"notify"));
+//! ```
+
+use std::collections::HashMap;
+use std::fmt;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use crate::interviewer::{Answer, ConsoleInterviewer, Interviewer, Question, QuestionOption};
+use crate::lint::{FnRule, LintRule};
+use crate::nodes::codergen_node::run_codergen;
+use crate::nodes::exec_node::run_exec_command;
+use crate::nodes::step_recorder::StepRecorder;
+use crate::nodes::validate_graph::unregistered_handler_types;
+use crate::parallel::{FAN_IN, FanInHandler, PARALLEL, ParallelHandler};
+use crate::tool::{TOOL, ToolHandler};
+use crate::types::{AttractorGraph, AttractorNode, NodeOutcome, RunContext};
+use async_trait::async_trait;
+use tracing::instrument;
+
+/// Handler type of nodes that do not name one.
+pub const DEFAULT_HANDLER_TYPE: &str = "codergen";
+
+/// Runs one node: does its work and reports the outcome that picks the next edge.
+#[async_trait]
+pub trait Handler: Send + Sync {
+  /// Runs `node` with the run context as it was before the node. Context changes go in the
+  /// outcome's `context_updates`; failures are an error outcome.
+  async fn execute(
+    &self,
+    node: &AttractorNode,
+    context: &RunContext,
+    services: &HandlerServices,
+  ) -> NodeOutcome;
+}
+
+/// What the run gives every handler besides the node and context.
+#[derive(Debug, Clone, Default)]
+pub struct HandlerServices {
+  /// Command for agent (codergen) nodes; codergen fails when unset.
+  pub agent_cmd: Option<String>,
+  /// Directory for agent outcome.json and staging.
+  pub stage_dir: Option<PathBuf>,
+  /// The graph being run, for handlers that look at a node's edges.
+  pub graph: Arc<AttractorGraph>,
+  /// The run's handlers, for handlers that run other nodes.
+  pub handlers: HandlerRegistry,
+  /// Records the steps of nodes a handler runs itself (parallel branches) to the run's
+  /// execution log; None when the run keeps no log.
+  pub(crate) step_recorder: Option<StepRecorder>,
+}
+
+/// Handlers by handler type.
+#[derive(Clone)]
+pub struct HandlerRegistry {
+  handlers: HashMap<String, Arc<dyn Handler>>,
+}
+
+impl Default for HandlerRegistry {
+  /// The built-in handlers: `start`, `exit`, `conditional`, `exec`, `tool`, `codergen`,
+  /// `wait.human` asking on the console, `parallel` and `parallel.fan_in`.
+  fn default() -> Self {
+    let mut registry = Self::empty();
+    registry
+      .register("start", PassThroughHandler::new("Start"))
+      .register("exit", PassThroughHandler::new("Exit"))
+      .register(CONDITIONAL, ConditionalHandler)
+      .register("exec", ExecHandler)
+      .register(TOOL, ToolHandler)
+      .register(DEFAULT_HANDLER_TYPE, CodergenHandler)
+      .register(
+        "wait.human",
+        WaitHumanHandler::new(Arc::new(ConsoleInterviewer)),
+      )
+      .register(PARALLEL, ParallelHandler)
+      .register(FAN_IN, FanInHandler);
+    registry
+  }
+}
+
+impl fmt::Debug for HandlerRegistry {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    f.debug_tuple("HandlerRegistry")
+      .field(&self.handler_types())
+      .finish()
+  }
+}
+
+impl HandlerRegistry {
+  /// A registry with no handlers, not even the built-in ones.
+  pub fn empty() -> Self {
+    Self {
+      handlers: HashMap::new(),
+    }
+  }
+
+  /// Handles nodes of `handler_type` with `handler`, replacing any handler already registered
+  /// for that type.
+  pub fn register(
+    &mut self,
+    handler_type: impl Into<String>,
+    handler: impl Handler + 'static,
+  ) -> &mut Self {
+    self.handlers.insert(handler_type.into(), Arc::new(handler));
+    self
+  }
+
+  /// Handler for `handler_type`, if one is registered.
+  pub fn get(&self, handler_type: &str) -> Option<Arc<dyn Handler>> {
+    self.handlers.get(handler_type).cloned()
+  }
+
+  /// Handler for `node`, by its handler type ([DEFAULT_HANDLER_TYPE] when it has none).
+  pub fn resolve(&self, node: &AttractorNode) -> Result<Arc<dyn Handler>, String> {
+    let handler_type = handler_type_of(node);
+    self.get(handler_type).ok_or_else(|| {
+      format!(
+        "no handler registered for type '{}' (node '{}')",
+        handler_type, node.id
+      )
+    })
+  }
+
+  /// Registered handler types, sorted.
+  pub fn handler_types(&self) -> Vec<&str> {
+    let mut types: Vec<&str> = self.handlers.keys().map(String::as_str).collect();
+    types.sort();
+    types
+  }
+
+  /// The `type_known` lint rule for this registry: a node whose handler type has no handler
+  /// here is an error. Register it on a [crate::lint::LintRegistry] to replace the built-in
+  /// rule, which only knows the built-in handlers.
+  pub fn lint_rule(&self) -> impl LintRule + 'static {
+    let handlers = self.clone();
+    FnRule::new("type_known", move |graph| {
+      unregistered_handler_types(graph, &handlers)
+    })
+  }
+}
+
+/// Handler type of `node`: its `handler_type`, else [DEFAULT_HANDLER_TYPE].
+pub(crate) fn handler_type_of(node: &AttractorNode) -> &str {
+  node.handler_type.as_deref().unwrap_or(DEFAULT_HANDLER_TYPE)
+}
+
+/// Succeeds without doing anything; the `start` and `exit` handler.
+pub struct PassThroughHandler {
+  notes: String,
+}
+
+impl PassThroughHandler {
+  /// A handler whose outcome carries `notes`.
+  pub fn new(notes: impl Into<String>) -> Self {
+    Self {
+      notes: notes.into(),
+    }
+  }
+}
+
+#[async_trait]
+impl Handler for PassThroughHandler {
+  async fn execute(&self, _: &AttractorNode, _: &RunContext, _: &HandlerServices) -> NodeOutcome {
+    NodeOutcome::success(self.notes.clone())
+  }
+}
+
+/// Handler type of `diamond` nodes, which only route.
+pub const CONDITIONAL: &str = "conditional";
+
+/// Succeeds at once without side effects, so the node's outgoing edge conditions route purely
+/// on the context as the nodes before it left it. `outcome` in those conditions is this node's
+/// own and always success; route on context keys instead. A conditional node whose edges are
+/// all conditional and none can match fails validation (`conditional_routes`).
+pub struct ConditionalHandler;
+
+#[async_trait]
+impl Handler for ConditionalHandler {
+  async fn execute(
+    &self,
+    node: &AttractorNode,
+    _: &RunContext,
+    _: &HandlerServices,
+  ) -> NodeOutcome {
+    NodeOutcome::success(format!("routing from {}", node.id))
+  }
+}
+
+/// Runs the node's `command` with `sh -c`: success on exit 0, retry on exit 75, error
+/// otherwise.
+pub struct ExecHandler;
+
+#[async_trait]
+impl Handler for ExecHandler {
+  #[instrument(level = "trace", skip_all, fields(node = %node.id))]
+  async fn execute(
+    &self,
+    node: &AttractorNode,
+    _: &RunContext,
+    _: &HandlerServices,
+  ) -> NodeOutcome {
+    let Some(command) = node.command.clone() else {
+      return NodeOutcome::error(format!(
+        "exec node '{}' requires a command attribute",
+        node.id
+      ));
+    };
+    run_exec_command(&command).await
+  }
+}
+
+/// Runs the agent command with the node's `prompt` as stdin and reads its outcome.json.
+pub struct CodergenHandler;
+
+#[async_trait]
+impl Handler for CodergenHandler {
+  #[instrument(level = "trace", skip_all, fields(node = %node.id))]
+  async fn execute(
+    &self,
+    node: &AttractorNode,
+    _: &RunContext,
+    services: &HandlerServices,
+  ) -> NodeOutcome {
+    let prompt = node.prompt.as_deref().unwrap_or_default();
+    run_codergen(
+      services.agent_cmd.as_deref(),
+      prompt,
+      services.stage_dir.as_deref(),
+    )
+    .await
+  }
+}
+
+/// Human gate: asks an [Interviewer] which outgoing edge to take and routes on the answer.
+///
+/// The question is the node's label (or id) with one option per outgoing edge, keyed by the
+/// edge label's accelerator (`[A] Approve`). The choice becomes the outcome's
+/// `preferred_label` and suggested next node, and is recorded in the context as
+/// `human.gate.selected` (key) and `human.gate.label`. With a `timeout` attribute the gate stops
+/// waiting after that long and takes `human.default_choice`, or fails when there is none, so an
+/// unattended run takes the fail edge instead of asking again. A skipped question fails the node.
+pub struct WaitHumanHandler {
+  interviewer: Arc<dyn Interviewer>,
+}
+
+impl WaitHumanHandler {
+  /// A gate that asks `interviewer`.
+  pub fn new(interviewer: Arc<dyn Interviewer>) -> Self {
+    Self { interviewer }
+  }
+}
+
+#[async_trait]
+impl Handler for WaitHumanHandler {
+  #[instrument(level = "trace", skip_all, fields(node = %node.id))]
+  async fn execute(
+    &self,
+    node: &AttractorNode,
+    _: &RunContext,
+    services: &HandlerServices,
+  ) -> NodeOutcome {
+    let mut question = Question {
+      node_id: node.id.clone(),
+      text: node.label.clone().unwrap_or_else(|| node.id.clone()),
+      options: services
+        .graph
+        .outgoing_edges(&node.id)
+        .into_iter()
+        .map(|e| QuestionOption::new(e.label.as_deref().unwrap_or(&e.to_node), &e.to_node))
+        .collect(),
+      default: None,
+    };
+    if question.options.is_empty() {
+      return NodeOutcome::error(format!(
+        "wait.human node '{}' has no outgoing edges to choose from",
+        node.id
+      ));
+    }
+    if let Some(choice) = node.attrs.get("human.default_choice") {
+      let Some(option) = question.find(choice) else {
+        return NodeOutcome::error(format!(
+          "human.default_choice '{}' of node '{}' matches no outgoing edge",
+          choice, node.id
+        ));
+      };
+      question.default = Some(option.key.clone());
+    }
+    let timeout = match node.attrs.get_duration("timeout") {
+      Ok(timeout) => timeout,
+      Err(e) => return NodeOutcome::error(e),
+    };
+    let answer = match timeout {
+      Some(timeout) => tokio::time::timeout(timeout, self.interviewer.ask(&question))
+        .await
+        .unwrap_or(Answer::Timeout),
+      None => self.interviewer.ask(&question).await,
+    };
+    let choice = match answer {
+      Answer::Selected(choice) => choice,
+      Answer::Timeout => match question.default.clone() {
+        Some(default) => default,
+        None => return NodeOutcome::error("human gate timed out with no default choice"),
+      },
+      Answer::Skipped => return NodeOutcome::error("human gate skipped"),
+    };
+    let Some(option) = question.find(&choice) else {
+      return NodeOutcome::error(format!("answer '{}' matches no option", choice));
+    };
+    let mut outcome = NodeOutcome::success(format!("human chose {}", option.label));
+    outcome.preferred_label = Some(option.label.clone());
+    outcome.suggested_next_ids = vec![option.next_node.clone()];
+    outcome
+      .context_updates
+      .insert("human.gate.selected".to_string(), option.key.clone());
+    outcome
+      .context_updates
+      .insert("human.gate.label".to_string(), option.label.clone());
+    outcome
+  }
+}
@@ -0,0 +1,273 @@
+//! Tests for `handler`.
+
+use std::sync::Arc;
+
+use crate::dot_parser::parse_dot;
+use crate::handler::{
+  CodergenHandler, ConditionalHandler, ExecHandler, Handler, HandlerRegistry, HandlerServices,
+  PassThroughHandler, WaitHumanHandler,
+};
+use crate::interviewer::{Answer, Interviewer, Question, QueueInterviewer};
+use crate::lint::LintRegistry;
+use crate::types::{AttractorNode, NodeOutcome, OutcomeStatus, RunContext};
+use async_trait::async_trait;
+
+/// Echoes a context key back as the outcome notes.
+struct Echo;
+
+#[async_trait]
+impl Handler for Echo {
+  async fn execute(
+    &self,
+    node: &AttractorNode,
+    context: &RunContext,
+    _services: &HandlerServices,
+  ) -> NodeOutcome {
+    let said = context.get("say").cloned().unwrap_or_default();
+    NodeOutcome::success(format!("{}: {}", node.id, said))
+  }
+}
+
+fn node(id: &str, handler_type: Option<&str>) -> AttractorNode {
+  AttractorNode {
+    id: id.to_string(),
+    shape: "box".to_string(),
+    handler_type: handler_type.map(String::from),
+    label: None,
+    prompt: None,
+    command: None,
+    goal_gate: false,
+    max_retries: None,
+    retry_policy: None,
+    retry_target: None,
+    fallback_retry_target: None,
+    classes: Vec::new(),
+    attrs: Default::default(),
+  }
+}
+
+#[test]
+fn default_registry_has_builtin_handlers() {
+  let handlers = HandlerRegistry::default();
+  assert_eq!(
+    handlers.handler_types(),
+    [
+      "codergen",
+      "conditional",
+      "exec",
+      "exit",
+      "parallel",
+      "parallel.fan_in",
+      "start",
+      "tool",
+      "wait.human"
+    ]
+  );
+  assert!(HandlerRegistry::empty().handler_types().is_empty());
+}
+
+#[test]
+fn resolve_defaults_to_codergen_and_rejects_unknown_types() {
+  let handlers = HandlerRegistry::default();
+  assert!(handlers.resolve(&node("a", None)).is_ok());
+  let err = handlers.resolve(&node("b", Some("notify"))).err().unwrap();
+  assert_eq!(err, "no handler registered for type 'notify' (node 'b')");
+}
+
+#[tokio::test]
+async fn register_adds_or_replaces_handler() {
+  let mut handlers = HandlerRegistry::default();
+  handlers
+    .register("notify", Echo)
+    .register("exec", PassThroughHandler::new("skipped"));
+  let context: RunContext = [("say".to_string(), "hi".to_string())].into();
+  let services = HandlerServices::default();
+
+  let notify = handlers.resolve(&node("n", Some("notify"))).unwrap();
+  let out = notify
+    .execute(&node("n", Some("notify")), &context, &services)
+    .await;
+  assert_eq!(out.notes.as_deref(), Some("n: hi"));
+
+  let exec = handlers.get("exec").unwrap();
+  let out = exec
+    .execute(&node("run", Some("exec")), &context, &services)
+    .await;
+  assert_eq!(out.status, OutcomeStatus::Success);
+  assert_eq!(out.notes.as_deref(), Some("skipped"));
+}
+
+#[tokio::test]
+async fn exec_handler_reports_exit_status() {
+  let services = HandlerServices::default();
+  let mut run = node("run", Some("exec"));
+  run.command = Some("exit 3".to_string());
+  let out = ExecHandler
+    .execute(&run, &RunContext::new(), &services)
+    .await;
+  assert_eq!(out.status, OutcomeStatus::Error);
+  run.command = None;
+  let out = ExecHandler
+    .execute(&run, &RunContext::new(), &services)
+    .await;
+  assert_eq!(
+    out.failure_reason.as_deref(),
+    Some("exec node 'run' requires a command attribute")
+  );
+}
+
+#[tokio::test]
+async fn codergen_handler_fails_when_the_agent_cannot_start() {
+  let dir = tempfile::tempdir().unwrap();
+  let services = HandlerServices {
+    agent_cmd: Some("no-such-agent-xyz --print".to_string()),
+    stage_dir: Some(dir.path().to_path_buf()),
+    ..Default::default()
+  };
+  let out = CodergenHandler
+    .execute(&node("work", None), &RunContext::new(), &services)
+    .await;
+  assert_eq!(out.status, OutcomeStatus::Error);
+  assert!(
+    out
+      .failure_reason
+      .as_deref()
+      .unwrap()
+      .starts_with("agent spawn:")
+  );
+}
+
+#[tokio::test]
+async fn conditional_handler_succeeds_without_touching_context() {
+  let context: RunContext = [("outcome".to_string(), "Error".to_string())].into();
+  let out = ConditionalHandler
+    .execute(
+      &node("check", Some("conditional")),
+      &context,
+      &HandlerServices::default(),
+    )
+    .await;
+  assert_eq!(out.status, OutcomeStatus::Success);
+  assert!(out.context_updates.is_empty());
+  assert!(out.preferred_label.is_none() && out.suggested_next_ids.is_empty());
+}
+
+#[test]
+fn lint_rule_accepts_registered_types() {
+  let graph = parse_dot(
+    r#"digraph G {
+      start [shape=Mdiamond]
+      exit [shape=Msquare]
+      ping [type="notify", prompt="Tell the team"]
+      start -> ping -> exit
+    }"#,
+  )
+  .unwrap();
+  let builtin = LintRegistry::default().validate(&graph);
+  assert_eq!(builtin.len(), 1);
+  assert_eq!(builtin[0].rule, "type_known");
+  assert!(builtin[0].message.contains("'notify'"));
+
+  let mut handlers = HandlerRegistry::default();
+  handlers.register("notify", Echo);
+  let mut lints = LintRegistry::default();
+  lints.register(handlers.lint_rule());
+  assert!(lints.validate(&graph).is_empty());
+}
+
+/// Never answers.
+struct Silent;
+
+#[async_trait]
+impl Interviewer for Silent {
+  async fn ask(&self, _question: &Question) -> Answer {
+    std::future::pending().await
+  }
+}
+
+/// Services for the human gate `review` (edges to `ship` and `rework`), with `attrs` on it.
+fn review_gate(attrs: &str) -> (AttractorNode, HandlerServices) {
+  let graph = parse_dot(&format!(
+    r#"digraph G {{
+      start [shape=Mdiamond]
+      exit [shape=Msquare]
+      review [shape=hexagon, label="Ship it?"{}]
+      ship [type=exec, command="true"]
+      rework [type=exec, command="true"]
+      start -> review
+      review -> ship [label="[A] Approve"]
+      review -> rework [label="[R] Revise"]
+      ship -> exit
+      rework -> exit
+    }}"#,
+    attrs
+  ))
+  .unwrap();
+  let node = graph.nodes["review"].clone();
+  let services = HandlerServices {
+    graph: Arc::new(graph),
+    ..Default::default()
+  };
+  (node, services)
+}
+
+async fn ask(handler: WaitHumanHandler, attrs: &str) -> NodeOutcome {
+  let (node, services) = review_gate(attrs);
+  handler.execute(&node, &RunContext::new(), &services).await
+}
+
+#[tokio::test]
+async fn wait_human_routes_on_chosen_edge() {
+  let interviewer = Arc::new(QueueInterviewer::selecting(["r"]));
+  let out = ask(WaitHumanHandler::new(interviewer.clone()), "").await;
+  assert_eq!(interviewer.remaining(), 0);
+  assert_eq!(out.status, OutcomeStatus::Success);
+  assert_eq!(out.preferred_label.as_deref(), Some("[R] Revise"));
+  assert_eq!(out.suggested_next_ids, ["rework"]);
+  assert_eq!(out.context_updates["human.gate.selected"], "R");
+  assert_eq!(out.context_updates["human.gate.label"], "[R] Revise");
+}
+
+#[tokio::test]
+async fn wait_human_timeout_takes_default_choice() {
+  let handler = WaitHumanHandler::new(Arc::new(Silent));
+  let out = ask(
+    handler,
+    r#", timeout="20ms", "human.default_choice"="approve""#,
+  )
+  .await;
+  assert_eq!(out.status, OutcomeStatus::Success);
+  assert_eq!(out.suggested_next_ids, ["ship"]);
+
+  let handler = WaitHumanHandler::new(Arc::new(QueueInterviewer::new([Answer::Timeout])));
+  let out = ask(handler, "").await;
+  assert_eq!(out.status, OutcomeStatus::Error);
+  assert_eq!(
+    out.failure_reason.as_deref(),
+    Some("human gate timed out with no default choice")
+  );
+}
+
+#[tokio::test]
+async fn wait_human_fails_on_skip_or_unknown_answer() {
+  let handler = WaitHumanHandler::new(Arc::new(QueueInterviewer::new([Answer::Skipped])));
+  let out = ask(handler, "").await;
+  assert_eq!(out.status, OutcomeStatus::Error);
+  assert_eq!(out.failure_reason.as_deref(), Some("human gate skipped"));
+
+  let handler = WaitHumanHandler::new(Arc::new(QueueInterviewer::selecting(["maybe"])));
+  let out = ask(handler, "").await;
+  assert_eq!(
+    out.failure_reason.as_deref(),
+    Some("answer 'maybe' matches no option")
+  );
+
+  let handler = WaitHumanHandler::new(Arc::new(Silent));
+  let out = ask(handler, r#", "human.default_choice"="later""#).await;
+  assert!(
+    out
+      .failure_reason
+      .unwrap()
+      .contains("matches no outgoing edge")
+  );
+}
@@ -0,0 +1,347 @@
+//! Asking a human to choose, for `wait.human` nodes.
+//!
+//! A human gate builds a [Question] from its label and outgoing edges and asks an
+//! [Interviewer]. The choices here: [ConsoleInterviewer] asks on the terminal,
+//! [AutoApproveInterviewer] always takes the default (or first) choice, [QueueInterviewer]
+//! answers from a fixed list (for tests and scripted runs), and [FileDropInterviewer] writes
+//! the question to a directory and waits for an answer file.
+
+use std::collections::VecDeque;
+use std::io::{BufRead, Write};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Mutex, OnceLock};
+use std::time::Duration;
+
+use crate::nodes::select_edge::normalize_label;
+use async_trait::async_trait;
+use serde::Serialize;
+use tokio::sync::mpsc;
+use tracing::instrument;
+
+/// A multiple-choice question from a human gate.
+#[derive(Debug, Clone, Serialize)]
+pub struct Question {
+  /// Id of the asking node.
+  pub node_id: String,
+  /// What to ask: the node's label, or its id when it has none.
+  pub text: String,
+  /// One choice per outgoing edge, in edge order.
+  pub options: Vec<QuestionOption>,
+  /// Key of the choice taken on timeout, when the node sets one.
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub default: Option<String>,
+}
+
+/// One choice of a [Question].
+#[derive(Debug, Clone, Serialize)]
+pub struct QuestionOption {
+  /// Short key to answer with: the accelerator of the edge label (`[A] Approve` → `A`), else
+  /// its first character.
+  pub key: String,
+  /// Edge label, or the target node id when the edge has none.
+  pub label: String,
+  /// Node the choice leads to.
+  pub next_node: String,
+}
+
+impl QuestionOption {
+  /// The choice for an edge to `next_node` labelled `label`.
+  pub fn new(label: impl Into<String>, next_node: impl Into<String>) -> Self {
+    let label = label.into();
+    Self {
+      key: accelerator_key(&label),
+      label,
+      next_node: next_node.into(),
+    }
+  }
+}
+
+impl Question {
+  /// The option `answer` names: by key (any case), by label (ignoring accelerators and case),
+  /// or by next node id.
+  pub fn find(&self, answer: &str) -> Option<&QuestionOption> {
+    let answer = answer.trim();
+    let normalized = normalize_label(answer);
+    self
+      .options
+      .iter()
+      .find(|o| o.key.eq_ignore_ascii_case(answer))
+      .or_else(|| {
+        self
+          .options
+          .iter()
+          .find(|o| normalize_label(&o.label) == normalized || o.next_node == answer)
+      })
+  }
+
+  /// The question as shown to a person: the text, then one line per option, its label prefixed
+  /// with `[key]` unless the label already starts with its accelerator.
+  pub fn prompt(&self) -> String {
+    let mut prompt = self.text.clone();
+    for option in &self.options {
+      let label = option.label.trim();
+      if normalize_label(label) == label.to_lowercase() {
+        prompt.push_str(&format!("\n  [{}] {}", option.key, label));
+      } else {
+        prompt.push_str(&format!("\n  {}", label));
+      }
+    }
+    if let Some(ref default) = self.default {
+      prompt.push_str(&format!("\n(default: {})", default));
+    }
+    prompt
+  }
+}
+
+/// How a question was answered.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Answer {
+  /// The person chose; the text is matched with [Question::find].
+  Selected(String),
+  /// Nobody answered in time.
+  Timeout,
+  /// The person declined to answer, or there was nobody to ask.
+  Skipped,
+}
+
+/// Puts a [Question] to a human and returns their answer.
+#[async_trait]
+pub trait Interviewer: Send + Sync {
+  async fn ask(&self, question: &Question) -> Answer;
+}
+
+/// Accelerator key of an edge label: `K` in `[K] Label`, `K) Label` or `K - Label`, else the
+/// label's first character. Upper-cased.
+pub(crate) fn accelerator_key(label: &str) -> String {
+  let label = label.trim();
+  let key = if let Some(bracketed) = label.strip_prefix('[') {
+    bracketed.split_once(']').map(|(k, _)| k.trim())
+  } else {
+    let mut chars = label.char_indices();
+    match (chars.next(), chars.as_str().trim_start().chars().next()) {
+      (Some((_, c)), Some(')' | '-')) if c.is_alphanumeric() => Some(&label[..c.len_utf8()]),
+      _ => None,
+    }
+  };
+  match key {
+    Some(k) if !k.is_empty() => k.to_uppercase(),
+    _ => label
+      .chars()
+      .next()
+      .map(|c| c.to_uppercase().to_string())
+      .unwrap_or_default(),
+  }
+}
+
+/// Asks on the terminal: prints the question to stderr and reads the answer from stdin,
+/// asking again until it names an option. End of input skips the question.
+///
+/// Stdin is read by one reader thread for the whole process, so a question the gate stops
+/// waiting for (its `timeout`) leaves no read behind. Lines typed after that, meant for the
+/// abandoned question, are dropped before the next question is asked.
+#[derive(Debug, Default)]
+pub struct ConsoleInterviewer;
+
+#[async_trait]
+impl Interviewer for ConsoleInterviewer {
+  async fn ask(&self, question: &Question) -> Answer {
+    stdin_lines().ask(question).await
+  }
+}
+
+/// The process's stdin lines, read by a reader thread started on the first question.
+fn stdin_lines() -> &'static LineInput {
+  static STDIN: OnceLock<LineInput> = OnceLock::new();
+  STDIN.get_or_init(|| {
+    let (tx, rx) = mpsc::unbounded_channel();
+    std::thread::spawn(move || {
+      for line in std::io::stdin().lock().lines() {
+        let Ok(line) = line else { break };
+        if tx.send(line).is_err() {
+          break;
+        }
+      }
+    });
+    LineInput::new(rx)
+  })
+}
+
+/// Lines of input that questions are answered from, one question at a time.
+pub(crate) struct LineInput {
+  lines: tokio::sync::Mutex<mpsc::UnboundedReceiver<String>>,
+  /// Set when a question stopped waiting before it was answered.
+  abandoned: AtomicBool,
+}
+
+impl LineInput {
+  /// Answers from the lines sent on `lines`; the sender closing is end of input.
+  pub(crate) fn new(lines: mpsc::UnboundedReceiver<String>) -> Self {
+    Self {
+      lines: tokio::sync::Mutex::new(lines),
+      abandoned: AtomicBool::new(false),
+    }
+  }
+
+  /// Asks `question` on stderr and reads lines until one names an option (an empty line takes
+  /// the default). Input left over from an abandoned question is dropped first.
+  pub(crate) async fn ask(&self, question: &Question) -> Answer {
+    let mut lines = self.lines.lock().await;
+    if self.abandoned.swap(false, Ordering::SeqCst) {
+      while lines.try_recv().is_ok() {}
+    }
+    let waiting = MarkOnDrop(&self.abandoned);
+    let answer = loop {
+      eprint!("{}\n> ", question.prompt());
+      let _ = std::io::stderr().flush();
+      let Some(line) = lines.recv().await else {
+        break Answer::Skipped;
+      };
+      if line.trim().is_empty()
+        && let Some(ref default) = question.default
+      {
+        break Answer::Selected(default.clone());
+      }
+      if question.find(&line).is_some() {
+        break Answer::Selected(line.trim().to_string());
+      }
+      eprintln!("'{}' is not one of the options", line.trim());
+    };
+    std::mem::forget(waiting);
+    answer
+  }
+}
+
+/// Sets a flag when dropped, i.e. when a question is abandoned before it is answered.
+struct MarkOnDrop<'a>(&'a AtomicBool);
+
+impl Drop for MarkOnDrop<'_> {
+  fn drop(&mut self) {
+    self.0.store(true, Ordering::SeqCst);
+  }
+}
+
+/// Never asks: takes the question's default, else its first option.
+#[derive(Debug, Default)]
+pub struct AutoApproveInterviewer;
+
+#[async_trait]
+impl Interviewer for AutoApproveInterviewer {
+  async fn ask(&self, question: &Question) -> Answer {
+    question
+      .default
+      .clone()
+      .or_else(|| question.options.first().map(|o| o.key.clone()))
+      .map_or(Answer::Skipped, Answer::Selected)
+  }
+}
+
+/// Answers from a fixed list, one per question, in order; skips once the list runs out.
+#[derive(Debug, Default)]
+pub struct QueueInterviewer {
+  answers: Mutex<VecDeque<Answer>>,
+}
+
+impl QueueInterviewer {
+  /// Answers each question with the next of `answers`.
+  pub fn new(answers: impl IntoIterator<Item = Answer>) -> Self {
+    Self {
+      answers: Mutex::new(answers.into_iter().collect()),
+    }
+  }
+
+  /// Selects each of `choices` in turn.
+  pub fn selecting<S: Into<String>>(choices: impl IntoIterator<Item = S>) -> Self {
+    Self::new(choices.into_iter().map(|c| Answer::Selected(c.into())))
+  }
+
+  /// Answers not used yet.
+  pub fn remaining(&self) -> usize {
+    self.answers.lock().unwrap().len()
+  }
+}
+
+#[async_trait]
+impl Interviewer for QueueInterviewer {
+  async fn ask(&self, _question: &Question) -> Answer {
+    self
+      .answers
+      .lock()
+      .unwrap()
+      .pop_front()
+      .unwrap_or(Answer::Skipped)
+  }
+}
+
+/// Asks through files, for gates answered by another process or person: writes
+/// `<dir>/<node>.question.json` and waits for `<dir>/<node>.answer`, whose trimmed contents are
+/// the answer. Both files are removed once answered, and the question file also when the gate
+/// stops waiting (set a `timeout` on the node for that).
+#[derive(Debug)]
+pub struct FileDropInterviewer {
+  dir: PathBuf,
+  poll_interval: Duration,
+}
+
+impl FileDropInterviewer {
+  /// Drops questions into `dir`, creating it on the first question.
+  pub fn new(dir: impl Into<PathBuf>) -> Self {
+    Self {
+      dir: dir.into(),
+      poll_interval: Duration::from_millis(200),
+    }
+  }
+
+  /// How often to look for the answer file (default 200ms).
+  pub fn with_poll_interval(mut self, poll_interval: Duration) -> Self {
+    self.poll_interval = poll_interval;
+    self
+  }
+
+  /// Path of the question file for `node_id`.
+  pub fn question_path(&self, node_id: &str) -> PathBuf {
+    self.dir.join(format!("{}.question.json", node_id))
+  }
+
+  /// Path of the answer file for `node_id`.
+  pub fn answer_path(&self, node_id: &str) -> PathBuf {
+    self.dir.join(format!("{}.answer", node_id))
+  }
+}
+
+#[async_trait]
+impl Interviewer for FileDropInterviewer {
+  #[instrument(level = "trace", skip_all, fields(node = %question.node_id))]
+  async fn ask(&self, question: &Question) -> Answer {
+    let question_path = self.question_path(&question.node_id);
+    let answer_path = self.answer_path(&question.node_id);
+    let json = serde_json::to_string_pretty(question).unwrap_or_default();
+    if let Err(e) =
+      std::fs::create_dir_all(&self.dir).and_then(|()| std::fs::write(&question_path, json))
+    {
+      tracing::warn!(path = %question_path.display(), "cannot write question: {}", e);
+      return Answer::Skipped;
+    }
+    tracing::info!(path = %question_path.display(), "waiting for {}", answer_path.display());
+    let _question_file = RemoveOnDrop(question_path);
+    loop {
+      if let Ok(answer) = std::fs::read_to_string(&answer_path) {
+        let _ = std::fs::remove_file(&answer_path);
+        return match answer.trim() {
+          "" => Answer::Skipped,
+          answer => Answer::Selected(answer.to_string()),
+        };
+      }
+      tokio::time::sleep(self.poll_interval).await;
+    }
+  }
+}
+
+/// Removes a file when dropped, including when a timed-out ask is cancelled.
+struct RemoveOnDrop(PathBuf);
+
+impl Drop for RemoveOnDrop {
+  fn drop(&mut self) {
+    let _ = std::fs::remove_file(&self.0);
+  }
+}
@@ -0,0 +1,159 @@
+//! Tests for `interviewer`.
+
+use std::time::Duration;
+
+use crate::interviewer::{
+  Answer, AutoApproveInterviewer, FileDropInterviewer, Interviewer, LineInput, Question,
+  QuestionOption, QueueInterviewer, accelerator_key,
+};
+use tokio::sync::mpsc;
+
+fn review() -> Question {
+  Question {
+    node_id: "review".to_string(),
+    text: "Ship it?".to_string(),
+    options: vec![
+      QuestionOption::new("[A] Approve", "ship"),
+      QuestionOption::new("R) Revise", "rework"),
+      QuestionOption::new("abort", "exit"),
+    ],
+    default: None,
+  }
+}
+
+#[test]
+fn accelerator_key_from_label() {
+  assert_eq!(accelerator_key("[A] Approve"), "A");
+  assert_eq!(accelerator_key("r) Revise"), "R");
+  assert_eq!(accelerator_key("Y - Yes"), "Y");
+  assert_eq!(accelerator_key("retry"), "R");
+  assert_eq!(accelerator_key("Re-run"), "R");
+  assert_eq!(accelerator_key(""), "");
+}
+
+#[test]
+fn find_matches_key_label_or_next_node() {
+  let q = review();
+  assert_eq!(q.find("a").unwrap().next_node, "ship");
+  assert_eq!(q.find(" Revise ").unwrap().next_node, "rework");
+  assert_eq!(q.find("[R] revise").unwrap().next_node, "rework");
+  assert_eq!(q.find("exit").unwrap().label, "abort");
+  assert!(q.find("maybe").is_none());
+}
+
+#[test]
+fn prompt_lists_options_and_default() {
+  let mut q = review();
+  q.default = Some("R".to_string());
+  assert_eq!(
+    q.prompt(),
+    "Ship it?\n  [A] Approve\n  R) Revise\n  [A] abort\n(default: R)"
+  );
+}
+
+#[tokio::test]
+async fn auto_approve_takes_default_else_first_option() {
+  let mut q = review();
+  assert_eq!(
+    AutoApproveInterviewer.ask(&q).await,
+    Answer::Selected("A".to_string())
+  );
+  q.default = Some("R".to_string());
+  assert_eq!(
+    AutoApproveInterviewer.ask(&q).await,
+    Answer::Selected("R".to_string())
+  );
+  q.options.clear();
+  q.default = None;
+  assert_eq!(AutoApproveInterviewer.ask(&q).await, Answer::Skipped);
+}
+
+#[tokio::test]
+async fn queue_answers_in_order_then_skips() {
+  let interviewer = QueueInterviewer::new([Answer::Selected("R".to_string()), Answer::Timeout]);
+  assert_eq!(interviewer.remaining(), 2);
+  assert_eq!(
+    interviewer.ask(&review()).await,
+    Answer::Selected("R".to_string())
+  );
+  assert_eq!(interviewer.ask(&review()).await, Answer::Timeout);
+  assert_eq!(interviewer.ask(&review()).await, Answer::Skipped);
+  assert_eq!(interviewer.remaining(), 0);
+}
+
+#[tokio::test]
+async fn file_drop_writes_question_and_reads_answer() {
+  let dir = tempfile::tempdir().unwrap();
+  let interviewer =
+    FileDropInterviewer::new(dir.path().join("gates")).with_poll_interval(Duration::from_millis(5));
+  let question_path = interviewer.question_path("review");
+  let answer_path = interviewer.answer_path("review");
+  let q = review();
+  let answer = tokio::spawn(async move { interviewer.ask(&q).await });
+  while !question_path.exists() {
+    tokio::time::sleep(Duration::from_millis(5)).await;
+  }
+  let written: serde_json::Value =
+    serde_json::from_str(&std::fs::read_to_string(&question_path).unwrap()).unwrap();
+  assert_eq!(written["text"], "Ship it?");
+  assert_eq!(written["options"][1]["next_node"], "rework");
+  std::fs::write(&answer_path, "revise\n").unwrap();
+  assert_eq!(
+    answer.await.unwrap(),
+    Answer::Selected("revise".to_string())
+  );
+  assert!(!question_path.exists());
+  assert!(!answer_path.exists());
+}
+
+#[tokio::test]
+async fn file_drop_removes_question_when_gate_stops_waiting() {
+  let dir = tempfile::tempdir().unwrap();
+  let interviewer =
+    FileDropInterviewer::new(dir.path()).with_poll_interval(Duration::from_millis(5));
+  let question = review();
+  let ask = interviewer.ask(&question);
+  assert!(
+    tokio::time::timeout(Duration::from_millis(50), ask)
+      .await
+      .is_err()
+  );
+  assert!(!interviewer.question_path("review").exists());
+}
+
+#[tokio::test]
+async fn line_input_asks_again_until_an_option_is_named() {
+  let (tx, rx) = mpsc::unbounded_channel();
+  let input = LineInput::new(rx);
+  tx.send("maybe".to_string()).unwrap();
+  tx.send(" r ".to_string()).unwrap();
+  assert_eq!(
+    input.ask(&review()).await,
+    Answer::Selected("r".to_string())
+  );
+  drop(tx);
+  assert_eq!(input.ask(&review()).await, Answer::Skipped);
+}
+
+#[tokio::test]
+async fn line_input_drops_late_answers_to_an_abandoned_question() {
+  let (tx, rx) = mpsc::unbounded_channel();
+  let input = LineInput::new(rx);
+  assert!(
+    tokio::time::timeout(Duration::from_millis(20), input.ask(&review()))
+      .await
+      .is_err()
+  );
+  // Typed for the question that timed out: must not answer the next one.
+  tx.send("r".to_string()).unwrap();
+  let question = review();
+  let next = input.ask(&question);
+  tokio::pin!(next);
+  assert!(
+    tokio::time::timeout(Duration::from_millis(20), &mut next)
+      .await
+      .is_err()
+  );
+  tx.send("a".to_string()).unwrap();
+  assert_eq!(next.await, Answer::Selected("a".to_string()));
+}
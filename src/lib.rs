@@ -19,17 +19,47 @@ pub mod execution_log_io;
 pub mod compiler;
 #[cfg(test)]
 mod compiler_test;
+pub mod condition;
+#[cfg(test)]
+mod condition_test;
+pub mod dot_format;
+#[cfg(test)]
+mod dot_format_test;
 pub mod dot_parser;
 #[cfg(test)]
 mod dot_parser_test;
+pub mod dot_writer;
+#[cfg(test)]
+mod dot_writer_test;
 pub mod graphs;
+pub mod handler;
+#[cfg(test)]
+mod handler_test;
+pub mod interviewer;
+#[cfg(test)]
+mod interviewer_test;
+pub mod lint;
+#[cfg(test)]
+mod lint_test;
 pub mod nodes;
+pub mod parallel;
+#[cfg(test)]
+mod parallel_test;
+pub mod pipeline_file;
+#[cfg(test)]
+mod pipeline_file_test;
 pub mod runner;
 #[cfg(test)]
 mod runner_test;
+pub mod tool;
+#[cfg(test)]
+mod tool_test;
 pub mod types;
 
-pub use compiler::compile_attractor_graph;
+pub use compiler::{CompileOptions, compile_attractor_graph};
+pub use handler::{Handler, HandlerRegistry, HandlerServices};
 pub use nodes::AttractorResult;
+pub use nodes::validate_graph::{validate, validate_or_raise};
+pub use pipeline_file::{PipelineFormat, load_pipeline};
 pub use runner::{RunOptions, run_compiled_graph, run_streamweave_graph};
-pub use types::{AttractorGraph, AttractorNode, ExecutionState, NodeOutcome};
+pub use types::{AttractorGraph, AttractorNode, Diagnostic, ExecutionState, NodeOutcome, Severity};
@@ -0,0 +1,214 @@
+//! Lint rules for pipeline graphs and the registry that runs them.
+//!
+//! [LintRegistry::default] holds the built-in attractor-spec §7 rules (see
+//! [crate::validate]). Callers add their own rules with [LintRegistry::register] and change
+//! how any rule is reported with [LintRegistry::set_level]:
+//!
+//! ```
+//! use streamweave_attractor::lint::{FnRule, LintRegistry, RuleLevel};
+//! use streamweave_attractor::{Diagnostic, Severity};
+//!
+//! let mut lints = LintRegistry::default();
+//! lints.register(FnRule::new("exec_has_fail_edge", |graph| {
+//!   graph
+//!     .nodes
+//!     .values()
+//!     .filter(|n| n.handler_type.as_deref() == Some("exec"))
+//!     .filter(|n| graph.outgoing_edges(&n.id).len() < 2)
+//!     .map(|n| {
+//!       Diagnostic::new("exec_has_fail_edge", Severity::Warning, "exec node has no fail edge")
+//!         .with_node(&n.id)
+//!     })
+//!     .collect()
+//! }));
+//! lints.set_level("exec_has_fail_edge", RuleLevel::Deny).unwrap();
+//! lints.set_level("prompt_on_llm_nodes", RuleLevel::Allow).unwrap();
+//! ```
+
+use std::collections::HashMap;
+use std::fmt;
+use std::str::FromStr;
+use std::sync::Arc;
+
+use crate::nodes::validate_graph::builtin_rules;
+use crate::types::{AttractorGraph, Diagnostic, Severity};
+use tracing::instrument;
+
+/// A check over a whole graph.
+pub trait LintRule: Send + Sync {
+  /// Rule id: the `rule` of every diagnostic it reports, and the key for [RuleLevel]s.
+  fn name(&self) -> &str;
+
+  /// Returns the rule's findings for `graph`, with the severity it considers right.
+  fn check(&self, graph: &AttractorGraph) -> Vec<Diagnostic>;
+}
+
+/// A [LintRule] made from a name and a function.
+pub struct FnRule<F> {
+  name: String,
+  check: F,
+}
+
+impl<F> FnRule<F>
+where
+  F: Fn(&AttractorGraph) -> Vec<Diagnostic> + Send + Sync,
+{
+  pub fn new(name: impl Into<String>, check: F) -> Self {
+    Self {
+      name: name.into(),
+      check,
+    }
+  }
+}
+
+impl<F> LintRule for FnRule<F>
+where
+  F: Fn(&AttractorGraph) -> Vec<Diagnostic> + Send + Sync,
+{
+  fn name(&self) -> &str {
+    &self.name
+  }
+
+  fn check(&self, graph: &AttractorGraph) -> Vec<Diagnostic> {
+    (self.check)(graph)
+  }
+}
+
+/// How a rule's diagnostics are reported, overriding the severity the rule gives them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RuleLevel {
+  /// Drop them.
+  Allow,
+  /// Report them as warnings.
+  Warn,
+  /// Report them as errors.
+  Deny,
+}
+
+impl FromStr for RuleLevel {
+  type Err = String;
+
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    match s {
+      "allow" => Ok(Self::Allow),
+      "warn" => Ok(Self::Warn),
+      "deny" => Ok(Self::Deny),
+      _ => Err(format!(
+        "unknown rule level {:?} (expected allow, warn or deny)",
+        s
+      )),
+    }
+  }
+}
+
+impl fmt::Display for RuleLevel {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    f.write_str(match self {
+      Self::Allow => "allow",
+      Self::Warn => "warn",
+      Self::Deny => "deny",
+    })
+  }
+}
+
+/// Lint rules in the order they run, with per-rule levels.
+///
+/// Pass one as [crate::RunOptions::lints] to check runs and compiles with it.
+#[derive(Clone)]
+pub struct LintRegistry {
+  rules: Vec<Arc<dyn LintRule>>,
+  levels: HashMap<String, RuleLevel>,
+}
+
+impl Default for LintRegistry {
+  /// The built-in rules, each at its own severity.
+  fn default() -> Self {
+    Self {
+      rules: builtin_rules(),
+      levels: HashMap::new(),
+    }
+  }
+}
+
+impl LintRegistry {
+  /// A registry with no rules, not even the built-in ones.
+  pub fn empty() -> Self {
+    Self {
+      rules: Vec::new(),
+      levels: HashMap::new(),
+    }
+  }
+
+  /// Adds `rule` after the existing ones, or replaces the rule with the same name in place.
+  pub fn register(&mut self, rule: impl LintRule + 'static) -> &mut Self {
+    let rule: Arc<dyn LintRule> = Arc::new(rule);
+    match self.rules.iter_mut().find(|r| r.name() == rule.name()) {
+      Some(existing) => *existing = rule,
+      None => self.rules.push(rule),
+    }
+    self
+  }
+
+  /// Sets how diagnostics from rule `name` are reported. Fails if no such rule is registered.
+  pub fn set_level(&mut self, name: &str, level: RuleLevel) -> Result<&mut Self, String> {
+    if !self.has_rule(name) {
+      return Err(format!("unknown lint rule '{}'", name));
+    }
+    self.levels.insert(name.to_string(), level);
+    Ok(self)
+  }
+
+  /// Whether a rule named `name` is registered.
+  pub fn has_rule(&self, name: &str) -> bool {
+    self.rules.iter().any(|r| r.name() == name)
+  }
+
+  /// Names of the registered rules, in order.
+  pub fn rule_names(&self) -> impl Iterator<Item = &str> {
+    self.rules.iter().map(|r| r.name())
+  }
+
+  /// Runs every rule and returns the diagnostics, errors first, then in rule order. Each
+  /// diagnostic's `rule` is set to the name of the rule that reported it.
+  #[instrument(level = "trace", skip(self, graph))]
+  pub fn validate(&self, graph: &AttractorGraph) -> Vec<Diagnostic> {
+    let mut diagnostics = Vec::new();
+    for rule in &self.rules {
+      let level = self.levels.get(rule.name()).copied();
+      if level == Some(RuleLevel::Allow) {
+        continue;
+      }
+      for mut d in rule.check(graph) {
+        d.rule = rule.name().to_string();
+        match level {
+          Some(RuleLevel::Warn) => d.severity = Severity::Warning,
+          Some(RuleLevel::Deny) => d.severity = Severity::Error,
+          _ => {}
+        }
+        diagnostics.push(d);
+      }
+    }
+    diagnostics.sort_by_key(|d| d.severity);
+    diagnostics
+  }
+
+  /// Like [LintRegistry::validate], but fails if there are any errors; otherwise returns the
+  /// warnings and infos. The error joins every error message, one per line.
+  #[instrument(level = "trace", skip(self, graph))]
+  pub fn validate_or_raise(&self, graph: &AttractorGraph) -> Result<Vec<Diagnostic>, String> {
+    let (errors, rest): (Vec<Diagnostic>, Vec<Diagnostic>) = self
+      .validate(graph)
+      .into_iter()
+      .partition(Diagnostic::is_error);
+    if errors.is_empty() {
+      return Ok(rest);
+    }
+    Err(
+      errors
+        .iter()
+        .map(|d| d.message.as_str())
+        .collect::<Vec<_>>()
+        .join("\n"),
+    )
+  }
+}
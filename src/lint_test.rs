@@ -0,0 +1,128 @@
+//! Tests for `lint`.
+
+use crate::dot_parser::parse_dot;
+use crate::lint::{FnRule, LintRegistry, LintRule, RuleLevel};
+use crate::types::{AttractorGraph, Diagnostic, Severity};
+
+/// House rule from the docs: no `git push` unless an approval (wait.human) node comes first.
+struct PushNeedsApproval;
+
+impl LintRule for PushNeedsApproval {
+  fn name(&self) -> &str {
+    "push_needs_approval"
+  }
+
+  fn check(&self, graph: &AttractorGraph) -> Vec<Diagnostic> {
+    let mut ids: Vec<&String> = graph.nodes.keys().collect();
+    ids.sort();
+    ids
+      .into_iter()
+      .filter(|id| {
+        let pushes = graph.nodes[*id]
+          .command
+          .as_deref()
+          .is_some_and(|c| c.contains("git push"));
+        let approved = graph.edges.iter().any(|e| {
+          &e.to_node == *id
+            && graph.nodes[&e.from_node].handler_type.as_deref() == Some("wait.human")
+        });
+        pushes && !approved
+      })
+      .map(|id| {
+        Diagnostic::new("ignored", Severity::Warning, "git push without approval").with_node(id)
+      })
+      .collect()
+  }
+}
+
+fn pipeline() -> AttractorGraph {
+  parse_dot(
+    r#"digraph G {
+      start [shape=Mdiamond]
+      exit [shape=Msquare]
+      draft
+      push [type=exec, command="git push"]
+      start -> draft -> push -> exit
+    }"#,
+  )
+  .unwrap()
+}
+
+fn rules(diagnostics: &[Diagnostic]) -> Vec<(&str, Severity)> {
+  diagnostics
+    .iter()
+    .map(|d| (d.rule.as_str(), d.severity))
+    .collect()
+}
+
+#[test]
+fn custom_rules_run_after_builtins_under_their_own_name() {
+  let mut lints = LintRegistry::default();
+  lints.register(PushNeedsApproval);
+  let d = lints.validate(&pipeline());
+  assert_eq!(
+    rules(&d),
+    [
+      ("prompt_on_llm_nodes", Severity::Warning),
+      ("push_needs_approval", Severity::Warning)
+    ]
+  );
+  assert_eq!(d[1].node_id.as_deref(), Some("push"));
+  assert!(lints.validate_or_raise(&pipeline()).is_ok());
+}
+
+#[test]
+fn levels_allow_warn_and_deny_rules() {
+  let mut lints = LintRegistry::default();
+  lints.register(PushNeedsApproval);
+  lints
+    .set_level("push_needs_approval", RuleLevel::Deny)
+    .unwrap()
+    .set_level("prompt_on_llm_nodes", RuleLevel::Allow)
+    .unwrap();
+  let d = lints.validate(&pipeline());
+  assert_eq!(rules(&d), [("push_needs_approval", Severity::Error)]);
+  assert_eq!(
+    lints.validate_or_raise(&pipeline()).unwrap_err(),
+    "git push without approval"
+  );
+
+  let mut g = pipeline();
+  g.edges.retain(|e| e.to_node != "exit");
+  lints.set_level("reachability", RuleLevel::Warn).unwrap();
+  lints
+    .set_level("push_needs_approval", RuleLevel::Allow)
+    .unwrap();
+  assert_eq!(
+    rules(&lints.validate(&g)),
+    [
+      ("reachability", Severity::Warning),
+      ("no_dead_ends", Severity::Warning)
+    ]
+  );
+}
+
+#[test]
+fn register_replaces_rule_with_same_name() {
+  let mut lints = LintRegistry::default();
+  let count = lints.rule_names().count();
+  lints.register(FnRule::new("prompt_on_llm_nodes", |_: &AttractorGraph| {
+    vec![Diagnostic::new("x", Severity::Info, "replaced")]
+  }));
+  assert_eq!(lints.rule_names().count(), count);
+  let d = lints.validate(&pipeline());
+  assert_eq!(rules(&d), [("prompt_on_llm_nodes", Severity::Info)]);
+  assert_eq!(d[0].message, "replaced");
+}
+
+#[test]
+fn set_level_rejects_unknown_rules() {
+  let mut lints = LintRegistry::empty();
+  assert_eq!(lints.rule_names().count(), 0);
+  assert_eq!(
+    lints.set_level("no_such_rule", RuleLevel::Deny).err(),
+    Some("unknown lint rule 'no_such_rule'".to_string())
+  );
+  assert_eq!("warn".parse::<RuleLevel>(), Ok(RuleLevel::Warn));
+  assert!("forbid".parse::<RuleLevel>().is_err());
+}
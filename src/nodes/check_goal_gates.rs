@@ -1,6 +1,7 @@
 //! Check goal gates per attractor-spec §3.4.
 
-use crate::types::{AttractorGraph, NodeOutcome, OutcomeStatus};
+use crate::nodes::run_shutdown::{RunShutdown, next_or_shutdown};
+use crate::types::{AttractorGraph, GraphPayload, NodeOutcome, OutcomeStatus};
 use async_trait::async_trait;
 use std::any::Any;
 use std::collections::HashMap;
@@ -10,24 +11,25 @@ use streamweave::node::{InputStreams, Node, NodeExecutionError, OutputStreams};
 use tokio_stream::wrappers::ReceiverStream;
 use tracing::instrument;
 
-/// Input for CheckGoalGatesNode.
+/// Input for [check_goal_gates].
 #[derive(Clone)]
 pub struct CheckGoalGatesInput {
-  /// The attractor graph.
-  pub graph: AttractorGraph,
+  /// The attractor graph, shared rather than copied per payload.
+  pub graph: Arc<AttractorGraph>,
   /// Outcomes keyed by node id.
   pub node_outcomes: HashMap<String, NodeOutcome>,
   /// Whether we are at the exit node.
   pub at_exit: bool,
 }
 
-/// Output: (gate_ok, retry_target).
+/// Result of a goal gate check.
 #[derive(Clone)]
-#[allow(dead_code)] // fields reserved for future wiring of CheckGoalGatesNode
 pub struct CheckGoalGatesOutput {
   /// Whether all goal gates passed.
   pub gate_ok: bool,
-  /// Node id to retry if gate failed.
+  /// First unsatisfied goal gate node (by id), if any.
+  pub failed_node: Option<String>,
+  /// Node to jump back to for the failed gate (spec §3.4); None means the run fails.
   pub retry_target: Option<String>,
 }
 
@@ -37,51 +39,83 @@ pub(crate) fn goal_gate_passed(outcome: &NodeOutcome) -> bool {
   outcome.status == OutcomeStatus::Success || outcome.status == OutcomeStatus::PartialSuccess
 }
 
-/// Checks goal gates per attractor-spec §3.4; returns gate_ok and optional retry_target.
+/// Checks goal gates per attractor-spec §3.4; returns gate_ok and, for the first unsatisfied
+/// gate (by node id), its retry target from [AttractorGraph::retry_target_for].
 #[instrument(level = "trace", skip(input))]
 pub(crate) fn check_goal_gates(input: &CheckGoalGatesInput) -> CheckGoalGatesOutput {
+  let ok = CheckGoalGatesOutput {
+    gate_ok: true,
+    failed_node: None,
+    retry_target: None,
+  };
   if !input.at_exit {
-    return CheckGoalGatesOutput {
-      gate_ok: true,
-      retry_target: None,
-    };
+    return ok;
   }
-  for (node_id, outcome) in &input.node_outcomes {
-    let node = match input.graph.nodes.get(node_id) {
-      Some(n) => n,
-      None => continue,
+  let mut node_ids: Vec<&String> = input.node_outcomes.keys().collect();
+  node_ids.sort();
+  for node_id in node_ids {
+    let Some(node) = input.graph.nodes.get(node_id) else {
+      continue;
     };
-    if node.goal_gate && !goal_gate_passed(outcome) {
+    if node.goal_gate && !goal_gate_passed(&input.node_outcomes[node_id]) {
       return CheckGoalGatesOutput {
         gate_ok: false,
-        retry_target: node_id.clone().into(),
+        failed_node: Some(node_id.clone()),
+        retry_target: input.graph.retry_target_for(node_id).map(String::from),
       };
     }
   }
-  CheckGoalGatesOutput {
-    gate_ok: true,
-    retry_target: None,
-  }
+  ok
 }
 
-/// StreamWeave node that checks goal gates when at exit.
+/// Port name for jumping back to `target` after an unsatisfied goal gate.
+pub(crate) fn retry_port(target: &str) -> String {
+  format!("retry_{}", target)
+}
+
+/// StreamWeave node that checks goal gates for payloads about to enter exit.
+/// Ports: `pass` (all gates satisfied), `retry_<target>` per possible retry target, and
+/// `fail` (a gate is unsatisfied and has no retry target; the payload carries an error outcome).
 pub struct CheckGoalGatesNode {
   /// Node display name.
   name: String,
+  /// AST with goal_gate nodes and retry targets.
+  graph: Arc<AttractorGraph>,
   /// Input port names (e.g. `in`).
   input_ports: Vec<String>,
-  /// Output port names (e.g. `out`).
+  /// Output port names: `pass`, `fail`, then `retry_<target>` sorted.
   output_ports: Vec<String>,
+  /// When set, checking stops once the run shuts down.
+  shutdown: Option<RunShutdown>,
 }
 
 impl CheckGoalGatesNode {
-  pub fn new(name: impl Into<String>) -> Self {
+  pub fn new(name: impl Into<String>, graph: Arc<AttractorGraph>) -> Self {
+    let mut targets: Vec<String> = graph
+      .nodes
+      .values()
+      .filter(|n| n.goal_gate)
+      .filter_map(|n| graph.retry_target_for(&n.id))
+      .map(retry_port)
+      .collect();
+    targets.sort();
+    targets.dedup();
+    let mut output_ports = vec!["pass".to_string(), "fail".to_string()];
+    output_ports.extend(targets);
     Self {
       name: name.into(),
+      graph,
       input_ports: vec!["in".to_string()],
-      output_ports: vec!["out".to_string()],
+      output_ports,
+      shutdown: None,
     }
   }
+
+  /// Stops checking once `shutdown` is triggered.
+  pub(crate) fn with_shutdown(mut self, shutdown: RunShutdown) -> Self {
+    self.shutdown = Some(shutdown);
+    self
+  }
 }
 
 #[async_trait]
@@ -107,7 +141,7 @@ impl Node for CheckGoalGatesNode {
   }
 
   fn has_output_port(&self, name: &str) -> bool {
-    name == "out"
+    self.output_ports.iter().any(|p| p == name)
   }
 
   fn execute(
@@ -116,31 +150,59 @@ impl Node for CheckGoalGatesNode {
   ) -> Pin<
     Box<dyn std::future::Future<Output = Result<OutputStreams, NodeExecutionError>> + Send + '_>,
   > {
+    let name = self.name.clone();
+    let graph = Arc::clone(&self.graph);
+    let ports = self.output_ports.clone();
+    let shutdown = self.shutdown.clone();
     Box::pin(async move {
-      let in_stream = inputs.remove("in").ok_or("Missing 'in' input")?;
-      let (out_tx, out_rx) = tokio::sync::mpsc::channel(16);
+      let mut in_stream = inputs.remove("in").ok_or("Missing 'in' input")?;
+      let mut senders = HashMap::new();
+      let mut outputs = HashMap::new();
+      for port in &ports {
+        let (tx, rx) = tokio::sync::mpsc::channel(16);
+        senders.insert(port.clone(), tx);
+        outputs.insert(
+          port.clone(),
+          Box::pin(ReceiverStream::new(rx))
+            as Pin<Box<dyn futures::Stream<Item = Arc<dyn Any + Send + Sync>> + Send>>,
+        );
+      }
 
       tokio::spawn(async move {
-        use futures::StreamExt;
-        let mut s = in_stream;
-        while let Some(item) = s.next().await {
-          let input = match item.downcast::<CheckGoalGatesInput>() {
-            Ok(arc) => (*arc).clone(),
-            Err(_) => continue,
+        while let Some(item) = next_or_shutdown(&mut in_stream, shutdown.as_ref()).await {
+          let Ok(payload) = item.clone().downcast::<GraphPayload>() else {
+            tracing::trace!(node = %name, "CheckGoalGatesNode dropping non-payload item");
+            continue;
+          };
+          let gates = check_goal_gates(&CheckGoalGatesInput {
+            graph: Arc::clone(&graph),
+            node_outcomes: payload.node_outcomes.clone(),
+            at_exit: true,
+          });
+          let failed = gates.failed_node.unwrap_or_default();
+          let (port, item) = if gates.gate_ok {
+            ("pass".to_string(), item)
+          } else if let Some(target) = gates.retry_target {
+            tracing::info!(node = %failed, retry_target = %target, "goal gate unsatisfied, retrying");
+            (retry_port(&target), item)
+          } else {
+            tracing::info!(node = %failed, "goal gate unsatisfied, no retry target");
+            let mut p = (*payload).clone();
+            p.outcome = Some(NodeOutcome::error(format!(
+              "goal gate '{}' unsatisfied and no retry target",
+              failed
+            )));
+            (
+              "fail".to_string(),
+              Arc::new(p) as Arc<dyn Any + Send + Sync>,
+            )
           };
-          let output = check_goal_gates(&input);
-          let _ = out_tx
-            .send(Arc::new(output) as Arc<dyn Any + Send + Sync>)
-            .await;
+          if let Some(tx) = senders.get(&port) {
+            let _ = tx.send(item).await;
+          }
         }
       });
 
-      let mut outputs = HashMap::new();
-      outputs.insert(
-        "out".to_string(),
-        Box::pin(ReceiverStream::new(out_rx))
-          as Pin<Box<dyn futures::Stream<Item = Arc<dyn Any + Send + Sync>> + Send>>,
-      );
       Ok(outputs)
     })
   }
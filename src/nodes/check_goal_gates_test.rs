@@ -3,7 +3,7 @@
 use std::collections::HashMap;
 use std::sync::Arc;
 
-use crate::types::{AttractorGraph, AttractorNode, NodeOutcome};
+use crate::types::{AttractorGraph, AttractorNode, GraphPayload, NodeOutcome, OutcomeStatus};
 use futures::StreamExt;
 use streamweave::node::Node;
 use tokio_stream::wrappers::ReceiverStream;
@@ -21,7 +21,12 @@ fn node(id: &str, goal_gate: bool) -> AttractorNode {
     prompt: None,
     command: None,
     goal_gate,
-    max_retries: 0,
+    max_retries: None,
+    retry_policy: None,
+    retry_target: None,
+    fallback_retry_target: None,
+    classes: Vec::new(),
+    attrs: Default::default(),
   }
 }
 
@@ -33,24 +38,40 @@ fn graph(nodes: Vec<AttractorNode>) -> AttractorGraph {
     nodes: nodes_map,
     edges: vec![],
     default_max_retry: 50,
+    default_retry_policy: Default::default(),
+    retry_target: None,
+    fallback_retry_target: None,
+    name: None,
+    attrs: Default::default(),
   }
 }
 
+fn gated_graph() -> AttractorGraph {
+  let mut tests = node("tests", true);
+  tests.retry_target = Some("implement".to_string());
+  let mut g = graph(vec![tests, node("lint", true), node("implement", false)]);
+  g.fallback_retry_target = Some("plan".to_string());
+  g
+}
+
 #[test]
 fn node_trait_methods() {
-  let mut node = CheckGoalGatesNode::new("check");
+  let mut node = CheckGoalGatesNode::new("check", Arc::new(gated_graph()));
   assert_eq!(node.name(), "check");
   node.set_name("gates");
   assert_eq!(node.name(), "gates");
   assert!(node.has_input_port("in"));
-  assert!(node.has_output_port("out"));
+  assert_eq!(
+    node.output_port_names(),
+    ["pass", "fail", "retry_implement", "retry_plan"]
+  );
 }
 
 #[test]
 fn goal_gate_passed_for_success_and_partial_success() {
   assert!(goal_gate_passed(&NodeOutcome::success("ok")));
   let mut o = NodeOutcome::success("x");
-  o.status = crate::types::OutcomeStatus::PartialSuccess;
+  o.status = OutcomeStatus::PartialSuccess;
   assert!(goal_gate_passed(&o));
 }
 
@@ -65,7 +86,7 @@ fn gate_ok_when_not_at_exit() {
   let mut outcomes = HashMap::new();
   outcomes.insert("a".to_string(), NodeOutcome::error("err"));
   let input = CheckGoalGatesInput {
-    graph: g,
+    graph: Arc::new(g),
     node_outcomes: outcomes,
     at_exit: false,
   };
@@ -79,7 +100,7 @@ fn gate_ok_when_goal_gate_success() {
   let mut outcomes = HashMap::new();
   outcomes.insert("gate".to_string(), NodeOutcome::success("ok"));
   let input = CheckGoalGatesInput {
-    graph: g,
+    graph: Arc::new(g),
     node_outcomes: outcomes,
     at_exit: true,
   };
@@ -93,52 +114,48 @@ fn gate_not_ok_when_goal_gate_fail() {
   let mut outcomes = HashMap::new();
   outcomes.insert("gate".to_string(), NodeOutcome::error("err"));
   let input = CheckGoalGatesInput {
-    graph: g,
+    graph: Arc::new(g),
     node_outcomes: outcomes,
     at_exit: true,
   };
   let out = check_goal_gates(&input);
   assert!(!out.gate_ok);
-  assert_eq!(out.retry_target.as_deref(), Some("gate"));
+  assert_eq!(out.failed_node.as_deref(), Some("gate"));
+  assert_eq!(out.retry_target, None);
 }
 
-#[tokio::test]
-async fn node_execute_skips_wrong_type() {
-  let node = CheckGoalGatesNode::new("check");
-  let (tx, rx) = tokio::sync::mpsc::channel(4);
-  tx.send(Arc::new(0_i32) as Arc<dyn std::any::Any + Send + Sync>)
-    .await
-    .unwrap();
-  drop(tx);
-  let mut inputs: streamweave::node::InputStreams = HashMap::new();
-  inputs.insert(
-    "in".to_string(),
-    Box::pin(ReceiverStream::new(rx))
-      as std::pin::Pin<
-        Box<dyn futures::Stream<Item = Arc<dyn std::any::Any + Send + Sync>> + Send>,
-      >,
-  );
-  let mut outputs = node.execute(inputs).await.unwrap();
-  let mut out = outputs.remove("out").unwrap();
-  let item: Option<Arc<dyn std::any::Any + Send + Sync>> = out.next().await;
-  assert!(item.is_none());
-}
-
-#[tokio::test]
-async fn node_execute_checks_gates() {
-  let g = graph(vec![node("gate", true)]);
+#[test]
+fn gate_not_ok_returns_retry_target_of_failed_gate() {
   let mut outcomes = HashMap::new();
-  outcomes.insert("gate".to_string(), NodeOutcome::success("ok"));
+  outcomes.insert("lint".to_string(), NodeOutcome::success("ok"));
+  outcomes.insert("tests".to_string(), NodeOutcome::error("err"));
   let input = CheckGoalGatesInput {
-    graph: g,
-    node_outcomes: outcomes,
+    graph: Arc::new(gated_graph()),
+    node_outcomes: outcomes.clone(),
     at_exit: true,
   };
-  let node = CheckGoalGatesNode::new("check");
+  let out = check_goal_gates(&input);
+  assert_eq!(out.failed_node.as_deref(), Some("tests"));
+  assert_eq!(out.retry_target.as_deref(), Some("implement"));
+
+  outcomes.insert("lint".to_string(), NodeOutcome::error("err"));
+  let out = check_goal_gates(&CheckGoalGatesInput {
+    graph: Arc::new(gated_graph()),
+    node_outcomes: outcomes,
+    at_exit: true,
+  });
+  assert_eq!(out.failed_node.as_deref(), Some("lint"));
+  assert_eq!(out.retry_target.as_deref(), Some("plan"));
+}
+
+/// Sends `item` through a gate node for `g` and returns the port it came out of.
+async fn gate(
+  g: AttractorGraph,
+  item: Arc<dyn std::any::Any + Send + Sync>,
+) -> Vec<(String, Arc<dyn std::any::Any + Send + Sync>)> {
+  let node = CheckGoalGatesNode::new("check", Arc::new(g));
   let (tx, rx) = tokio::sync::mpsc::channel(4);
-  tx.send(Arc::new(input) as Arc<dyn std::any::Any + Send + Sync>)
-    .await
-    .unwrap();
+  tx.send(item).await.unwrap();
   drop(tx);
   let mut inputs: streamweave::node::InputStreams = HashMap::new();
   inputs.insert(
@@ -148,13 +165,74 @@ async fn node_execute_checks_gates() {
         Box<dyn futures::Stream<Item = Arc<dyn std::any::Any + Send + Sync>> + Send>,
       >,
   );
-  let mut outputs = node.execute(inputs).await.unwrap();
-  let mut out = outputs.remove("out").unwrap();
-  let item: Option<Arc<dyn std::any::Any + Send + Sync>> = out.next().await;
-  assert!(item.is_some());
-  let result = item
-    .unwrap()
-    .downcast::<super::check_goal_gates::CheckGoalGatesOutput>()
-    .unwrap();
-  assert!(result.gate_ok);
+  let outputs = node.execute(inputs).await.unwrap();
+  let mut routed = vec![];
+  for (port, mut stream) in outputs {
+    if let Some(item) = stream.next().await {
+      routed.push((port, item));
+    }
+  }
+  routed
+}
+
+fn payload(outcomes: &[(&str, NodeOutcome)]) -> Arc<dyn std::any::Any + Send + Sync> {
+  let p = GraphPayload::new(
+    HashMap::new(),
+    Some(NodeOutcome::success("ok")),
+    "lint".to_string(),
+    vec![],
+  )
+  .with_node_outcomes(
+    outcomes
+      .iter()
+      .map(|(id, o)| (id.to_string(), o.clone()))
+      .collect(),
+  );
+  Arc::new(p)
+}
+
+#[tokio::test]
+async fn node_execute_skips_wrong_type() {
+  let routed = gate(gated_graph(), Arc::new(0_i32)).await;
+  assert!(routed.is_empty());
+}
+
+#[tokio::test]
+async fn node_execute_passes_satisfied_gates() {
+  let item = payload(&[
+    ("tests", NodeOutcome::success("ok")),
+    ("lint", NodeOutcome::success("ok")),
+  ]);
+  let routed = gate(gated_graph(), item).await;
+  assert_eq!(routed.len(), 1);
+  assert_eq!(routed[0].0, "pass");
+}
+
+#[tokio::test]
+async fn node_execute_routes_to_retry_target() {
+  let item = payload(&[
+    ("tests", NodeOutcome::error("err")),
+    ("lint", NodeOutcome::success("ok")),
+  ]);
+  let routed = gate(gated_graph(), item).await;
+  assert_eq!(routed.len(), 1);
+  assert_eq!(routed[0].0, "retry_implement");
+}
+
+#[tokio::test]
+async fn node_execute_fails_without_retry_target() {
+  let item = payload(&[("gate", NodeOutcome::error("err"))]);
+  let routed = gate(graph(vec![node("gate", true)]), item).await;
+  assert_eq!(routed.len(), 1);
+  assert_eq!(routed[0].0, "fail");
+  let p = routed[0].1.clone().downcast::<GraphPayload>().unwrap();
+  let outcome = p.outcome.as_ref().unwrap();
+  assert_eq!(outcome.status, OutcomeStatus::Error);
+  assert!(
+    outcome
+      .failure_reason
+      .as_deref()
+      .unwrap()
+      .contains("'gate'")
+  );
 }
@@ -0,0 +1,163 @@
+//! Edge router for compiled graphs: picks the next node for each payload at runtime.
+//! Evaluates the source node's outgoing edges with [select_edge] (conditions, preferred_label,
+//! suggested_next_ids, weight and lexical tie-break), so compiled and interpreted routing agree.
+//! A payload no edge takes leaves on [NO_EDGE_PORT] with an error outcome, so the run fails
+//! instead of waiting forever.
+
+use crate::nodes::run_shutdown::{RunShutdown, next_or_shutdown};
+use crate::nodes::select_edge::{SelectEdgeInput, select_edge};
+use crate::types::{AttractorGraph, GraphPayload, NodeOutcome};
+use async_trait::async_trait;
+use std::collections::HashMap;
+use std::pin::Pin;
+use std::sync::Arc;
+use streamweave::node::{InputStreams, Node, NodeExecutionError, OutputStreams};
+use tokio_stream::wrappers::ReceiverStream;
+use tracing;
+
+/// Output port for payloads no outgoing edge takes; the compiler wires it to the exit router.
+pub(crate) const NO_EDGE_PORT: &str = "<no edge>";
+
+/// Routes GraphPayload items from one AST node to one output port per target node
+/// (the port is named after the target node id), plus [NO_EDGE_PORT].
+pub struct EdgeRouterNode {
+  /// Node display name.
+  name: String,
+  /// AST node whose outgoing edges are evaluated.
+  node_id: String,
+  /// AST used for edge selection.
+  graph: Arc<AttractorGraph>,
+  /// Input port names.
+  input_ports: Vec<String>,
+  /// Output port names: distinct target node ids, sorted, then [NO_EDGE_PORT].
+  output_ports: Vec<String>,
+  /// When set, routing stops once the run shuts down.
+  shutdown: Option<RunShutdown>,
+}
+
+impl EdgeRouterNode {
+  pub fn new(
+    name: impl Into<String>,
+    node_id: impl Into<String>,
+    graph: Arc<AttractorGraph>,
+  ) -> Self {
+    let node_id = node_id.into();
+    let mut output_ports: Vec<String> = graph
+      .outgoing_edges(&node_id)
+      .iter()
+      .map(|e| e.to_node.clone())
+      .collect();
+    output_ports.sort();
+    output_ports.dedup();
+    output_ports.push(NO_EDGE_PORT.to_string());
+    Self {
+      name: name.into(),
+      node_id,
+      graph,
+      input_ports: vec!["in".to_string()],
+      output_ports,
+      shutdown: None,
+    }
+  }
+
+  /// Stops routing once `shutdown` is triggered.
+  pub(crate) fn with_shutdown(mut self, shutdown: RunShutdown) -> Self {
+    self.shutdown = Some(shutdown);
+    self
+  }
+}
+
+#[async_trait]
+impl Node for EdgeRouterNode {
+  fn name(&self) -> &str {
+    &self.name
+  }
+
+  fn set_name(&mut self, name: &str) {
+    self.name = name.to_string();
+  }
+
+  fn input_port_names(&self) -> &[String] {
+    &self.input_ports
+  }
+
+  fn output_port_names(&self) -> &[String] {
+    &self.output_ports
+  }
+
+  fn has_input_port(&self, name: &str) -> bool {
+    name == "in"
+  }
+
+  fn has_output_port(&self, name: &str) -> bool {
+    self.output_ports.iter().any(|p| p == name)
+  }
+
+  fn execute(
+    &self,
+    mut inputs: InputStreams,
+  ) -> Pin<
+    Box<dyn std::future::Future<Output = Result<OutputStreams, NodeExecutionError>> + Send + '_>,
+  > {
+    let name = self.name.clone();
+    let node_id = self.node_id.clone();
+    let graph = Arc::clone(&self.graph);
+    let ports = self.output_ports.clone();
+    let shutdown = self.shutdown.clone();
+    Box::pin(async move {
+      tracing::trace!(node = %name, "EdgeRouterNode executing");
+      let mut in_stream = inputs.remove("in").ok_or("Missing 'in' input")?;
+      let mut senders = HashMap::new();
+      let mut outputs = HashMap::new();
+      for port in &ports {
+        let (tx, rx) = tokio::sync::mpsc::channel(16);
+        senders.insert(port.clone(), tx);
+        outputs.insert(
+          port.clone(),
+          Box::pin(ReceiverStream::new(rx))
+            as Pin<Box<dyn futures::Stream<Item = Arc<dyn std::any::Any + Send + Sync>> + Send>>,
+        );
+      }
+
+      tokio::spawn(async move {
+        while let Some(item) = next_or_shutdown(&mut in_stream, shutdown.as_ref()).await {
+          let Ok(payload) = item.clone().downcast::<GraphPayload>() else {
+            tracing::trace!(node = %name, "EdgeRouterNode dropping non-payload item");
+            continue;
+          };
+          // Start passes no outcome through; route it as a success like the execution loop.
+          let outcome = payload
+            .outcome
+            .clone()
+            .unwrap_or_else(|| NodeOutcome::success("Start"));
+          let next = select_edge(&SelectEdgeInput {
+            node_id: node_id.clone(),
+            outcome,
+            context: payload.context.clone(),
+            graph: Arc::clone(&graph),
+          })
+          .next_node_id;
+          match next.as_ref().and_then(|id| senders.get(id)) {
+            Some(tx) => {
+              tracing::trace!(node = %name, next = ?next, "EdgeRouterNode routing payload");
+              let _ = tx.send(item).await;
+            }
+            None => {
+              tracing::warn!(node = %name, "EdgeRouterNode found no edge for payload");
+              let mut p = (*payload).clone();
+              p.outcome = Some(NodeOutcome::error(format!(
+                "no outgoing edge of '{}' matches its outcome",
+                node_id
+              )));
+              let _ = senders[NO_EDGE_PORT]
+                .send(Arc::new(p) as Arc<dyn std::any::Any + Send + Sync>)
+                .await;
+            }
+          }
+        }
+      });
+
+      Ok(outputs)
+    })
+  }
+}
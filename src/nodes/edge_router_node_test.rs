@@ -0,0 +1,134 @@
+//! Tests for `EdgeRouterNode`.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use futures::StreamExt;
+use streamweave::node::{Node, OutputStreams};
+use tokio_stream::wrappers::ReceiverStream;
+
+use super::EdgeRouterNode;
+use super::edge_router_node::NO_EDGE_PORT;
+use crate::dot_parser::parse_dot;
+use crate::types::{GraphPayload, NodeOutcome, OutcomeStatus};
+
+fn router() -> EdgeRouterNode {
+  let dot = r#"
+    digraph G {
+      start [shape=Mdiamond]
+      exit [shape=Msquare]
+      check [type=exec, command="true"]
+      fix [type=exec, command="true"]
+      retry [type=exec, command="true"]
+      start -> check
+      check -> exit [condition="tests_passed=true"]
+      check -> fix [condition="outcome!=success"]
+      check -> retry [weight=5]
+      check -> fix [weight=1]
+      fix -> check
+      retry -> check
+    }
+  "#;
+  EdgeRouterNode::new("route_check", "check", Arc::new(parse_dot(dot).unwrap()))
+}
+
+/// Feeds `payload` to `node` and returns its output streams.
+async fn outputs(node: &EdgeRouterNode, payload: GraphPayload) -> OutputStreams {
+  let (tx, rx) = tokio::sync::mpsc::channel(4);
+  tx.send(Arc::new(payload) as Arc<dyn std::any::Any + Send + Sync>)
+    .await
+    .unwrap();
+  drop(tx);
+  let mut inputs: streamweave::node::InputStreams = HashMap::new();
+  inputs.insert(
+    "in".to_string(),
+    Box::pin(ReceiverStream::new(rx))
+      as std::pin::Pin<
+        Box<dyn futures::Stream<Item = Arc<dyn std::any::Any + Send + Sync>> + Send>,
+      >,
+  );
+  node.execute(inputs).await.unwrap()
+}
+
+async fn route(node: &EdgeRouterNode, payload: GraphPayload) -> Vec<String> {
+  let mut routed = vec![];
+  for (port, mut stream) in outputs(node, payload).await {
+    if stream.next().await.is_some() {
+      routed.push(port);
+    }
+  }
+  routed
+}
+
+fn payload(outcome: NodeOutcome, context: &[(&str, &str)]) -> GraphPayload {
+  let ctx = context
+    .iter()
+    .map(|(k, v)| (k.to_string(), v.to_string()))
+    .collect();
+  GraphPayload::new(
+    ctx,
+    Some(outcome),
+    "check".to_string(),
+    vec!["check".to_string()],
+  )
+}
+
+#[test]
+fn node_trait_methods() {
+  let mut node = router();
+  assert_eq!(node.name(), "route_check");
+  node.set_name("route");
+  assert_eq!(node.name(), "route");
+  assert!(node.has_input_port("in"));
+  assert_eq!(
+    node.output_port_names(),
+    ["exit", "fix", "retry", NO_EDGE_PORT]
+  );
+  assert!(!node.has_output_port("check"));
+}
+
+#[tokio::test]
+async fn routes_by_context_condition() {
+  let p = payload(
+    NodeOutcome::success("ok"),
+    &[("outcome", "Success"), ("tests_passed", "true")],
+  );
+  assert_eq!(route(&router(), p).await, ["exit"]);
+}
+
+#[tokio::test]
+async fn routes_by_negated_outcome_condition() {
+  let p = payload(NodeOutcome::error("boom"), &[("outcome", "Error")]);
+  assert_eq!(route(&router(), p).await, ["fix"]);
+}
+
+#[tokio::test]
+async fn unmatched_conditions_fall_back_to_heaviest_unconditional_edge() {
+  let p = payload(NodeOutcome::success("ok"), &[("outcome", "Success")]);
+  assert_eq!(route(&router(), p).await, ["retry"]);
+}
+
+#[tokio::test]
+async fn payload_no_edge_takes_fails_on_the_no_edge_port() {
+  let dot = r#"
+    digraph G {
+      start [shape=Mdiamond]
+      exit [shape=Msquare]
+      check [type=exec, command="true"]
+      start -> check
+    }
+  "#;
+  let node = EdgeRouterNode::new("route_check", "check", Arc::new(parse_dot(dot).unwrap()));
+  let mut outputs = outputs(&node, payload(NodeOutcome::success("ok"), &[])).await;
+  let item = outputs.remove(NO_EDGE_PORT).unwrap().next().await.unwrap();
+  let routed = item.downcast::<GraphPayload>().unwrap();
+  let outcome = routed.outcome.clone().unwrap();
+  assert_eq!(outcome.status, OutcomeStatus::Error);
+  assert!(
+    outcome
+      .failure_reason
+      .as_deref()
+      .unwrap()
+      .contains("no outgoing edge of 'check'")
+  );
+}
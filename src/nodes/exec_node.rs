@@ -1,142 +1,93 @@
-//! Exec node: runs a shell command, succeeds on exit 0, fails otherwise.
-//! Accepts GraphPayload (passes context), applies context_updates from outcome, emits GraphPayload.
+//! Exec nodes: run a shell command, succeed on exit 0, ask for a retry on exit 75, fail otherwise.
 
-use crate::nodes::apply_context_updates::{ApplyContextUpdatesInput, apply_updates};
-use crate::types::{GraphPayload, NodeOutcome, OutcomeStatus, RunContext};
+use crate::dot_parser::parse_node_attrs;
+use crate::handler::{ExecHandler, HandlerServices};
+use crate::nodes::HandlerNode;
+use crate::types::NodeOutcome;
 use async_trait::async_trait;
-use std::any::Any;
-use std::collections::HashMap;
 use std::pin::Pin;
-use std::process::Command;
 use std::sync::Arc;
 use streamweave::node::{InputStreams, Node, NodeExecutionError, OutputStreams};
-use tokio::sync::mpsc;
-use tokio_stream::StreamExt;
-use tokio_stream::wrappers::ReceiverStream;
+use tokio::process::Command;
+use tracing::instrument;
 
-/// Node that runs a shell command and emits NodeOutcome.
-pub struct ExecNode {
-  /// Node display name.
-  name: String,
-  /// Shell command to execute.
-  command: String,
+/// Exit status an exec command uses to ask for a retry (sysexits `EX_TEMPFAIL`).
+pub(crate) const EXIT_TEMPFAIL: i32 = 75;
+
+/// Runs `command` via `sh -c`; success on exit 0, retry on exit 75 ([EXIT_TEMPFAIL]), error
+/// otherwise, including when the shell cannot be started.
+/// Used by [crate::handler::ExecHandler]. Dropping the future kills the shell.
+#[instrument(level = "trace")]
+pub(crate) async fn run_exec_command(command: &str) -> NodeOutcome {
+  match Command::new("sh")
+    .arg("-c")
+    .arg(command)
+    .kill_on_drop(true)
+    .output()
+    .await
+  {
+    Ok(o) => {
+      if o.status.success() {
+        NodeOutcome::success("ok")
+      } else if o.status.code() == Some(EXIT_TEMPFAIL) {
+        NodeOutcome::retry(format!("exit {}", EXIT_TEMPFAIL))
+      } else {
+        NodeOutcome::error(format!("exit {}", o.status.code().unwrap_or(-1)))
+      }
+    }
+    Err(e) => NodeOutcome::error(format!("cannot run sh: {}", e)),
+  }
 }
 
+/// Node that runs a shell command and emits NodeOutcome.
+#[deprecated(note = "use HandlerNode with ExecHandler, which the compiler builds for exec nodes")]
+pub struct ExecNode(HandlerNode);
+
+#[allow(deprecated)]
 impl ExecNode {
+  /// A HandlerNode running [ExecHandler] for an exec node `name` with `command`.
   pub fn new(name: impl Into<String>, command: impl Into<String>) -> Self {
-    Self {
-      name: name.into(),
-      command: command.into(),
-    }
+    let attrs = [
+      ("type".to_string(), "exec".to_string()),
+      ("command".to_string(), command.into()),
+    ];
+    let node = parse_node_attrs(&name.into(), &attrs).expect("exec attributes are valid");
+    Self(HandlerNode::new(
+      node,
+      Arc::new(ExecHandler),
+      HandlerServices::default(),
+    ))
   }
 }
 
+#[allow(deprecated)]
 #[async_trait]
 impl Node for ExecNode {
   fn name(&self) -> &str {
-    &self.name
+    self.0.name()
   }
   fn set_name(&mut self, name: &str) {
-    self.name = name.to_string();
+    self.0.set_name(name)
   }
   fn input_port_names(&self) -> &[String] {
-    static P: std::sync::OnceLock<Vec<String>> = std::sync::OnceLock::new();
-    P.get_or_init(|| vec!["in".to_string()])
+    self.0.input_port_names()
   }
   fn output_port_names(&self) -> &[String] {
-    static P: std::sync::OnceLock<Vec<String>> = std::sync::OnceLock::new();
-    P.get_or_init(|| vec!["out".to_string(), "error".to_string()])
+    self.0.output_port_names()
   }
   fn has_input_port(&self, name: &str) -> bool {
-    name == "in"
+    self.0.has_input_port(name)
   }
   fn has_output_port(&self, name: &str) -> bool {
-    name == "out" || name == "error"
+    self.0.has_output_port(name)
   }
 
   fn execute(
     &self,
-    mut inputs: InputStreams,
+    inputs: InputStreams,
   ) -> Pin<
     Box<dyn std::future::Future<Output = Result<OutputStreams, NodeExecutionError>> + Send + '_>,
   > {
-    let name = self.name.clone();
-    let cmd = self.command.clone();
-    Box::pin(async move {
-      tracing::trace!(node = %name, command = %cmd, "ExecNode executing");
-      let in_stream = inputs.remove("in").ok_or("Missing 'in' input")?;
-      let (out_tx, out_rx) = mpsc::channel(16);
-      let (err_tx, err_rx) = mpsc::channel(16);
-      // Must use while let (not if let): cyclic graphs (e.g. beads-worker-loop) feed multiple
-      // items. A previous fix for clippy::never_loop incorrectly changed this to if let, breaking loops.
-      tokio::spawn(async move {
-        let mut s = in_stream;
-        while let Some(item) = s.next().await {
-          tracing::trace!(node = %name, "ExecNode received item, processing");
-          tracing::info!(node = %name, command = %cmd, "running");
-          let incoming = item.downcast::<GraphPayload>().ok();
-          let context: RunContext = incoming
-            .as_ref()
-            .map(|p| p.context.clone())
-            .unwrap_or_default();
-          let (_current_node_id, completed_nodes) = incoming
-            .as_ref()
-            .map(|p| (p.current_node_id.clone(), p.completed_nodes.clone()))
-            .unwrap_or_else(|| (String::new(), vec![]));
-          let outcome = tokio::task::spawn_blocking({
-            let c = cmd.clone();
-            move || match Command::new("sh").arg("-c").arg(&c).output() {
-              Ok(o) => {
-                if o.status.success() {
-                  NodeOutcome::success("ok")
-                } else {
-                  NodeOutcome::error(format!("exit {}", o.status.code().unwrap_or(-1)))
-                }
-              }
-              Err(e) => NodeOutcome::error(format!("{}", e)),
-            }
-          })
-          .await
-          .unwrap_or_else(|e| NodeOutcome::error(format!("{}", e)));
-          let is_success = outcome.status == OutcomeStatus::Success
-            || outcome.status == OutcomeStatus::PartialSuccess;
-          let updated = apply_updates(&ApplyContextUpdatesInput {
-            context: context.clone(),
-            outcome: outcome.clone(),
-          });
-          let mut completed: Vec<String> = completed_nodes;
-          completed.push(name.clone());
-          let payload = GraphPayload::new(updated, Some(outcome), name.clone(), completed);
-          let arc = Arc::new(payload) as Arc<dyn Any + Send + Sync>;
-          // Process all items (while let); cyclic graphs (e.g. beads-worker-loop) feed multiple items.
-          // Drop both senders after loop to close output streams.
-
-          if is_success {
-            tracing::trace!(node = %name, "ExecNode sending to out port");
-            tracing::info!(node = %name, "finished: success");
-            let _ = out_tx.send(arc).await;
-          } else {
-            tracing::trace!(node = %name, "ExecNode sending to error port");
-            tracing::info!(node = %name, "finished: error");
-            let _ = err_tx.send(arc).await;
-            break; // Exit path: break cycle to avoid deadlock with MergeNode
-          }
-        }
-        drop(out_tx);
-        drop(err_tx);
-      });
-      let mut outputs = HashMap::new();
-      outputs.insert(
-        "out".to_string(),
-        Box::pin(ReceiverStream::new(out_rx))
-          as Pin<Box<dyn tokio_stream::Stream<Item = Arc<dyn Any + Send + Sync>> + Send>>,
-      );
-      outputs.insert(
-        "error".to_string(),
-        Box::pin(ReceiverStream::new(err_rx))
-          as Pin<Box<dyn tokio_stream::Stream<Item = Arc<dyn Any + Send + Sync>> + Send>>,
-      );
-      Ok(outputs)
-    })
+    self.0.execute(inputs)
   }
 }
@@ -1,6 +1,10 @@
-//! Execute a single node handler (start, exit, codergen stub).
+//! Execute a single node's handler, resolved through a [HandlerRegistry].
+//!
+//! The compiled graph's HandlerNode resolves handlers the same way, so the execution loop
+//! behaves like the compiled graph.
 
-use crate::types::{AttractorGraph, AttractorNode, NodeOutcome, OutcomeStatus, RunContext};
+use crate::handler::HandlerServices;
+use crate::types::{AttractorNode, NodeOutcome, RunContext};
 use async_trait::async_trait;
 use std::any::Any;
 use std::collections::HashMap;
@@ -12,14 +16,13 @@ use tracing::instrument;
 
 /// Input bundle for ExecuteHandlerNode.
 #[derive(Clone)]
-#[allow(dead_code)] // context and graph reserved for future handler implementations
 pub struct ExecuteHandlerInput {
   /// The node whose handler to execute.
   pub node: AttractorNode,
-  /// Current run context (reserved for future handler use).
+  /// Current run context, passed to the handler.
   pub context: RunContext,
-  /// The attractor graph (reserved for future handler use).
-  pub graph: AttractorGraph,
+  /// Graph, handlers, agent command and stage directory the handler runs with.
+  pub services: HandlerServices,
 }
 
 /// StreamWeave node that executes the handler for one Attractor pipeline node.
@@ -45,31 +48,16 @@ impl ExecuteHandlerNode {
   }
 }
 
-/// Builds a codergen NodeOutcome for the given node.
-#[instrument(level = "trace")]
-pub(crate) fn build_codergen_outcome(node: &AttractorNode) -> NodeOutcome {
-  let mut updates = HashMap::new();
-  updates.insert("last_stage".to_string(), node.id.clone());
-  NodeOutcome {
-    status: OutcomeStatus::Success,
-    notes: Some(format!("Stage completed: {}", node.id)),
-    failure_reason: None,
-    context_updates: updates,
-    preferred_label: None,
-    suggested_next_ids: vec![],
-  }
-}
-
-/// Executes the handler for the given node (start, exit, codergen stub, etc.) and returns the outcome.
+/// Executes the handler registered for the node's handler type and returns the outcome.
+/// Fails when no handler is registered for it.
 #[instrument(level = "trace", skip(input))]
-pub(crate) fn execute_handler(input: &ExecuteHandlerInput) -> Result<NodeOutcome, String> {
-  let handler = input.node.handler_type.as_deref().unwrap_or("codergen");
-  match handler {
-    "start" => Ok(NodeOutcome::success("Start")),
-    "exit" => Ok(NodeOutcome::success("Exit")),
-    "codergen" => Ok(build_codergen_outcome(&input.node)),
-    _ => Ok(NodeOutcome::success(format!("Handler {} (stub)", handler))),
-  }
+pub(crate) async fn execute_handler(input: &ExecuteHandlerInput) -> Result<NodeOutcome, String> {
+  let handler = input.services.handlers.resolve(&input.node)?;
+  Ok(
+    handler
+      .execute(&input.node, &input.context, &input.services)
+      .await,
+  )
 }
 
 #[async_trait]
@@ -123,7 +111,7 @@ impl Node for ExecuteHandlerNode {
               continue;
             }
           };
-          match execute_handler(&input) {
+          match execute_handler(&input).await {
             Ok(outcome) => {
               let _ = out_tx
                 .send(Arc::new(outcome) as Arc<dyn Any + Send + Sync>)
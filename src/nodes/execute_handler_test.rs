@@ -3,14 +3,13 @@
 use std::collections::HashMap;
 use std::sync::Arc;
 
-use crate::types::{AttractorGraph, AttractorNode, OutcomeStatus};
+use crate::handler::HandlerServices;
+use crate::types::{AttractorNode, OutcomeStatus};
 use futures::StreamExt;
 use streamweave::node::Node;
 use tokio_stream::wrappers::ReceiverStream;
 
-use super::execute_handler::{
-  ExecuteHandlerInput, ExecuteHandlerNode, build_codergen_outcome, execute_handler,
-};
+use super::execute_handler::{ExecuteHandlerInput, ExecuteHandlerNode, execute_handler};
 
 fn node(id: &str, handler_type: Option<&str>) -> AttractorNode {
   AttractorNode {
@@ -21,21 +20,17 @@ fn node(id: &str, handler_type: Option<&str>) -> AttractorNode {
     prompt: None,
     command: None,
     goal_gate: false,
-    max_retries: 0,
+    max_retries: None,
+    retry_policy: None,
+    retry_target: None,
+    fallback_retry_target: None,
+    classes: Vec::new(),
+    attrs: Default::default(),
   }
 }
 
-fn empty_graph() -> AttractorGraph {
-  AttractorGraph {
-    goal: "test".to_string(),
-    nodes: HashMap::new(),
-    edges: vec![],
-    default_max_retry: 50,
-  }
-}
-
-#[test]
-fn node_trait_methods() {
+#[tokio::test]
+async fn node_trait_methods() {
   let mut node = ExecuteHandlerNode::new("exec");
   assert_eq!(node.name(), "exec");
   node.set_name("handler");
@@ -45,66 +40,131 @@ fn node_trait_methods() {
   assert!(node.has_output_port("error"));
 }
 
-#[test]
-fn build_codergen_outcome_includes_last_stage() {
-  let n = node("run", Some("codergen"));
-  let o = build_codergen_outcome(&n);
-  assert_eq!(o.status, OutcomeStatus::Success);
-  assert_eq!(
-    o.context_updates.get("last_stage").map(String::as_str),
-    Some("run")
-  );
-}
-
-#[test]
-fn start_handler() {
+#[tokio::test]
+async fn start_handler() {
   let input = ExecuteHandlerInput {
     node: node("start", Some("start")),
     context: HashMap::new(),
-    graph: empty_graph(),
+    services: HandlerServices::default(),
   };
-  let out = execute_handler(&input).unwrap();
+  let out = execute_handler(&input).await.unwrap();
   assert_eq!(out.status, OutcomeStatus::Success);
   assert_eq!(out.notes.as_deref(), Some("Start"));
 }
 
-#[test]
-fn exit_handler() {
+#[tokio::test]
+async fn exit_handler() {
   let input = ExecuteHandlerInput {
     node: node("exit", Some("exit")),
     context: HashMap::new(),
-    graph: empty_graph(),
+    services: HandlerServices::default(),
   };
-  let out = execute_handler(&input).unwrap();
+  let out = execute_handler(&input).await.unwrap();
   assert_eq!(out.status, OutcomeStatus::Success);
   assert_eq!(out.notes.as_deref(), Some("Exit"));
 }
 
-#[test]
-fn codergen_handler() {
+#[tokio::test]
+async fn exec_handler_runs_command() {
+  let mut n = node("run", Some("exec"));
+  n.command = Some("true".to_string());
+  let input = ExecuteHandlerInput {
+    node: n,
+    context: HashMap::new(),
+    services: HandlerServices::default(),
+  };
+  let out = execute_handler(&input).await.unwrap();
+  assert_eq!(out.status, OutcomeStatus::Success);
+}
+
+#[tokio::test]
+async fn exec_handler_fails_on_non_zero_exit() {
+  let mut n = node("run", Some("exec"));
+  n.command = Some("exit 3".to_string());
+  let input = ExecuteHandlerInput {
+    node: n,
+    context: HashMap::new(),
+    services: HandlerServices::default(),
+  };
+  let out = execute_handler(&input).await.unwrap();
+  assert_eq!(out.status, OutcomeStatus::Error);
+  assert_eq!(out.failure_reason.as_deref(), Some("exit 3"));
+}
+
+#[tokio::test]
+async fn exec_handler_without_command_fails() {
+  let input = ExecuteHandlerInput {
+    node: node("run", Some("exec")),
+    context: HashMap::new(),
+    services: HandlerServices::default(),
+  };
+  let out = execute_handler(&input).await.unwrap();
+  assert_eq!(out.status, OutcomeStatus::Error);
+  assert!(out.failure_reason.as_deref().unwrap().contains("command"));
+}
+
+#[tokio::test]
+async fn codergen_handler_runs_agent_cmd() {
+  let stage_dir = tempfile::tempdir().unwrap();
   let input = ExecuteHandlerInput {
     node: node("run", Some("codergen")),
     context: HashMap::new(),
-    graph: empty_graph(),
+    services: HandlerServices {
+      agent_cmd: Some("true".to_string()),
+      stage_dir: Some(stage_dir.path().to_path_buf()),
+      ..Default::default()
+    },
   };
-  let out = execute_handler(&input).unwrap();
+  let out = execute_handler(&input).await.unwrap();
   assert_eq!(out.status, OutcomeStatus::Success);
-  assert_eq!(
-    out.context_updates.get("last_stage").map(String::as_str),
-    Some("run")
+  assert_eq!(out.notes.as_deref(), Some("agent completed"));
+}
+
+#[tokio::test]
+async fn codergen_handler_fails_without_agent_cmd() {
+  let input = ExecuteHandlerInput {
+    node: node("run", Some("codergen")),
+    context: HashMap::new(),
+    services: HandlerServices::default(),
+  };
+  let out = execute_handler(&input).await.unwrap();
+  assert_eq!(out.status, OutcomeStatus::Error);
+  assert!(
+    out
+      .failure_reason
+      .as_deref()
+      .unwrap()
+      .contains("ATTRACTOR_AGENT_CMD")
   );
 }
 
-#[test]
-fn unknown_handler_stub() {
+#[tokio::test]
+async fn unregistered_handler_type_is_err() {
   let input = ExecuteHandlerInput {
     node: node("x", Some("custom.handler")),
     context: HashMap::new(),
-    graph: empty_graph(),
+    services: HandlerServices::default(),
   };
-  let out = execute_handler(&input).unwrap();
-  assert_eq!(out.status, OutcomeStatus::Success);
-  assert!(out.notes.as_deref().unwrap().contains("custom.handler"));
+  let err = execute_handler(&input).await.unwrap_err();
+  assert!(err.contains("custom.handler"));
+}
+
+#[tokio::test]
+async fn node_without_handler_type_runs_as_codergen() {
+  let input = ExecuteHandlerInput {
+    node: node("x", None),
+    context: HashMap::new(),
+    services: HandlerServices::default(),
+  };
+  let out = execute_handler(&input).await.unwrap();
+  assert_eq!(out.status, OutcomeStatus::Error);
+  assert!(
+    out
+      .failure_reason
+      .as_deref()
+      .unwrap()
+      .contains("ATTRACTOR_AGENT_CMD")
+  );
 }
 
 #[tokio::test]
@@ -137,7 +197,7 @@ async fn node_execute_runs_handler() {
   let input = ExecuteHandlerInput {
     node: node("run", Some("start")),
     context: HashMap::new(),
-    graph: empty_graph(),
+    services: HandlerServices::default(),
   };
   let (tx, rx) = tokio::sync::mpsc::channel(4);
   tx.send(Arc::new(input) as Arc<dyn std::any::Any + Send + Sync>)
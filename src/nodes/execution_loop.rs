@@ -1,7 +1,10 @@
 //! Attractor execution loop - runs the pipeline traversal until terminal.
 
+use crate::handler::HandlerServices;
+use crate::nodes::check_goal_gates::{CheckGoalGatesInput, check_goal_gates};
 use crate::nodes::execute_handler::{ExecuteHandlerInput, execute_handler};
-use crate::nodes::select_edge::{SelectEdgeInput, select_edge};
+use crate::nodes::retry::run_with_retries;
+use crate::parallel::next_node_id;
 use crate::types::{ExecutionState, ExecutionStepEntry, NodeOutcome};
 use async_trait::async_trait;
 use std::any::Any;
@@ -48,18 +51,30 @@ pub(crate) enum RunLoopResult {
 
 /// Optional callback invoked after each step (e.g. to persist execution log). If it returns `Err`, the loop returns that error.
 pub(crate) type AfterStepCallback<'a> =
-  Option<&'a mut dyn FnMut(&ExecutionState) -> Result<(), String>>;
+  Option<&'a mut (dyn FnMut(&ExecutionState) -> Result<(), String> + Send)>;
 
 /// Runs the execution loop on one ExecutionState; returns result or error.
 /// When `after_step` is `Some`, it is invoked after each step (after step_log is updated); persist failures propagate as `RunLoopResult::Err`.
 #[instrument(level = "trace", skip(state, after_step))]
-pub(crate) fn run_execution_loop_once(
+pub(crate) async fn run_execution_loop_once(
   state: &mut ExecutionState,
   mut after_step: AfterStepCallback<'_>,
 ) -> RunLoopResult {
   let max_iter = 1000;
   let mut iter = 0;
   let mut last_outcome;
+  // Outcome reported in the result: start/exit pass the previous outcome through, as the
+  // compiled graph's IdentityNode does, so a failing step routed to exit still reports failure.
+  let mut result_outcome: Option<NodeOutcome> = None;
+  // One shared copy for the whole run, so steps do not each clone the graph.
+  let graph = Arc::new(state.graph.clone());
+  let services = HandlerServices {
+    agent_cmd: state.agent_cmd.clone(),
+    stage_dir: state.stage_dir.clone(),
+    graph: Arc::clone(&graph),
+    handlers: state.handlers.clone(),
+    step_recorder: None,
+  };
 
   loop {
     if iter >= max_iter {
@@ -75,13 +90,73 @@ pub(crate) fn run_execution_loop_once(
       }
     };
 
+    // Goal gates (spec §3.4): before exiting, every goal_gate node must have succeeded.
+    if node.is_terminal() {
+      let gates = check_goal_gates(&CheckGoalGatesInput {
+        graph: Arc::clone(&graph),
+        node_outcomes: state.node_outcomes.clone(),
+        at_exit: true,
+      });
+      if !gates.gate_ok {
+        let failed = gates.failed_node.unwrap_or_default();
+        match gates.retry_target {
+          Some(target) => {
+            info!(node_id = %failed, retry_target = %target, "goal gate unsatisfied, retrying");
+            state.current_node_id = target;
+            continue;
+          }
+          None => {
+            return RunLoopResult::Err(format!(
+              "goal gate '{}' unsatisfied and no retry target",
+              failed
+            ));
+          }
+        }
+      }
+    }
+
     let context_before = state.context.clone();
     let handler_input = ExecuteHandlerInput {
       node: node.clone(),
       context: state.context.clone(),
-      graph: state.graph.clone(),
+      services: services.clone(),
     };
-    last_outcome = execute_handler(&handler_input).unwrap_or_else(crate::types::NodeOutcome::error);
+    let max_retries = state.graph.max_retries_for(&state.current_node_id);
+    let retry_policy = state.graph.retry_policy_for(&state.current_node_id);
+    let attempt;
+    (last_outcome, attempt) = run_with_retries(
+      &state.current_node_id,
+      max_retries,
+      &retry_policy,
+      |attempt, outcome| {
+        if let Some(ref mut log) = state.step_log {
+          let step = (log.len() + 1) as u32;
+          log.push(
+            ExecutionStepEntry::new(
+              step,
+              state.current_node_id.clone(),
+              node.handler_type.clone(),
+              context_before.clone(),
+              outcome.clone(),
+              context_before.clone(),
+              Some(state.current_node_id.clone()),
+              state.completed_nodes.clone(),
+            )
+            .with_attempt(attempt),
+          );
+        }
+      },
+      || async {
+        execute_handler(&handler_input)
+          .await
+          .unwrap_or_else(NodeOutcome::error)
+      },
+    )
+    .await;
+    let is_pass_through = matches!(node.handler_type.as_deref(), Some("start" | "exit"));
+    if !is_pass_through {
+      result_outcome = Some(last_outcome.clone());
+    }
     apply_context_updates(&mut state.context, &last_outcome);
     state.completed_nodes.push(state.current_node_id.clone());
     state
@@ -90,27 +165,23 @@ pub(crate) fn run_execution_loop_once(
     let context_after = state.context.clone();
     let completed_nodes_after = state.completed_nodes.clone();
 
-    let sel_input = SelectEdgeInput {
-      node_id: state.current_node_id.clone(),
-      outcome: last_outcome.clone(),
-      context: state.context.clone(),
-      graph: state.graph.clone(),
-    };
-    let sel_out = select_edge(&sel_input);
-    let next_node_id = sel_out.next_node_id.clone();
+    let next_node_id = next_node_id(&graph, &node, &last_outcome, &state.context);
 
     if let Some(ref mut log) = state.step_log {
       let step = (log.len() + 1) as u32;
-      log.push(ExecutionStepEntry::new(
-        step,
-        state.current_node_id.clone(),
-        node.handler_type.clone(),
-        context_before,
-        last_outcome.clone(),
-        context_after,
-        next_node_id.clone(),
-        completed_nodes_after,
-      ));
+      log.push(
+        ExecutionStepEntry::new(
+          step,
+          state.current_node_id.clone(),
+          node.handler_type.clone(),
+          context_before,
+          last_outcome.clone(),
+          context_after,
+          next_node_id.clone(),
+          completed_nodes_after,
+        )
+        .with_attempt(attempt),
+      );
     }
 
     if let Some(cb) = &mut after_step
@@ -119,7 +190,7 @@ pub(crate) fn run_execution_loop_once(
       return RunLoopResult::Err(e);
     }
 
-    match sel_out.next_node_id {
+    match next_node_id {
       Some(next_id) => {
         state.current_node_id = next_id;
       }
@@ -129,7 +200,7 @@ pub(crate) fn run_execution_loop_once(
           "execution loop complete"
         );
         return RunLoopResult::Ok(AttractorResult {
-          last_outcome: last_outcome.clone(),
+          last_outcome: result_outcome.unwrap_or(last_outcome),
           completed_nodes: state.completed_nodes.clone(),
           context: state.context.clone(),
           already_completed: false,
@@ -210,7 +281,7 @@ impl Node for AttractorExecutionLoopNode {
             }
           };
 
-          match run_execution_loop_once(&mut state, None) {
+          match run_execution_loop_once(&mut state, None).await {
             RunLoopResult::Ok(result) => {
               let _ = out_tx
                 .send(Arc::new(result) as Arc<dyn Any + Send + Sync>)
@@ -58,7 +58,12 @@ async fn node_execute_err_node_not_found() {
       prompt: None,
       command: None,
       goal_gate: false,
-      max_retries: 0,
+      max_retries: None,
+      retry_policy: None,
+      retry_target: None,
+      fallback_retry_target: None,
+      classes: Vec::new(),
+      attrs: Default::default(),
     },
   );
   nodes.insert(
@@ -71,7 +76,12 @@ async fn node_execute_err_node_not_found() {
       prompt: None,
       command: None,
       goal_gate: false,
-      max_retries: 0,
+      max_retries: None,
+      retry_policy: None,
+      retry_target: None,
+      fallback_retry_target: None,
+      classes: Vec::new(),
+      attrs: Default::default(),
     },
   );
   let graph = AttractorGraph {
@@ -79,6 +89,11 @@ async fn node_execute_err_node_not_found() {
     nodes,
     edges: vec![],
     default_max_retry: 50,
+    default_retry_policy: Default::default(),
+    retry_target: None,
+    fallback_retry_target: None,
+    name: None,
+    attrs: Default::default(),
   };
   let mut context = HashMap::new();
   context.insert("goal".to_string(), "test".to_string());
@@ -89,6 +104,9 @@ async fn node_execute_err_node_not_found() {
     completed_nodes: vec![],
     node_outcomes: HashMap::new(),
     step_log: None,
+    agent_cmd: None,
+    stage_dir: None,
+    handlers: Default::default(),
   };
   let node = AttractorExecutionLoopNode::new("exec");
   let (tx, rx) = tokio::sync::mpsc::channel(4);
@@ -145,8 +163,8 @@ fn new_creates_node() {
   assert!(n.has_output_port("error"));
 }
 
-#[test]
-fn run_execution_loop_once_returns_ok_for_simple_pipeline() {
+#[tokio::test]
+async fn run_execution_loop_once_returns_ok_for_simple_pipeline() {
   let dot = r#"digraph G { start [shape=Mdiamond] exit [shape=Msquare] start -> exit }"#;
   let graph = crate::dot_parser::parse_dot(dot).unwrap();
   let mut context = HashMap::new();
@@ -158,8 +176,11 @@ fn run_execution_loop_once_returns_ok_for_simple_pipeline() {
     completed_nodes: vec![],
     node_outcomes: HashMap::new(),
     step_log: None,
+    agent_cmd: None,
+    stage_dir: None,
+    handlers: Default::default(),
   };
-  match run_execution_loop_once(&mut state, None) {
+  match run_execution_loop_once(&mut state, None).await {
     RunLoopResult::Ok(r) => {
       assert_eq!(r.completed_nodes, vec!["start", "exit"]);
     }
@@ -167,8 +188,8 @@ fn run_execution_loop_once_returns_ok_for_simple_pipeline() {
   }
 }
 
-#[test]
-fn run_execution_loop_once_returns_err_when_node_not_found() {
+#[tokio::test]
+async fn run_execution_loop_once_returns_err_when_node_not_found() {
   let mut nodes = HashMap::new();
   nodes.insert(
     "start".to_string(),
@@ -180,7 +201,12 @@ fn run_execution_loop_once_returns_err_when_node_not_found() {
       prompt: None,
       command: None,
       goal_gate: false,
-      max_retries: 0,
+      max_retries: None,
+      retry_policy: None,
+      retry_target: None,
+      fallback_retry_target: None,
+      classes: Vec::new(),
+      attrs: Default::default(),
     },
   );
   let graph = AttractorGraph {
@@ -188,6 +214,11 @@ fn run_execution_loop_once_returns_err_when_node_not_found() {
     nodes,
     edges: vec![],
     default_max_retry: 50,
+    default_retry_policy: Default::default(),
+    retry_target: None,
+    fallback_retry_target: None,
+    name: None,
+    attrs: Default::default(),
   };
   let mut context = HashMap::new();
   context.insert("goal".to_string(), "test".to_string());
@@ -198,15 +229,18 @@ fn run_execution_loop_once_returns_err_when_node_not_found() {
     completed_nodes: vec![],
     node_outcomes: HashMap::new(),
     step_log: None,
+    agent_cmd: None,
+    stage_dir: None,
+    handlers: Default::default(),
   };
-  match run_execution_loop_once(&mut state, None) {
+  match run_execution_loop_once(&mut state, None).await {
     RunLoopResult::Err(e) => assert!(e.contains("Node not found")),
     RunLoopResult::Ok(_) => panic!("expected Err"),
   }
 }
 
-#[test]
-fn run_execution_loop_once_records_steps_when_step_log_is_some() {
+#[tokio::test]
+async fn run_execution_loop_once_records_steps_when_step_log_is_some() {
   let dot = r#"digraph G { start [shape=Mdiamond] exit [shape=Msquare] start -> exit }"#;
   let graph = crate::dot_parser::parse_dot(dot).unwrap();
   let mut context = HashMap::new();
@@ -219,8 +253,11 @@ fn run_execution_loop_once_records_steps_when_step_log_is_some() {
     completed_nodes: vec![],
     node_outcomes: HashMap::new(),
     step_log: step_log.clone(),
+    agent_cmd: None,
+    stage_dir: None,
+    handlers: Default::default(),
   };
-  match run_execution_loop_once(&mut state, None) {
+  match run_execution_loop_once(&mut state, None).await {
     RunLoopResult::Ok(r) => {
       assert_eq!(r.completed_nodes, vec!["start", "exit"]);
     }
@@ -237,3 +274,132 @@ fn run_execution_loop_once_records_steps_when_step_log_is_some() {
   assert_eq!(log[1].next_node_id, None);
   assert_eq!(log[1].completed_nodes_after, vec!["start", "exit"]);
 }
+
+#[tokio::test]
+async fn run_execution_loop_once_runs_exec_commands_and_follows_fail_edge() {
+  let dir = tempfile::tempdir().unwrap();
+  let marker = dir.path().join("fixed");
+  let dot = format!(
+    r#"digraph G {{
+      start [shape=Mdiamond]
+      exit [shape=Msquare]
+      check [type=exec, command="false"]
+      fix [type=exec, command="touch {}"]
+      start -> check
+      check -> exit [condition="outcome=success"]
+      check -> fix [condition="outcome=fail"]
+      fix -> exit
+    }}"#,
+    marker.display()
+  );
+  let graph = crate::dot_parser::parse_dot(&dot).unwrap();
+  let mut state = crate::nodes::init_context::create_initial_state(graph, None);
+  match run_execution_loop_once(&mut state, None).await {
+    RunLoopResult::Ok(r) => {
+      assert_eq!(r.completed_nodes, vec!["start", "check", "fix", "exit"]);
+      assert_eq!(r.last_outcome.status, crate::types::OutcomeStatus::Success);
+    }
+    RunLoopResult::Err(e) => panic!("expected Ok, got Err: {}", e),
+  }
+  assert!(marker.exists(), "fix exec command should have run");
+}
+
+#[tokio::test]
+async fn run_execution_loop_once_reports_failed_step_routed_to_exit() {
+  let dot = r#"digraph G {
+    start [shape=Mdiamond]
+    exit [shape=Msquare]
+    fail_step [type=exec, command="false"]
+    start -> fail_step
+    fail_step -> exit [condition="outcome=fail"]
+  }"#;
+  let graph = crate::dot_parser::parse_dot(dot).unwrap();
+  let mut state = crate::nodes::init_context::create_initial_state(graph, None);
+  match run_execution_loop_once(&mut state, None).await {
+    RunLoopResult::Ok(r) => {
+      assert_eq!(r.last_outcome.status, crate::types::OutcomeStatus::Error);
+      assert_eq!(r.completed_nodes, vec!["start", "fail_step", "exit"]);
+    }
+    RunLoopResult::Err(e) => panic!("expected Ok, got Err: {}", e),
+  }
+}
+
+#[tokio::test]
+async fn run_execution_loop_once_jumps_to_retry_target_for_unsatisfied_goal_gate() {
+  let dir = tempfile::tempdir().unwrap();
+  let counter = dir.path().join("count");
+  let dot = format!(
+    r#"digraph G {{
+      start [shape=Mdiamond]
+      exit [shape=Msquare]
+      implement [type=exec, command="true"]
+      tests [type=exec, goal_gate=true, retry_target=implement, command="n=$(cat {c} 2>/dev/null || echo 0); n=$((n+1)); echo $n > {c}; [ $n -ge 2 ]"]
+      start -> implement
+      implement -> tests
+      tests -> exit
+    }}"#,
+    c = counter.display()
+  );
+  let graph = crate::dot_parser::parse_dot(&dot).unwrap();
+  let mut state = crate::nodes::init_context::create_initial_state(graph, None);
+  match run_execution_loop_once(&mut state, None).await {
+    RunLoopResult::Ok(r) => {
+      assert_eq!(
+        r.completed_nodes,
+        vec!["start", "implement", "tests", "implement", "tests", "exit"]
+      );
+      assert_eq!(r.last_outcome.status, crate::types::OutcomeStatus::Success);
+    }
+    RunLoopResult::Err(e) => panic!("expected Ok, got Err: {}", e),
+  }
+}
+
+#[tokio::test]
+async fn run_execution_loop_once_fails_unsatisfied_goal_gate_without_retry_target() {
+  let dot = r#"digraph G {
+    start [shape=Mdiamond]
+    exit [shape=Msquare]
+    tests [type=exec, goal_gate=true, command="false"]
+    start -> tests
+    tests -> exit
+  }"#;
+  let graph = crate::dot_parser::parse_dot(dot).unwrap();
+  let mut state = crate::nodes::init_context::create_initial_state(graph, None);
+  match run_execution_loop_once(&mut state, None).await {
+    RunLoopResult::Ok(r) => panic!("expected Err, got {:?}", r.completed_nodes),
+    RunLoopResult::Err(e) => assert!(e.contains("goal gate 'tests'"), "{}", e),
+  }
+}
+
+#[tokio::test]
+async fn run_execution_loop_once_continues_at_fan_in_after_parallel_node() {
+  let graph = crate::dot_parser::parse_dot(
+    r#"digraph G {
+      start [shape=Mdiamond]
+      exit [shape=Msquare]
+      fork [shape=component]
+      a [type=exec, command="true"]
+      b [type=exec, command="false"]
+      join [shape=tripleoctagon]
+      start -> fork
+      fork -> a
+      fork -> b
+      a -> join
+      b -> join
+      join -> exit
+    }"#,
+  )
+  .unwrap();
+  let mut state = crate::nodes::init_context::create_initial_state(graph, None);
+  match run_execution_loop_once(&mut state, None).await {
+    RunLoopResult::Ok(r) => {
+      assert_eq!(r.completed_nodes, vec!["start", "fork", "join", "exit"]);
+      assert_eq!(
+        r.last_outcome.status,
+        crate::types::OutcomeStatus::PartialSuccess
+      );
+      assert_eq!(r.context["parallel.b.outcome"], "error");
+    }
+    RunLoopResult::Err(e) => panic!("expected Ok, got Err: {}", e),
+  }
+}
@@ -0,0 +1,191 @@
+//! Handler node: runs a pipeline node through its registered [Handler] in the compiled graph,
+//! emits GraphPayload with NodeOutcome and updated context (context_updates applied).
+
+use crate::handler::{Handler, HandlerServices};
+use crate::nodes::apply_context_updates::{ApplyContextUpdatesInput, apply_updates};
+use crate::nodes::retry::run_with_retries;
+use crate::nodes::run_shutdown::{RunShutdown, next_or_shutdown};
+use crate::nodes::step_recorder::StepRecorder;
+use crate::types::{AttractorNode, GraphPayload, OutcomeStatus, RetryPolicy, RunContext};
+use async_trait::async_trait;
+use std::any::Any;
+use std::collections::HashMap;
+use std::pin::Pin;
+use std::sync::Arc;
+use streamweave::node::{InputStreams, Node, NodeExecutionError, OutputStreams};
+use tokio::sync::mpsc;
+use tokio_stream::wrappers::ReceiverStream;
+
+/// Node that runs a [Handler] for one pipeline node and emits NodeOutcome.
+pub struct HandlerNode {
+  /// Node display name (the pipeline node id).
+  name: String,
+  /// Pipeline node passed to the handler.
+  node: AttractorNode,
+  /// Handler registered for the node's handler type.
+  handler: Arc<dyn Handler>,
+  /// Agent command and stage directory for the handler.
+  services: HandlerServices,
+  /// When set, each execution is recorded as an execution log step.
+  recorder: Option<StepRecorder>,
+  /// When set, the input loop ends once the run shuts down (cyclic graphs never close inputs).
+  shutdown: Option<RunShutdown>,
+  /// Extra attempts after a retryable outcome before escalating to an error.
+  max_retries: u32,
+  /// Backoff between attempts and which outcomes are retryable.
+  retry_policy: RetryPolicy,
+}
+
+impl HandlerNode {
+  pub fn new(node: AttractorNode, handler: Arc<dyn Handler>, services: HandlerServices) -> Self {
+    Self {
+      name: node.id.clone(),
+      node,
+      handler,
+      services,
+      recorder: None,
+      shutdown: None,
+      max_retries: 0,
+      retry_policy: RetryPolicy::default(),
+    }
+  }
+
+  /// Records every execution of this node to the run's execution log.
+  pub(crate) fn with_step_recorder(mut self, recorder: StepRecorder) -> Self {
+    self.recorder = Some(recorder);
+    self
+  }
+
+  /// Stops processing input once `shutdown` is triggered.
+  pub(crate) fn with_shutdown(mut self, shutdown: RunShutdown) -> Self {
+    self.shutdown = Some(shutdown);
+    self
+  }
+
+  /// Reruns the node up to `max_retries` times (default 0) while it returns a retryable
+  /// outcome, waiting between attempts per `policy`.
+  pub(crate) fn with_retries(mut self, max_retries: u32, policy: RetryPolicy) -> Self {
+    self.max_retries = max_retries;
+    self.retry_policy = policy;
+    self
+  }
+}
+
+#[async_trait]
+impl Node for HandlerNode {
+  fn name(&self) -> &str {
+    &self.name
+  }
+  fn set_name(&mut self, name: &str) {
+    self.name = name.to_string();
+  }
+  fn input_port_names(&self) -> &[String] {
+    static P: std::sync::OnceLock<Vec<String>> = std::sync::OnceLock::new();
+    P.get_or_init(|| vec!["in".to_string()])
+  }
+  fn output_port_names(&self) -> &[String] {
+    static P: std::sync::OnceLock<Vec<String>> = std::sync::OnceLock::new();
+    P.get_or_init(|| vec!["out".to_string(), "error".to_string()])
+  }
+  fn has_input_port(&self, name: &str) -> bool {
+    name == "in"
+  }
+  fn has_output_port(&self, name: &str) -> bool {
+    name == "out" || name == "error"
+  }
+
+  fn execute(
+    &self,
+    mut inputs: InputStreams,
+  ) -> Pin<
+    Box<dyn std::future::Future<Output = Result<OutputStreams, NodeExecutionError>> + Send + '_>,
+  > {
+    let name = self.name.clone();
+    let node = self.node.clone();
+    let handler = Arc::clone(&self.handler);
+    let services = self.services.clone();
+    let recorder = self.recorder.clone();
+    let shutdown = self.shutdown.clone();
+    let max_retries = self.max_retries;
+    let retry_policy = self.retry_policy.clone();
+    Box::pin(async move {
+      tracing::trace!(node = %name, "HandlerNode executing");
+      // Nodes reached only through a parallel node's branches run inside its handler, so the
+      // compiled graph gives them no input; they then have nothing to do.
+      let in_stream = inputs
+        .remove("in")
+        .unwrap_or_else(|| Box::pin(futures::stream::empty()));
+      let (out_tx, out_rx) = mpsc::channel(16);
+      let (err_tx, err_rx) = mpsc::channel(16);
+      // Must use while let (not if let): cyclic graphs (e.g. beads-worker-loop) feed multiple items.
+      tokio::spawn(async move {
+        let mut s = in_stream;
+        while let Some(item) = next_or_shutdown(&mut s, shutdown.as_ref()).await {
+          tracing::info!(node = %name, "running");
+          let incoming = item.downcast::<GraphPayload>().ok();
+          let context: RunContext = incoming
+            .as_ref()
+            .map(|p| p.context.clone())
+            .unwrap_or_default();
+          let completed_nodes = incoming
+            .as_ref()
+            .map(|p| p.completed_nodes.clone())
+            .unwrap_or_default();
+          let (outcome, attempt) = run_with_retries(
+            &name,
+            max_retries,
+            &retry_policy,
+            |attempt, outcome| {
+              if let Some(r) = &recorder {
+                r.record_retry(&name, attempt, &context, outcome, &completed_nodes);
+              }
+            },
+            || handler.execute(&node, &context, &services),
+          )
+          .await;
+          let is_success = outcome.status == OutcomeStatus::Success
+            || outcome.status == OutcomeStatus::PartialSuccess;
+          let updated = apply_updates(&ApplyContextUpdatesInput {
+            context: context.clone(),
+            outcome: outcome.clone(),
+          });
+          let mut completed: Vec<String> = completed_nodes;
+          completed.push(name.clone());
+          if let Some(ref r) = recorder {
+            r.record(&name, attempt, context, &outcome, &updated, &completed);
+          }
+          let mut node_outcomes = incoming
+            .as_ref()
+            .map(|p| p.node_outcomes.clone())
+            .unwrap_or_default();
+          node_outcomes.insert(name.clone(), outcome.clone());
+          let payload = GraphPayload::new(updated, Some(outcome), name.clone(), completed)
+            .with_node_outcomes(node_outcomes);
+          let arc = Arc::new(payload) as Arc<dyn Any + Send + Sync>;
+          if is_success {
+            tracing::info!(node = %name, "finished: success");
+            let _ = out_tx.send(arc).await;
+          } else {
+            tracing::info!(node = %name, "finished: error");
+            let _ = err_tx.send(arc).await;
+          }
+        }
+        // Dropping both senders closes the output streams once the input ends or the run shuts down.
+        drop(out_tx);
+        drop(err_tx);
+      });
+      let mut outputs = HashMap::new();
+      outputs.insert(
+        "out".to_string(),
+        Box::pin(ReceiverStream::new(out_rx))
+          as Pin<Box<dyn tokio_stream::Stream<Item = Arc<dyn Any + Send + Sync>> + Send>>,
+      );
+      outputs.insert(
+        "error".to_string(),
+        Box::pin(ReceiverStream::new(err_rx))
+          as Pin<Box<dyn tokio_stream::Stream<Item = Arc<dyn Any + Send + Sync>> + Send>>,
+      );
+      Ok(outputs)
+    })
+  }
+}
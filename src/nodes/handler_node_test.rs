@@ -0,0 +1,140 @@
+//! Tests for `HandlerNode`.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use futures::StreamExt;
+use streamweave::node::Node;
+use tokio_stream::wrappers::ReceiverStream;
+
+use super::HandlerNode;
+use crate::handler::{Handler, HandlerServices};
+use crate::types::{AttractorNode, GraphPayload, NodeOutcome, OutcomeStatus, RunContext};
+
+/// Counts the `n` context key up, failing once it reaches `limit`.
+struct Count {
+  limit: u32,
+}
+
+#[async_trait]
+impl Handler for Count {
+  async fn execute(
+    &self,
+    _node: &AttractorNode,
+    context: &RunContext,
+    _services: &HandlerServices,
+  ) -> NodeOutcome {
+    let n: u32 = context.get("n").and_then(|v| v.parse().ok()).unwrap_or(0) + 1;
+    if n >= self.limit {
+      return NodeOutcome::error(format!("reached {}", n));
+    }
+    let mut outcome = NodeOutcome::success("counted");
+    outcome
+      .context_updates
+      .insert("n".to_string(), n.to_string());
+    outcome
+  }
+}
+
+fn count_node(limit: u32) -> HandlerNode {
+  let node = AttractorNode {
+    id: "count".to_string(),
+    shape: "box".to_string(),
+    handler_type: Some("count".to_string()),
+    label: None,
+    prompt: None,
+    command: None,
+    goal_gate: false,
+    max_retries: None,
+    retry_policy: None,
+    retry_target: None,
+    fallback_retry_target: None,
+    classes: Vec::new(),
+    attrs: Default::default(),
+  };
+  HandlerNode::new(node, Arc::new(Count { limit }), HandlerServices::default())
+}
+
+/// Runs `node` on one payload and returns the port it emitted on with the payload.
+async fn run(node: &dyn Node, context: &[(&str, &str)]) -> (String, GraphPayload) {
+  let ctx = context
+    .iter()
+    .map(|(k, v)| (k.to_string(), v.to_string()))
+    .collect();
+  let (tx, rx) = tokio::sync::mpsc::channel(4);
+  tx.send(Arc::new(GraphPayload::initial(ctx, "start")) as Arc<dyn std::any::Any + Send + Sync>)
+    .await
+    .unwrap();
+  drop(tx);
+  let mut inputs: streamweave::node::InputStreams = HashMap::new();
+  inputs.insert(
+    "in".to_string(),
+    Box::pin(ReceiverStream::new(rx))
+      as std::pin::Pin<
+        Box<dyn futures::Stream<Item = Arc<dyn std::any::Any + Send + Sync>> + Send>,
+      >,
+  );
+  let outputs = node.execute(inputs).await.unwrap();
+  for (port, mut stream) in outputs {
+    if let Some(item) = stream.next().await {
+      let payload = item.downcast::<GraphPayload>().unwrap();
+      return (port, (*payload).clone());
+    }
+  }
+  panic!("no output");
+}
+
+#[test]
+fn node_trait_methods() {
+  let mut node = count_node(3);
+  assert_eq!(node.name(), "count");
+  node.set_name("count_again");
+  assert_eq!(node.name(), "count_again");
+  assert!(node.has_input_port("in"));
+  assert!(node.has_output_port("out"));
+  assert!(node.has_output_port("error"));
+}
+
+#[tokio::test]
+async fn success_applies_context_updates_and_emits_on_out() {
+  let (port, payload) = run(&count_node(3), &[("n", "1")]).await;
+  assert_eq!(port, "out");
+  assert_eq!(payload.context.get("n").map(String::as_str), Some("2"));
+  assert_eq!(payload.current_node_id, "count");
+  assert_eq!(payload.completed_nodes, ["count"]);
+  assert_eq!(
+    payload.node_outcomes["count"].notes.as_deref(),
+    Some("counted")
+  );
+}
+
+#[tokio::test]
+async fn failure_emits_on_error() {
+  let (port, payload) = run(&count_node(3), &[("n", "2")]).await;
+  assert_eq!(port, "error");
+  let outcome = payload.outcome.unwrap();
+  assert_eq!(outcome.status, OutcomeStatus::Error);
+  assert_eq!(outcome.failure_reason.as_deref(), Some("reached 3"));
+}
+
+#[tokio::test]
+#[allow(deprecated)]
+async fn deprecated_exec_node_runs_its_command_through_a_handler_node() {
+  let node = super::ExecNode::new("check", "exit 3");
+  assert_eq!(node.name(), "check");
+  let (port, payload) = run(&node, &[]).await;
+  assert_eq!(port, "error");
+  assert_eq!(payload.current_node_id, "check");
+  let outcome = payload.outcome.unwrap();
+  assert_eq!(outcome.failure_reason.as_deref(), Some("exit 3"));
+}
+
+#[tokio::test]
+#[allow(deprecated)]
+async fn deprecated_codergen_node_fails_without_an_agent_command() {
+  let node = super::CodergenNode::new("draft", "Write it", None, None);
+  let (port, payload) = run(&node, &[]).await;
+  assert_eq!(port, "error");
+  assert_eq!(payload.current_node_id, "draft");
+}
@@ -1,6 +1,8 @@
 //! Identity / pass-through node for compiled graph (start and exit placeholders).
 //! Forwards GraphPayload with current_node_id and completed_nodes updated to this node.
 
+use crate::nodes::run_shutdown::{RunShutdown, next_or_shutdown};
+use crate::nodes::step_recorder::StepRecorder;
 use crate::types::GraphPayload;
 use async_trait::async_trait;
 use std::collections::HashMap;
@@ -19,6 +21,10 @@ pub struct IdentityNode {
   input_ports: Vec<String>,
   /// Output port names.
   output_ports: Vec<String>,
+  /// When set, each forwarded payload is recorded as an execution log step.
+  recorder: Option<StepRecorder>,
+  /// When set, forwarding stops once the run shuts down.
+  shutdown: Option<RunShutdown>,
 }
 
 impl IdentityNode {
@@ -27,8 +33,22 @@ impl IdentityNode {
       name: name.into(),
       input_ports: vec!["in".to_string()],
       output_ports: vec!["out".to_string()],
+      recorder: None,
+      shutdown: None,
     }
   }
+
+  /// Records every forwarded payload to the run's execution log.
+  pub(crate) fn with_step_recorder(mut self, recorder: StepRecorder) -> Self {
+    self.recorder = Some(recorder);
+    self
+  }
+
+  /// Stops forwarding once `shutdown` is triggered.
+  pub(crate) fn with_shutdown(mut self, shutdown: RunShutdown) -> Self {
+    self.shutdown = Some(shutdown);
+    self
+  }
 }
 
 #[async_trait]
@@ -64,6 +84,8 @@ impl Node for IdentityNode {
     Box<dyn std::future::Future<Output = Result<OutputStreams, NodeExecutionError>> + Send + '_>,
   > {
     let name = self.name.clone();
+    let recorder = self.recorder.clone();
+    let shutdown = self.shutdown.clone();
     Box::pin(async move {
       tracing::trace!(node = %name, "IdentityNode executing");
       let Some(mut in_stream) = inputs.remove("in") else {
@@ -81,13 +103,16 @@ impl Node for IdentityNode {
       let (out_tx, out_rx) = tokio::sync::mpsc::channel(16);
 
       tokio::spawn(async move {
-        use futures::StreamExt;
-        while let Some(item) = in_stream.next().await {
+        while let Some(item) = next_or_shutdown(&mut in_stream, shutdown.as_ref()).await {
           tracing::trace!(node = %name, "IdentityNode received item, forwarding");
           tracing::info!(node = %name, "forwarding payload");
           let out_item: Arc<dyn std::any::Any + Send + Sync> =
             if let Ok(payload) = item.clone().downcast::<GraphPayload>() {
-              Arc::new(payload.with_node_completed(&name))
+              let forwarded = payload.with_node_completed(&name);
+              if let Some(ref r) = recorder {
+                r.record_pass_through(&name, &forwarded.context, &forwarded.completed_nodes);
+              }
+              Arc::new(forwarded)
             } else {
               item
             };
@@ -1,6 +1,7 @@
 //! Initialize run context from validated graph.
 
-use crate::types::{AttractorGraph, ExecutionState, ExecutionStepEntry, ResumeState, RunContext};
+use crate::handler::HandlerRegistry;
+use crate::types::{AttractorGraph, ExecutionState, ExecutionStepEntry, RunContext};
 use async_trait::async_trait;
 use std::any::Any;
 use std::collections::HashMap;
@@ -50,23 +51,9 @@ pub(crate) fn create_initial_state(
     completed_nodes: vec![],
     node_outcomes: HashMap::new(),
     step_log,
-  }
-}
-
-/// Builds ExecutionState from resume state (execution log only, no checkpoint.json).
-#[instrument(level = "trace", skip(st))]
-pub(crate) fn create_initial_state_from_resume_state(
-  graph: AttractorGraph,
-  st: &ResumeState,
-  step_log: Option<Vec<ExecutionStepEntry>>,
-) -> ExecutionState {
-  ExecutionState {
-    graph,
-    context: st.context.clone(),
-    current_node_id: st.current_node_id.clone(),
-    completed_nodes: st.completed_nodes.clone(),
-    node_outcomes: HashMap::new(),
-    step_log,
+    agent_cmd: None,
+    stage_dir: None,
+    handlers: HandlerRegistry::default(),
   }
 }
 
@@ -6,8 +6,11 @@ mod apply_context_updates_test;
 mod check_goal_gates;
 #[cfg(test)]
 mod check_goal_gates_test;
-mod codergen_node;
-mod exec_node;
+pub(crate) mod codergen_node;
+pub(crate) mod edge_router_node;
+#[cfg(test)]
+mod edge_router_node_test;
+pub(crate) mod exec_node;
 pub(crate) mod execute_handler;
 #[cfg(test)]
 mod execute_handler_test;
@@ -20,6 +23,9 @@ mod find_start_test;
 mod fix_node;
 #[cfg(test)]
 mod fix_node_test;
+mod handler_node;
+#[cfg(test)]
+mod handler_node_test;
 mod identity_node;
 pub(crate) mod init_context;
 #[cfg(test)]
@@ -28,21 +34,32 @@ mod outcome_router_node;
 mod parse_dot;
 #[cfg(test)]
 mod parse_dot_test;
+pub(crate) mod retry;
+#[cfg(test)]
+mod retry_test;
+pub(crate) mod run_shutdown;
+#[cfg(test)]
+mod run_shutdown_test;
 pub(crate) mod select_edge;
 #[cfg(test)]
 mod select_edge_test;
+pub(crate) mod step_recorder;
 pub(crate) mod validate_graph;
 #[cfg(test)]
 mod validate_graph_test;
 
 pub use apply_context_updates::ApplyContextUpdatesNode;
 pub use check_goal_gates::CheckGoalGatesNode;
+#[allow(deprecated)]
 pub use codergen_node::CodergenNode;
+pub use edge_router_node::EdgeRouterNode;
+#[allow(deprecated)]
 pub use exec_node::ExecNode;
 pub use execution_loop::AttractorExecutionLoopNode;
 pub use execution_loop::AttractorResult;
 pub use find_start::FindStartNode;
 pub use fix_node::FixNode;
+pub use handler_node::HandlerNode;
 pub use identity_node::IdentityNode;
 pub use init_context::InitContextNode;
 pub use outcome_router_node::OutcomeRouterNode;
@@ -1,6 +1,7 @@
 //! Routes payload to success or fail output based on outcome status.
 //! Accepts GraphPayload (routes by payload.outcome) or legacy NodeOutcome.
 
+use crate::nodes::run_shutdown::RunShutdown;
 use crate::types::{GraphPayload, NodeOutcome, OutcomeStatus};
 use async_trait::async_trait;
 use std::collections::HashMap;
@@ -18,6 +19,8 @@ pub struct OutcomeRouterNode {
   input_ports: Vec<String>,
   /// Output port names.
   output_ports: Vec<String>,
+  /// When set, this is the run's terminal router: the first routed item ends the run.
+  shutdown: Option<RunShutdown>,
 }
 
 impl OutcomeRouterNode {
@@ -26,8 +29,16 @@ impl OutcomeRouterNode {
       name: name.into(),
       input_ports: vec!["in".to_string()],
       output_ports: vec!["success".to_string(), "fail".to_string()],
+      shutdown: None,
     }
   }
+
+  /// Makes this the run's terminal router: after routing the first item it triggers `shutdown`
+  /// so every other node of the run stops, then stops itself.
+  pub(crate) fn with_shutdown(mut self, shutdown: RunShutdown) -> Self {
+    self.shutdown = Some(shutdown);
+    self
+  }
 }
 
 #[async_trait]
@@ -63,6 +74,7 @@ impl Node for OutcomeRouterNode {
     Box<dyn std::future::Future<Output = Result<OutputStreams, NodeExecutionError>> + Send + '_>,
   > {
     let name = self.name.clone();
+    let shutdown = self.shutdown.clone();
     Box::pin(async move {
       tracing::trace!(node = %name, "OutcomeRouterNode executing");
       let mut in_stream = inputs.remove("in").ok_or("Missing 'in' input")?;
@@ -95,6 +107,11 @@ impl Node for OutcomeRouterNode {
               .send(Arc::new(o) as Arc<dyn std::any::Any + Send + Sync>)
               .await;
           }
+          if let Some(ref sd) = shutdown {
+            tracing::info!(node = %name, "run finished, shutting down graph");
+            sd.trigger();
+            break;
+          }
         }
       });
 
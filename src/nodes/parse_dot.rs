@@ -13,13 +13,13 @@ use tracing::instrument;
 /// Parses DOT source into AttractorGraph.
 #[instrument(level = "trace", skip(s))]
 pub(crate) fn process_dot(s: &str) -> Result<crate::types::AttractorGraph, String> {
-  dot_parser::parse_dot(s)
+  dot_parser::parse_dot(s).map_err(|e| e.to_string())
 }
 
 /// Result of processing one parse-dot input item.
 pub(crate) enum ParseDotItemResult {
   /// Parsed DOT produced an AttractorGraph.
-  Graph(crate::types::AttractorGraph),
+  Graph(Box<crate::types::AttractorGraph>),
   /// Parse failed with error message.
   ParseError(String),
   /// Input was not a string.
@@ -34,7 +34,7 @@ pub(crate) fn process_parse_dot_item(item: Arc<dyn Any + Send + Sync>) -> ParseD
     Err(_) => return ParseDotItemResult::WrongType,
   };
   match process_dot(&s) {
-    Ok(g) => ParseDotItemResult::Graph(g),
+    Ok(g) => ParseDotItemResult::Graph(Box::new(g)),
     Err(e) => ParseDotItemResult::ParseError(e),
   }
 }
@@ -103,7 +103,7 @@ impl Node for ParseDotNode {
           match process_parse_dot_item(item) {
             ParseDotItemResult::Graph(graph) => {
               let _ = out_tx
-                .send(Arc::new(graph) as Arc<dyn Any + Send + Sync>)
+                .send(Arc::new(*graph) as Arc<dyn Any + Send + Sync>)
                 .await;
             }
             ParseDotItemResult::ParseError(e) => {
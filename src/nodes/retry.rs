@@ -0,0 +1,70 @@
+//! Node retries: a retryable outcome (`Retry`, plus any statuses in the node's `retry_on`)
+//! runs the node again until its retry budget (`max_retries`, else the graph's
+//! `default_max_retry`) is spent; the last attempt then escalates to an error so the node's
+//! fail edge is taken. Waits between attempts follow the node's [RetryPolicy].
+
+use crate::types::{NodeOutcome, OutcomeStatus, RetryPolicy};
+use tracing::instrument;
+
+/// True when `outcome` asks for the node to run again under `policy`.
+pub(crate) fn wants_retry(outcome: &NodeOutcome, policy: &RetryPolicy) -> bool {
+  outcome.status == OutcomeStatus::Retry || policy.retries(outcome.status)
+}
+
+/// Turns the retryable outcome of the final attempt into an error.
+pub(crate) fn retries_exhausted(outcome: NodeOutcome, attempts: u32) -> NodeOutcome {
+  let reason = outcome
+    .failure_reason
+    .as_deref()
+    .unwrap_or("retry requested");
+  NodeOutcome {
+    status: OutcomeStatus::Error,
+    failure_reason: Some(format!(
+      "max retries exceeded after {} attempts: {}",
+      attempts, reason
+    )),
+    ..outcome
+  }
+}
+
+/// Runs `attempt` until it returns a non-retryable outcome or `max_retries` extra attempts
+/// have been made, sleeping (without blocking the runtime) per `policy` between attempts.
+/// Returns the final outcome and its attempt number.
+/// Each attempt that is retried is passed to `on_retry` with its number, so the caller can record
+/// it (the final one is recorded by the caller). Both engines retry through this function.
+#[instrument(level = "trace", skip(policy, on_retry, attempt_fn))]
+pub(crate) async fn run_with_retries<R, F, Fut>(
+  node_id: &str,
+  max_retries: u32,
+  policy: &RetryPolicy,
+  mut on_retry: R,
+  mut attempt_fn: F,
+) -> (NodeOutcome, u32)
+where
+  R: FnMut(u32, &NodeOutcome),
+  F: FnMut() -> Fut,
+  Fut: Future<Output = NodeOutcome>,
+{
+  let mut attempt = 1;
+  loop {
+    let outcome = attempt_fn().await;
+    if !wants_retry(&outcome, policy) {
+      return (outcome, attempt);
+    }
+    if attempt > max_retries {
+      tracing::info!(node = %node_id, attempt, "retry budget spent");
+      return (retries_exhausted(outcome, attempt), attempt);
+    }
+    let delay = policy.delay_for(attempt);
+    tracing::info!(
+      node = %node_id,
+      attempt,
+      delay_ms = delay.as_millis() as u64,
+      reason = ?outcome.failure_reason,
+      "retrying"
+    );
+    on_retry(attempt, &outcome);
+    tokio::time::sleep(delay).await;
+    attempt += 1;
+  }
+}
@@ -0,0 +1,128 @@
+//! Tests for `retry`.
+
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU32, Ordering};
+
+use super::exec_node::run_exec_command;
+use super::retry::{retries_exhausted, run_with_retries, wants_retry};
+use crate::types::{NodeOutcome, OutcomeStatus, RetryPolicy};
+
+/// Handler that returns `Retry` for its first `retries` calls, then success.
+fn flaky(retries: u32) -> (Arc<AtomicU32>, impl Fn() -> NodeOutcome) {
+  let calls = Arc::new(AtomicU32::new(0));
+  let c = Arc::clone(&calls);
+  (calls, move || {
+    if c.fetch_add(1, Ordering::SeqCst) < retries {
+      NodeOutcome::retry("busy")
+    } else {
+      NodeOutcome::success("ok")
+    }
+  })
+}
+
+#[test]
+fn wants_retry_only_for_retry_status() {
+  let policy = RetryPolicy::default();
+  assert!(wants_retry(&NodeOutcome::retry("busy"), &policy));
+  assert!(!wants_retry(&NodeOutcome::error("boom"), &policy));
+  assert!(!wants_retry(&NodeOutcome::success("ok"), &policy));
+}
+
+#[test]
+fn wants_retry_for_errors_when_policy_retries_them() {
+  let policy: RetryPolicy = "none retry_on=error".parse().unwrap();
+  assert!(wants_retry(&NodeOutcome::error("boom"), &policy));
+  assert!(wants_retry(&NodeOutcome::retry("busy"), &policy));
+  assert!(!wants_retry(&NodeOutcome::success("ok"), &policy));
+}
+
+#[test]
+fn retries_exhausted_escalates_to_error() {
+  let o = retries_exhausted(NodeOutcome::retry("busy"), 3);
+  assert_eq!(o.status, OutcomeStatus::Error);
+  assert_eq!(
+    o.failure_reason.as_deref(),
+    Some("max retries exceeded after 3 attempts: busy")
+  );
+}
+
+#[tokio::test]
+async fn exec_tempfail_exit_asks_for_retry() {
+  let o = run_exec_command("exit 75").await;
+  assert_eq!(o.status, OutcomeStatus::Retry);
+  assert_eq!(
+    run_exec_command("exit 1").await.status,
+    OutcomeStatus::Error
+  );
+}
+
+#[tokio::test]
+async fn run_with_retries_reruns_until_success() {
+  let (calls, run) = flaky(2);
+  let mut retried = Vec::new();
+  let (outcome, attempt) = run_with_retries(
+    "n",
+    2,
+    &RetryPolicy::default(),
+    |attempt, outcome| retried.push((attempt, outcome.status)),
+    || std::future::ready(run()),
+  )
+  .await;
+  assert_eq!(outcome.status, OutcomeStatus::Success);
+  assert_eq!(attempt, 3);
+  assert_eq!(calls.load(Ordering::SeqCst), 3);
+  assert_eq!(
+    retried,
+    [(1, OutcomeStatus::Retry), (2, OutcomeStatus::Retry)]
+  );
+}
+
+#[tokio::test]
+async fn run_with_retries_stops_at_budget() {
+  let (calls, run) = flaky(5);
+  let (outcome, attempt) = run_with_retries(
+    "n",
+    1,
+    &RetryPolicy::default(),
+    |_, _| {},
+    || std::future::ready(run()),
+  )
+  .await;
+  assert_eq!(outcome.status, OutcomeStatus::Error);
+  assert_eq!(attempt, 2);
+  assert_eq!(calls.load(Ordering::SeqCst), 2);
+}
+
+#[tokio::test]
+async fn run_with_retries_does_not_rerun_errors() {
+  let calls = Arc::new(AtomicU32::new(0));
+  let c = Arc::clone(&calls);
+  let run = move || {
+    c.fetch_add(1, Ordering::SeqCst);
+    NodeOutcome::error("boom")
+  };
+  let (outcome, attempt) = run_with_retries(
+    "n",
+    3,
+    &RetryPolicy::default(),
+    |_, _| {},
+    || std::future::ready(run()),
+  )
+  .await;
+  assert_eq!(outcome.status, OutcomeStatus::Error);
+  assert_eq!(attempt, 1);
+  assert_eq!(calls.load(Ordering::SeqCst), 1);
+}
+
+#[tokio::test(start_paused = true)]
+async fn run_with_retries_waits_per_policy() {
+  let policy: RetryPolicy = "exponential delay=1s".parse().unwrap();
+  let (_, run) = flaky(3);
+  let started = tokio::time::Instant::now();
+  let (outcome, attempt) =
+    run_with_retries("n", 3, &policy, |_, _| {}, || std::future::ready(run())).await;
+  assert_eq!(outcome.status, OutcomeStatus::Success);
+  assert_eq!(attempt, 4);
+  // 1s + 2s + 4s of backoff on the paused clock.
+  assert!(started.elapsed() >= std::time::Duration::from_secs(7));
+}
@@ -0,0 +1,57 @@
+//! Run-wide shutdown for compiled graphs.
+//!
+//! Cyclic graphs (fix-and-retry loops) never close their input streams on their own: every
+//! node in a loop feeds its own upstream MergeNode. Instead, the terminal exit router triggers
+//! [RunShutdown] once the run has a result; every compiled node then ends its input loop and
+//! drops its senders, so all channels close and the graph completes.
+
+use std::sync::Arc;
+use tokio::sync::watch;
+use tokio_stream::{Stream, StreamExt};
+use tracing::instrument;
+
+/// Shared by every node of one compiled graph; once triggered it stays triggered.
+#[derive(Clone)]
+pub(crate) struct RunShutdown {
+  /// `true` once the run is shutting down.
+  tx: Arc<watch::Sender<bool>>,
+}
+
+impl RunShutdown {
+  pub(crate) fn new() -> Self {
+    Self {
+      tx: Arc::new(watch::Sender::new(false)),
+    }
+  }
+
+  /// Signals every node of the run to stop.
+  pub(crate) fn trigger(&self) {
+    self.tx.send_replace(true);
+  }
+
+  /// Resolves once [RunShutdown::trigger] has been called (immediately if it already was).
+  pub(crate) async fn triggered(&self) {
+    let mut rx = self.tx.subscribe();
+    let _ = rx.wait_for(|stopped| *stopped).await;
+  }
+}
+
+/// Next item from `stream`, or `None` once `shutdown` is triggered (ends a node's input loop).
+/// Without a shutdown signal, this is plain `stream.next()`.
+#[instrument(level = "trace", skip(stream, shutdown))]
+pub(crate) async fn next_or_shutdown<S>(
+  stream: &mut S,
+  shutdown: Option<&RunShutdown>,
+) -> Option<S::Item>
+where
+  S: Stream + Unpin,
+{
+  match shutdown {
+    None => stream.next().await,
+    Some(sd) => tokio::select! {
+      biased;
+      _ = sd.triggered() => None,
+      item = stream.next() => item,
+    },
+  }
+}
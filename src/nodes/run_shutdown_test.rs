@@ -0,0 +1,38 @@
+//! Tests for `RunShutdown` and `next_or_shutdown`.
+
+use super::run_shutdown::{RunShutdown, next_or_shutdown};
+use tokio_stream::wrappers::ReceiverStream;
+
+#[tokio::test]
+async fn next_or_shutdown_yields_items_until_triggered() {
+  let (tx, rx) = tokio::sync::mpsc::channel(4);
+  let mut stream = ReceiverStream::new(rx);
+  let shutdown = RunShutdown::new();
+  tx.send(1_u32).await.unwrap();
+  assert_eq!(
+    next_or_shutdown(&mut stream, Some(&shutdown)).await,
+    Some(1)
+  );
+  shutdown.trigger();
+  // Input is still open (as in a cycle), but the loop ends.
+  tx.send(2_u32).await.unwrap();
+  assert_eq!(next_or_shutdown(&mut stream, Some(&shutdown)).await, None);
+}
+
+#[tokio::test]
+async fn next_or_shutdown_without_signal_reads_until_closed() {
+  let (tx, rx) = tokio::sync::mpsc::channel(4);
+  let mut stream = ReceiverStream::new(rx);
+  tx.send(1_u32).await.unwrap();
+  drop(tx);
+  assert_eq!(next_or_shutdown(&mut stream, None).await, Some(1));
+  assert_eq!(next_or_shutdown(&mut stream, None).await, None);
+}
+
+#[tokio::test]
+async fn triggered_resolves_for_clones_after_trigger() {
+  let shutdown = RunShutdown::new();
+  let clone = shutdown.clone();
+  shutdown.trigger();
+  clone.triggered().await;
+}
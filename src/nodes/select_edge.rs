@@ -1,5 +1,6 @@
 //! Select next edge per attractor-spec §3.3.
 
+use crate::condition::Condition;
 use crate::types::{AttractorEdge, AttractorGraph, NodeOutcome, OutcomeStatus, RunContext};
 use async_trait::async_trait;
 use std::any::Any;
@@ -19,8 +20,8 @@ pub struct SelectEdgeInput {
   pub outcome: NodeOutcome,
   /// Current run context.
   pub context: RunContext,
-  /// The attractor graph.
-  pub graph: AttractorGraph,
+  /// The attractor graph, shared rather than copied per payload.
+  pub graph: Arc<AttractorGraph>,
 }
 
 /// Output: selected next node ID or None if done/fail.
@@ -109,42 +110,36 @@ pub(crate) fn select_edge(input: &SelectEdgeInput) -> SelectEdgeOutput {
   }
 }
 
-/// Evaluates a condition string (e.g. `outcome=Success`, `has_tasks=true`) against context.
-#[instrument(level = "trace", skip(_outcome, context))]
-pub(crate) fn evaluate_condition(cond: &str, _outcome: &NodeOutcome, context: &RunContext) -> bool {
-  let cond = cond.trim();
-  if let Some(stripped) = cond.strip_prefix("outcome=") {
-    let outcome_str = context.get("outcome").map(|s| s.as_str()).unwrap_or("");
-    return stripped.eq_ignore_ascii_case(outcome_str)
-      || (stripped.eq_ignore_ascii_case("success") && outcome_str == "SUCCESS")
-      || (stripped.eq_ignore_ascii_case("fail") && outcome_str == "FAIL");
-  }
-  if let Some(stripped) = cond.strip_prefix("outcome!=") {
-    let outcome_str = context.get("outcome").map(|s| s.as_str()).unwrap_or("");
-    return !stripped.eq_ignore_ascii_case(outcome_str);
-  }
-  // Generic key=value: context key must equal value
-  if let Some((key, value)) = cond.split_once('=') {
-    let key = key.trim();
-    let value = value.trim();
-    if !key.is_empty() {
-      return context
-        .get(key)
-        .map(|v| v.as_str() == value || value.eq_ignore_ascii_case(v))
-        .unwrap_or(false);
-    }
-  }
-  false
+/// Evaluates a parsed edge condition (e.g. `outcome=success && tests_passed=true`) for a node's
+/// outcome and the context after its updates.
+#[instrument(level = "trace", skip(cond, outcome, context))]
+pub(crate) fn evaluate_condition(
+  cond: &Condition,
+  outcome: &NodeOutcome,
+  context: &RunContext,
+) -> bool {
+  cond.evaluate(outcome, context)
 }
 
-/// Normalizes an edge label for comparison (lowercase, trim, strip prefixes).
+/// Normalizes an edge label for comparison (lowercase, trim, strip accelerator prefixes such as
+/// `[Y] `, `Y) ` and `Y - `), so `[A] Approve` and `approve` compare equal.
 #[instrument(level = "trace")]
 pub(crate) fn normalize_label(l: &str) -> String {
-  l.to_lowercase()
-    .trim()
-    .trim_start_matches(|c: char| c == '[' || c.is_ascii_alphabetic())
-    .trim_start_matches([')', ' ', '-'])
-    .to_string()
+  let l = l.trim().to_lowercase();
+  let mut chars = l.chars();
+  let key = chars.next();
+  let after_key = chars.as_str();
+  let rest = if let Some(bracketed) = l.strip_prefix('[') {
+    bracketed.split_once(']').map_or(l.as_str(), |(_, r)| r)
+  } else if key.is_some_and(char::is_alphanumeric) {
+    after_key
+      .strip_prefix(')')
+      .or_else(|| after_key.strip_prefix(' ')?.trim_start().strip_prefix('-'))
+      .unwrap_or(l.as_str())
+  } else {
+    l.as_str()
+  };
+  rest.trim_start_matches([')', ' ', '-']).trim().to_string()
 }
 
 /// Picks the best edge by weight (descending), then lexically by to_node.
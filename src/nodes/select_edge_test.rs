@@ -3,6 +3,7 @@
 use std::collections::HashMap;
 use std::sync::Arc;
 
+use crate::condition::Condition;
 use crate::types::{AttractorEdge, AttractorGraph, AttractorNode, NodeOutcome};
 use futures::StreamExt;
 use streamweave::node::Node;
@@ -22,10 +23,19 @@ fn node(id: &str, shape: &str) -> AttractorNode {
     prompt: None,
     command: None,
     goal_gate: false,
-    max_retries: 0,
+    max_retries: None,
+    retry_policy: None,
+    retry_target: None,
+    fallback_retry_target: None,
+    classes: Vec::new(),
+    attrs: Default::default(),
   }
 }
 
+fn c(source: &str) -> Condition {
+  Condition::parse(source).unwrap()
+}
+
 fn graph(nodes: Vec<AttractorNode>, edges: Vec<AttractorEdge>) -> AttractorGraph {
   let nodes_map: HashMap<String, AttractorNode> =
     nodes.into_iter().map(|n| (n.id.clone(), n)).collect();
@@ -34,6 +44,11 @@ fn graph(nodes: Vec<AttractorNode>, edges: Vec<AttractorEdge>) -> AttractorGraph
     nodes: nodes_map,
     edges,
     default_max_retry: 50,
+    default_retry_policy: Default::default(),
+    retry_target: None,
+    fallback_retry_target: None,
+    name: None,
+    attrs: Default::default(),
   }
 }
 
@@ -42,8 +57,29 @@ fn evaluate_condition_outcome_equals() {
   let mut ctx = HashMap::new();
   ctx.insert("outcome".to_string(), "Success".to_string());
   let o = NodeOutcome::success("x");
-  assert!(evaluate_condition("outcome=Success", &o, &ctx));
-  assert!(evaluate_condition("outcome=success", &o, &ctx));
+  assert!(evaluate_condition(&c("outcome=Success"), &o, &ctx));
+  assert!(evaluate_condition(&c("outcome=success"), &o, &ctx));
+}
+
+#[test]
+fn evaluate_condition_outcome_fail_matches_error_status() {
+  let mut ctx = HashMap::new();
+  ctx.insert("outcome".to_string(), "Error".to_string());
+  let o = NodeOutcome::error("exit 1");
+  assert!(evaluate_condition(&c("outcome=fail"), &o, &ctx));
+  assert!(evaluate_condition(&c("outcome=error"), &o, &ctx));
+  assert!(!evaluate_condition(&c("outcome=success"), &o, &ctx));
+  assert!(evaluate_condition(&c("outcome!=success"), &o, &ctx));
+  assert!(!evaluate_condition(&c("outcome!=fail"), &o, &ctx));
+}
+
+#[test]
+fn evaluate_condition_outcome_partial_success() {
+  let mut ctx = HashMap::new();
+  ctx.insert("outcome".to_string(), "PartialSuccess".to_string());
+  let o = NodeOutcome::success("x");
+  assert!(evaluate_condition(&c("outcome=partial_success"), &o, &ctx));
+  assert!(!evaluate_condition(&c("outcome=success"), &o, &ctx));
 }
 
 #[test]
@@ -51,7 +87,7 @@ fn evaluate_condition_has_tasks_true() {
   let mut ctx = HashMap::new();
   ctx.insert("has_tasks".to_string(), "true".to_string());
   let o = NodeOutcome::success("ok");
-  assert!(evaluate_condition("has_tasks=true", &o, &ctx));
+  assert!(evaluate_condition(&c("has_tasks=true"), &o, &ctx));
 }
 
 #[test]
@@ -59,7 +95,7 @@ fn evaluate_condition_has_tasks_false() {
   let mut ctx = HashMap::new();
   ctx.insert("has_tasks".to_string(), "false".to_string());
   let o = NodeOutcome::success("ok");
-  assert!(evaluate_condition("has_tasks=false", &o, &ctx));
+  assert!(evaluate_condition(&c("has_tasks=false"), &o, &ctx));
 }
 
 #[test]
@@ -78,15 +114,17 @@ fn select_by_condition_has_tasks() {
         from_node: "check".to_string(),
         to_node: "claim".to_string(),
         label: None,
-        condition: Some("has_tasks=true".to_string()),
+        condition: Some("has_tasks=true".parse().unwrap()),
         weight: 0,
+        attrs: Default::default(),
       },
       AttractorEdge {
         from_node: "check".to_string(),
         to_node: "exit".to_string(),
         label: None,
-        condition: Some("has_tasks=false".to_string()),
+        condition: Some("has_tasks=false".parse().unwrap()),
         weight: 0,
+        attrs: Default::default(),
       },
     ],
   );
@@ -94,7 +132,7 @@ fn select_by_condition_has_tasks() {
     node_id: "check".to_string(),
     outcome: NodeOutcome::success("ok"),
     context: ctx,
-    graph: g,
+    graph: Arc::new(g),
   };
   let out = select_edge(&input);
   assert_eq!(out.next_node_id.as_deref(), Some("claim"));
@@ -106,6 +144,54 @@ fn normalize_label_strips_prefixes() {
   assert!(n.contains("x"));
 }
 
+#[test]
+fn normalize_label_keeps_plain_words_and_strips_accelerators() {
+  assert_eq!(normalize_label("Approve"), "approve");
+  assert_eq!(normalize_label("[A] Approve"), "approve");
+  assert_eq!(normalize_label("R) Revise"), "revise");
+  assert_eq!(normalize_label("R - Revise"), "revise");
+  assert_eq!(normalize_label("x-ray"), "x-ray");
+  assert_ne!(normalize_label("Approve"), normalize_label("Revise"));
+}
+
+#[test]
+fn select_by_preferred_label_distinguishes_word_labels() {
+  let g = graph(
+    vec![
+      node("review", "box"),
+      node("ship", "box"),
+      node("rework", "box"),
+    ],
+    vec![
+      AttractorEdge {
+        from_node: "review".to_string(),
+        to_node: "ship".to_string(),
+        label: Some("[A] Approve".to_string()),
+        condition: None,
+        weight: 0,
+        attrs: Default::default(),
+      },
+      AttractorEdge {
+        from_node: "review".to_string(),
+        to_node: "rework".to_string(),
+        label: Some("[R] Revise".to_string()),
+        condition: None,
+        weight: 0,
+        attrs: Default::default(),
+      },
+    ],
+  );
+  let mut outcome = NodeOutcome::success("ok");
+  outcome.preferred_label = Some("revise".to_string());
+  let input = SelectEdgeInput {
+    node_id: "review".to_string(),
+    outcome,
+    context: HashMap::new(),
+    graph: Arc::new(g),
+  };
+  assert_eq!(select_edge(&input).next_node_id.as_deref(), Some("rework"));
+}
+
 #[test]
 fn best_by_weight_then_lexical_picks_highest_weight() {
   let edges = [
@@ -115,6 +201,7 @@ fn best_by_weight_then_lexical_picks_highest_weight() {
       label: None,
       condition: None,
       weight: 1,
+      attrs: Default::default(),
     },
     AttractorEdge {
       from_node: "a".to_string(),
@@ -122,6 +209,7 @@ fn best_by_weight_then_lexical_picks_highest_weight() {
       label: None,
       condition: None,
       weight: 10,
+      attrs: Default::default(),
     },
   ];
   let refs: Vec<_> = edges.iter().collect();
@@ -150,13 +238,14 @@ fn select_unconditional_edge() {
       label: None,
       condition: None,
       weight: 0,
+      attrs: Default::default(),
     }],
   );
   let input = SelectEdgeInput {
     node_id: "a".to_string(),
     outcome: NodeOutcome::success("ok"),
     context: HashMap::new(),
-    graph: g,
+    graph: Arc::new(g),
   };
   let out = select_edge(&input);
   assert_eq!(out.next_node_id.as_deref(), Some("b"));
@@ -170,7 +259,7 @@ fn done_when_no_edges_and_success() {
     node_id: "exit".to_string(),
     outcome: NodeOutcome::success("done"),
     context: HashMap::new(),
-    graph: g,
+    graph: Arc::new(g),
   };
   let out = select_edge(&input);
   assert!(out.next_node_id.is_none());
@@ -186,7 +275,7 @@ fn done_false_when_no_edges_and_fail() {
     node_id: "x".to_string(),
     outcome: NodeOutcome::error("err"),
     context: ctx,
-    graph: g,
+    graph: Arc::new(g),
   };
   let out = select_edge(&input);
   assert!(out.next_node_id.is_none());
@@ -204,15 +293,17 @@ fn select_by_condition_outcome_eq() {
         from_node: "a".to_string(),
         to_node: "b".to_string(),
         label: None,
-        condition: Some("outcome=Success".to_string()),
+        condition: Some("outcome=Success".parse().unwrap()),
         weight: 5,
+        attrs: Default::default(),
       },
       AttractorEdge {
         from_node: "a".to_string(),
         to_node: "c".to_string(),
         label: None,
-        condition: Some("outcome=Fail".to_string()),
+        condition: Some("outcome=Fail".parse().unwrap()),
         weight: 10,
+        attrs: Default::default(),
       },
     ],
   );
@@ -220,7 +311,7 @@ fn select_by_condition_outcome_eq() {
     node_id: "a".to_string(),
     outcome: NodeOutcome::success("ok"),
     context: ctx,
-    graph: g,
+    graph: Arc::new(g),
   };
   let out = select_edge(&input);
   assert_eq!(out.next_node_id.as_deref(), Some("b"));
@@ -236,15 +327,16 @@ fn select_by_condition_outcome_neq() {
       from_node: "a".to_string(),
       to_node: "b".to_string(),
       label: None,
-      condition: Some("outcome!=Success".to_string()),
+      condition: Some("outcome!=Success".parse().unwrap()),
       weight: 0,
+      attrs: Default::default(),
     }],
   );
   let input = SelectEdgeInput {
     node_id: "a".to_string(),
     outcome: NodeOutcome::error("x"),
     context: ctx,
-    graph: g,
+    graph: Arc::new(g),
   };
   let out = select_edge(&input);
   assert_eq!(out.next_node_id.as_deref(), Some("b"));
@@ -261,6 +353,7 @@ fn select_by_preferred_label() {
         label: Some("Next".to_string()),
         condition: None,
         weight: 0,
+        attrs: Default::default(),
       },
       AttractorEdge {
         from_node: "a".to_string(),
@@ -268,6 +361,7 @@ fn select_by_preferred_label() {
         label: Some("Other".to_string()),
         condition: None,
         weight: 0,
+        attrs: Default::default(),
       },
     ],
   );
@@ -277,7 +371,7 @@ fn select_by_preferred_label() {
     node_id: "a".to_string(),
     outcome,
     context: HashMap::new(),
-    graph: g,
+    graph: Arc::new(g),
   };
   let out = select_edge(&input);
   assert_eq!(out.next_node_id.as_deref(), Some("b"));
@@ -294,6 +388,7 @@ fn select_by_suggested_next_ids() {
         label: None,
         condition: None,
         weight: 0,
+        attrs: Default::default(),
       },
       AttractorEdge {
         from_node: "a".to_string(),
@@ -301,6 +396,7 @@ fn select_by_suggested_next_ids() {
         label: None,
         condition: None,
         weight: 0,
+        attrs: Default::default(),
       },
     ],
   );
@@ -310,7 +406,7 @@ fn select_by_suggested_next_ids() {
     node_id: "a".to_string(),
     outcome,
     context: HashMap::new(),
-    graph: g,
+    graph: Arc::new(g),
   };
   let out = select_edge(&input);
   assert_eq!(out.next_node_id.as_deref(), Some("c"));
@@ -327,6 +423,7 @@ fn select_best_by_weight_then_lexical() {
         label: None,
         condition: None,
         weight: 5,
+        attrs: Default::default(),
       },
       AttractorEdge {
         from_node: "a".to_string(),
@@ -334,6 +431,7 @@ fn select_best_by_weight_then_lexical() {
         label: None,
         condition: None,
         weight: 10,
+        attrs: Default::default(),
       },
     ],
   );
@@ -341,7 +439,7 @@ fn select_best_by_weight_then_lexical() {
     node_id: "a".to_string(),
     outcome: NodeOutcome::success("ok"),
     context: HashMap::new(),
-    graph: g,
+    graph: Arc::new(g),
   };
   let out = select_edge(&input);
   assert_eq!(out.next_node_id.as_deref(), Some("c"));
@@ -379,13 +477,14 @@ async fn node_execute_selects_edge() {
       label: None,
       condition: None,
       weight: 0,
+      attrs: Default::default(),
     }],
   );
   let input = SelectEdgeInput {
     node_id: "a".to_string(),
     outcome: NodeOutcome::success("ok"),
     context: HashMap::new(),
-    graph: g,
+    graph: Arc::new(g),
   };
   let sel_node = SelectEdgeNode::new("sel");
   let (tx, rx) = tokio::sync::mpsc::channel(4);
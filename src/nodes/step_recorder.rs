@@ -0,0 +1,155 @@
+//! Step recording for compiled graph nodes: turns one node execution into an
+//! [ExecutionStepEntry] and appends it to the run's [ExecutionLogSink].
+
+use crate::execution_log_io::ExecutionLogSink;
+use crate::parallel::next_node_id;
+use crate::types::{AttractorGraph, ExecutionStepEntry, NodeOutcome, RunContext};
+use std::fmt;
+use std::sync::Arc;
+use tracing::instrument;
+
+/// Shared by every node of one compiled graph; records steps in the order nodes finish.
+#[derive(Clone)]
+pub(crate) struct StepRecorder {
+  /// Sink that numbers and persists steps.
+  sink: ExecutionLogSink,
+  /// AST used to look up handler types and select the next node for the log.
+  graph: Arc<AttractorGraph>,
+  /// Parallel branch the recorded steps run in, if any.
+  branch: Option<String>,
+}
+
+impl fmt::Debug for StepRecorder {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    f.debug_struct("StepRecorder")
+      .field("branch", &self.branch)
+      .finish_non_exhaustive()
+  }
+}
+
+impl StepRecorder {
+  pub(crate) fn new(sink: ExecutionLogSink, graph: Arc<AttractorGraph>) -> Self {
+    Self {
+      sink,
+      graph,
+      branch: None,
+    }
+  }
+
+  /// A recorder for the steps of parallel branch `branch` (named by its first node).
+  pub(crate) fn in_branch(&self, branch: &str) -> Self {
+    Self {
+      branch: Some(branch.to_string()),
+      ..self.clone()
+    }
+  }
+
+  /// Records one step for `node_id`; `next_node_id` is selected per spec §3.3 from `context_after`
+  /// (for a parallel node, its fan-in).
+  /// Persist failures are logged, not propagated: the run itself continues.
+  #[instrument(level = "trace", skip(self, context_before, outcome, context_after))]
+  pub(crate) fn record(
+    &self,
+    node_id: &str,
+    attempt: u32,
+    context_before: RunContext,
+    outcome: &NodeOutcome,
+    context_after: &RunContext,
+    completed_nodes_after: &[String],
+  ) {
+    let next_node_id = self
+      .graph
+      .nodes
+      .get(node_id)
+      .and_then(|node| next_node_id(&self.graph, node, outcome, context_after));
+    self.push(
+      node_id,
+      attempt,
+      context_before,
+      outcome,
+      context_after,
+      next_node_id,
+      completed_nodes_after,
+    );
+  }
+
+  /// Records an attempt that asked for a retry: the context is unchanged and the next node is
+  /// `node_id` itself, so resuming from this step reruns the node.
+  pub(crate) fn record_retry(
+    &self,
+    node_id: &str,
+    attempt: u32,
+    context: &RunContext,
+    outcome: &NodeOutcome,
+    completed_nodes: &[String],
+  ) {
+    self.push(
+      node_id,
+      attempt,
+      context.clone(),
+      outcome,
+      context,
+      Some(node_id.to_string()),
+      completed_nodes,
+    );
+  }
+
+  #[allow(clippy::too_many_arguments)]
+  fn push(
+    &self,
+    node_id: &str,
+    attempt: u32,
+    context_before: RunContext,
+    outcome: &NodeOutcome,
+    context_after: &RunContext,
+    next_node_id: Option<String>,
+    completed_nodes_after: &[String],
+  ) {
+    let handler_type = self
+      .graph
+      .nodes
+      .get(node_id)
+      .and_then(|n| n.handler_type.clone());
+    let entry = ExecutionStepEntry::new(
+      0,
+      node_id,
+      handler_type,
+      context_before,
+      outcome.clone(),
+      context_after.clone(),
+      next_node_id,
+      completed_nodes_after.to_vec(),
+    )
+    .with_attempt(attempt)
+    .with_branch(self.branch.clone());
+    if let Err(e) = self.sink.record(entry) {
+      tracing::warn!(node = %node_id, error = %e, "failed to persist execution log step");
+    }
+  }
+
+  /// Records a start/exit pass-through step with the same outcome notes as the execution loop.
+  pub(crate) fn record_pass_through(
+    &self,
+    node_id: &str,
+    context: &RunContext,
+    completed_nodes_after: &[String],
+  ) {
+    let notes = match self
+      .graph
+      .nodes
+      .get(node_id)
+      .and_then(|n| n.handler_type.as_deref())
+    {
+      Some("exit") => "Exit",
+      _ => "Start",
+    };
+    self.record(
+      node_id,
+      1,
+      context.clone(),
+      &NodeOutcome::success(notes),
+      context,
+      completed_nodes_after,
+    );
+  }
+}
@@ -1,9 +1,15 @@
 //! Validate parsed Attractor graph (lint rules per attractor-spec §7).
 
-use crate::types::AttractorGraph;
+use crate::condition::Condition;
+use crate::dot_parser::resolve_handler_from_shape;
+use crate::handler::{CONDITIONAL, HandlerRegistry, handler_type_of};
+use crate::lint::{FnRule, LintRegistry, LintRule};
+use crate::parallel::{JoinPolicy, PARALLEL, fan_in_of};
+use crate::tool::{TOOL, arg_variables, tool_args};
+use crate::types::{AttractorGraph, Diagnostic, NodeOutcome, Severity};
 use async_trait::async_trait;
 use std::any::Any;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet, VecDeque};
 use std::pin::Pin;
 use std::sync::Arc;
 use streamweave::node::{InputStreams, Node, NodeExecutionError, OutputStreams};
@@ -34,16 +40,861 @@ impl ValidateGraphNode {
   }
 }
 
-/// Validates the graph (exactly one start, one exit) per attractor-spec §7.
+/// Context keys every run has before any node declares them: the goal, and the outcome of the
+/// node that just ran.
+const BUILTIN_CONTEXT_KEYS: [&str; 4] = ["goal", "graph.goal", "outcome", "preferred_label"];
+
+/// Checks the graph against the built-in lint rules of attractor-spec §7 and returns every
+/// finding, errors first, then in the order of the rules below:
+///
+/// | Rule | Severity | Checks |
+/// |------|----------|--------|
+/// | `start_node` | error | exactly one start node |
+/// | `terminal_node` | error | exactly one exit node |
+/// | `edge_target_exists` | error | both ends of every edge are nodes |
+/// | `start_no_incoming` | error | no edge enters the start node |
+/// | `exit_no_outgoing` | error | no edge leaves the exit node |
+/// | `reachability` | error | every node is reachable from the start node |
+/// | `cycle_has_escape` | error | every cycle has an edge leaving it |
+/// | `cycle_reaches_exit` | error | every cycle has a path to the exit node |
+/// | `no_dead_ends` | warning | every node but exit has an outgoing edge |
+/// | `condition_syntax` | error | every edge `condition` parses |
+/// | `retry_target_exists` | error | retry targets name existing nodes |
+/// | `exec_has_command` | error | exec nodes have a `command` (DSL rule §2.2) |
+/// | `tool_has_command` | error | tool nodes have a `tool_command` and object `tool_args` |
+/// | `parallel_join` | error | parallel nodes have a fan-in node and a valid `join_policy` |
+/// | `conditional_routes` | error | conditional nodes have an edge that can be taken |
+/// | `type_known` | error | every node's handler type has a handler |
+/// | `goal_gate_has_retry` | warning | goal gates have somewhere to retry |
+/// | `prompt_on_llm_nodes` | warning | codergen nodes have a prompt |
+/// | `context_key_produced` | warning | context keys are produced before they are read |
+///
+/// `type_known` knows only the built-in handlers; [HandlerRegistry::lint_rule] checks against
+/// a registry with custom ones. To add rules or change their levels, use a [LintRegistry].
 #[instrument(level = "trace")]
-pub(crate) fn validate(graph: &AttractorGraph) -> Result<(), String> {
-  if graph.find_start().is_none() {
-    return Err("Graph must have exactly one start node (shape=Mdiamond)".to_string());
+pub fn validate(graph: &AttractorGraph) -> Vec<Diagnostic> {
+  LintRegistry::default().validate(graph)
+}
+
+/// Validates the graph with the built-in rules and fails if there are any errors; otherwise
+/// returns the warnings and infos. The error joins every error message, one per line.
+#[instrument(level = "trace")]
+pub fn validate_or_raise(graph: &AttractorGraph) -> Result<Vec<Diagnostic>, String> {
+  LintRegistry::default().validate_or_raise(graph)
+}
+
+/// The built-in rules, in the order [validate] documents.
+pub(crate) fn builtin_rules() -> Vec<Arc<dyn LintRule>> {
+  vec![
+    Arc::new(FnRule::new("start_node", start_node)),
+    Arc::new(FnRule::new("terminal_node", terminal_node)),
+    Arc::new(FnRule::new("edge_target_exists", edge_target_exists)),
+    Arc::new(FnRule::new("start_no_incoming", start_no_incoming)),
+    Arc::new(FnRule::new("exit_no_outgoing", exit_no_outgoing)),
+    Arc::new(FnRule::new("reachability", reachability)),
+    Arc::new(FnRule::new("cycle_has_escape", cycle_has_escape)),
+    Arc::new(FnRule::new("cycle_reaches_exit", cycle_reaches_exit)),
+    Arc::new(FnRule::new("no_dead_ends", no_dead_ends)),
+    Arc::new(FnRule::new("condition_syntax", condition_syntax)),
+    Arc::new(FnRule::new("retry_target_exists", retry_target_exists)),
+    Arc::new(FnRule::new("exec_has_command", exec_has_command)),
+    Arc::new(FnRule::new("tool_has_command", tool_has_command)),
+    Arc::new(FnRule::new("parallel_join", parallel_join)),
+    Arc::new(FnRule::new("conditional_routes", conditional_routes)),
+    Arc::new(FnRule::new("type_known", type_known)),
+    Arc::new(FnRule::new("goal_gate_has_retry", goal_gate_has_retry)),
+    Arc::new(FnRule::new("prompt_on_llm_nodes", prompt_on_llm_nodes)),
+    Arc::new(FnRule::new("context_key_produced", context_key_produced)),
+  ]
+}
+
+fn start_node(graph: &AttractorGraph) -> Vec<Diagnostic> {
+  let starts = start_ids(graph);
+  if starts.len() == 1 {
+    return vec![];
+  }
+  vec![
+    Diagnostic::new(
+      "start_node",
+      Severity::Error,
+      format!(
+        "Graph must have exactly one start node (shape=Mdiamond), found {}",
+        describe_ids(&starts)
+      ),
+    )
+    .with_fix("Mark one node with shape=Mdiamond"),
+  ]
+}
+
+fn terminal_node(graph: &AttractorGraph) -> Vec<Diagnostic> {
+  let exits = exit_ids(graph);
+  if exits.len() == 1 {
+    return vec![];
+  }
+  vec![
+    Diagnostic::new(
+      "terminal_node",
+      Severity::Error,
+      format!(
+        "Graph must have exactly one exit node (shape=Msquare), found {}",
+        describe_ids(&exits)
+      ),
+    )
+    .with_fix("Mark one node with shape=Msquare"),
+  ]
+}
+
+fn edge_target_exists(graph: &AttractorGraph) -> Vec<Diagnostic> {
+  let mut diagnostics = Vec::new();
+  for e in &graph.edges {
+    for end in [&e.from_node, &e.to_node] {
+      if !graph.nodes.contains_key(end) {
+        diagnostics.push(
+          Diagnostic::new(
+            "edge_target_exists",
+            Severity::Error,
+            format!(
+              "Edge {} -> {} refers to unknown node '{}'",
+              e.from_node, e.to_node, end
+            ),
+          )
+          .with_edge(&e.from_node, &e.to_node)
+          .with_fix(format!("Declare node '{}' or fix the edge", end)),
+        );
+      }
+    }
+  }
+  diagnostics
+}
+
+fn start_no_incoming(graph: &AttractorGraph) -> Vec<Diagnostic> {
+  let [start] = start_ids(graph)[..] else {
+    return vec![];
+  };
+  graph
+    .edges
+    .iter()
+    .filter(|e| e.to_node == start)
+    .map(|e| {
+      Diagnostic::new(
+        "start_no_incoming",
+        Severity::Error,
+        format!(
+          "Start node '{}' has an incoming edge from '{}'",
+          e.to_node, e.from_node
+        ),
+      )
+      .with_edge(&e.from_node, &e.to_node)
+      .with_fix("Point the edge at the first node after start instead")
+    })
+    .collect()
+}
+
+fn exit_no_outgoing(graph: &AttractorGraph) -> Vec<Diagnostic> {
+  let [exit] = exit_ids(graph)[..] else {
+    return vec![];
+  };
+  graph
+    .outgoing_edges(exit)
+    .into_iter()
+    .map(|e| {
+      Diagnostic::new(
+        "exit_no_outgoing",
+        Severity::Error,
+        format!(
+          "Exit node '{}' has an outgoing edge to '{}'",
+          e.from_node, e.to_node
+        ),
+      )
+      .with_edge(&e.from_node, &e.to_node)
+      .with_fix("Remove the edge; the pipeline ends at the exit node")
+    })
+    .collect()
+}
+
+fn reachability(graph: &AttractorGraph) -> Vec<Diagnostic> {
+  let [start] = start_ids(graph)[..] else {
+    return vec![];
+  };
+  let reachable = reachable_from(graph, start);
+  sorted_ids(graph)
+    .into_iter()
+    .filter(|id| !reachable.contains(id))
+    .map(|id| {
+      Diagnostic::new(
+        "reachability",
+        Severity::Error,
+        format!("Node '{}' is not reachable from start node '{}'", id, start),
+      )
+      .with_node(id)
+      .with_fix(format!("Add an edge into '{}' or remove it", id))
+    })
+    .collect()
+}
+
+/// A cycle whose every outgoing edge leads back into it, such as a fix loop with no edge
+/// out on success, runs until the iteration limit.
+fn cycle_has_escape(graph: &AttractorGraph) -> Vec<Diagnostic> {
+  trapped_cycles(graph)
+    .into_iter()
+    .filter(|(_, closed)| *closed)
+    .map(|(cycle, _)| {
+      Diagnostic::new(
+        "cycle_has_escape",
+        Severity::Error,
+        format!(
+          "Cycle {} has no edge leaving it, so a run that enters it never ends",
+          cycle.join(" -> ")
+        ),
+      )
+      .with_node(cycle[0])
+      .with_fix("Add an edge out of the cycle toward exit, e.g. on condition=\"outcome=success\"")
+    })
+    .collect()
+}
+
+/// A cycle with edges out of it, none of which lead on to exit.
+fn cycle_reaches_exit(graph: &AttractorGraph) -> Vec<Diagnostic> {
+  trapped_cycles(graph)
+    .into_iter()
+    .filter(|(_, closed)| !*closed)
+    .map(|(cycle, _)| {
+      Diagnostic::new(
+        "cycle_reaches_exit",
+        Severity::Error,
+        format!("Cycle {} has no path to the exit node", cycle.join(" -> ")),
+      )
+      .with_node(cycle[0])
+      .with_fix("Route an edge from the cycle, or from where it leads, to exit")
+    })
+    .collect()
+}
+
+/// A node other than exit with no outgoing edges ends the run there, with that node's outcome.
+fn no_dead_ends(graph: &AttractorGraph) -> Vec<Diagnostic> {
+  sorted_ids(graph)
+    .into_iter()
+    .filter(|id| !graph.nodes[*id].is_exit() && graph.outgoing_edges(id).is_empty())
+    .map(|id| {
+      Diagnostic::new(
+        "no_dead_ends",
+        Severity::Warning,
+        format!(
+          "Node '{}' has no outgoing edges and is not the exit node",
+          id
+        ),
+      )
+      .with_node(id)
+      .with_fix(format!("Add an edge from '{}' toward exit", id))
+    })
+    .collect()
+}
+
+/// Conditions are parsed when a graph is loaded, so this only catches graphs built in code
+/// with a `condition` attribute that was never parsed.
+fn condition_syntax(graph: &AttractorGraph) -> Vec<Diagnostic> {
+  let mut diagnostics = Vec::new();
+  for e in &graph.edges {
+    if let Some(c) = e.attrs.get("condition")
+      && let Err(err) = Condition::parse(c)
+    {
+      diagnostics.push(
+        Diagnostic::new(
+          "condition_syntax",
+          Severity::Error,
+          format!(
+            "Invalid condition {:?} on edge {} -> {}: {}",
+            c, e.from_node, e.to_node, err
+          ),
+        )
+        .with_edge(&e.from_node, &e.to_node),
+      );
+    }
+  }
+  diagnostics
+}
+
+fn retry_target_exists(graph: &AttractorGraph) -> Vec<Diagnostic> {
+  let node_targets = sorted_ids(graph).into_iter().flat_map(|id| {
+    let n = &graph.nodes[id];
+    [
+      ("retry_target", n.retry_target.as_ref()),
+      ("fallback_retry_target", n.fallback_retry_target.as_ref()),
+    ]
+    .map(|(attr, t)| (format!("{} on node '{}'", attr, id), Some(id), t))
+  });
+  let graph_targets = [
+    ("graph retry_target", graph.retry_target.as_ref()),
+    (
+      "graph fallback_retry_target",
+      graph.fallback_retry_target.as_ref(),
+    ),
+  ]
+  .map(|(attr, t)| (attr.to_string(), None, t));
+  let mut diagnostics = Vec::new();
+  for (attr, node_id, target) in node_targets.chain(graph_targets) {
+    if let Some(t) = target
+      && !graph.nodes.contains_key(t)
+    {
+      let mut d = Diagnostic::new(
+        "retry_target_exists",
+        Severity::Error,
+        format!("{} refers to unknown node '{}'", attr, t),
+      );
+      if let Some(id) = node_id {
+        d = d.with_node(id);
+      }
+      diagnostics.push(d);
+    }
+  }
+  diagnostics
+}
+
+fn exec_has_command(graph: &AttractorGraph) -> Vec<Diagnostic> {
+  sorted_ids(graph)
+    .into_iter()
+    .filter(|id| {
+      let n = &graph.nodes[*id];
+      n.handler_type.as_deref() == Some("exec") && n.command.is_none()
+    })
+    .map(|id| {
+      Diagnostic::new(
+        "exec_has_command",
+        Severity::Error,
+        format!("exec node '{}' requires a command attribute", id),
+      )
+      .with_node(id)
+      .with_fix(format!("Add command=\"...\" to '{}'", id))
+    })
+    .collect()
+}
+
+fn tool_has_command(graph: &AttractorGraph) -> Vec<Diagnostic> {
+  let mut diagnostics = Vec::new();
+  for id in sorted_ids(graph) {
+    let node = &graph.nodes[id];
+    if handler_type_of(node) != TOOL {
+      continue;
+    }
+    if !node.attrs.contains_key("tool_command") {
+      diagnostics.push(
+        Diagnostic::new(
+          "tool_has_command",
+          Severity::Error,
+          format!("tool node '{}' requires a tool_command attribute", id),
+        )
+        .with_node(id)
+        .with_fix(format!("Add tool_command=\"...\" to '{}'", id)),
+      );
+    }
+    if let Err(e) = tool_args(node) {
+      diagnostics.push(Diagnostic::new("tool_has_command", Severity::Error, e).with_node(id));
+    }
+  }
+  diagnostics
+}
+
+fn parallel_join(graph: &AttractorGraph) -> Vec<Diagnostic> {
+  let mut diagnostics = Vec::new();
+  for id in sorted_ids(graph) {
+    let node = &graph.nodes[id];
+    if handler_type_of(node) != PARALLEL {
+      continue;
+    }
+    if fan_in_of(graph, id).is_none() {
+      diagnostics.push(
+        Diagnostic::new(
+          "parallel_join",
+          Severity::Error,
+          format!(
+            "Parallel node '{}' has no parallel.fan_in node where its branches meet",
+            id
+          ),
+        )
+        .with_node(id)
+        .with_fix("Route every branch to a node with shape=tripleoctagon"),
+      );
+    }
+    if let Err(e) = JoinPolicy::of(node) {
+      diagnostics.push(Diagnostic::new("parallel_join", Severity::Error, e).with_node(id));
+    }
+  }
+  diagnostics
+}
+
+/// A conditional node always succeeds, so when all its edges are conditional, a condition
+/// that the success outcome alone makes false (such as `outcome=fail`) can never route.
+fn conditional_routes(graph: &AttractorGraph) -> Vec<Diagnostic> {
+  let success = NodeOutcome::success("");
+  sorted_ids(graph)
+    .into_iter()
+    .filter(|id| {
+      let edges = graph.outgoing_edges(id);
+      handler_type_of(&graph.nodes[*id]) == CONDITIONAL
+        && !edges.is_empty()
+        && edges.iter().all(|e| {
+          e.condition
+            .as_ref()
+            .is_some_and(|c| c.expr().decided_by_outcome(&success) == Some(false))
+        })
+    })
+    .map(|id| {
+      Diagnostic::new(
+        "conditional_routes",
+        Severity::Error,
+        format!(
+          "Conditional node '{}' has no unconditional edge and none of its conditions can match: \
+           its outcome is always success",
+          id
+        ),
+      )
+      .with_node(id)
+      .with_fix(format!(
+        "Route '{}' on context keys, or add an edge without a condition",
+        id
+      ))
+    })
+    .collect()
+}
+
+fn type_known(graph: &AttractorGraph) -> Vec<Diagnostic> {
+  unregistered_handler_types(graph, &HandlerRegistry::default())
+}
+
+/// `type_known` against `handlers`: nodes whose handler type has no handler there.
+pub(crate) fn unregistered_handler_types(
+  graph: &AttractorGraph,
+  handlers: &HandlerRegistry,
+) -> Vec<Diagnostic> {
+  let known = handlers.handler_types();
+  sorted_ids(graph)
+    .into_iter()
+    .filter_map(|id| {
+      let t = handler_type_of(&graph.nodes[id]);
+      (!known.contains(&t)).then(|| {
+        Diagnostic::new(
+          "type_known",
+          Severity::Error,
+          format!("Node '{}' has type '{}', which has no handler", id, t),
+        )
+        .with_node(id)
+        .with_fix(format!(
+          "Use one of: {}; or register a handler for '{}'",
+          known.join(", "),
+          t
+        ))
+      })
+    })
+    .collect()
+}
+
+fn goal_gate_has_retry(graph: &AttractorGraph) -> Vec<Diagnostic> {
+  sorted_ids(graph)
+    .into_iter()
+    .filter(|id| graph.nodes[*id].goal_gate && graph.retry_target_for(id).is_none())
+    .map(|id| {
+      Diagnostic::new(
+        "goal_gate_has_retry",
+        Severity::Warning,
+        format!(
+          "Goal gate '{}' has no retry_target; an unmet gate fails the run",
+          id
+        ),
+      )
+      .with_node(id)
+      .with_fix("Set retry_target on the node or the graph")
+    })
+    .collect()
+}
+
+fn prompt_on_llm_nodes(graph: &AttractorGraph) -> Vec<Diagnostic> {
+  sorted_ids(graph)
+    .into_iter()
+    .filter(|id| {
+      let n = &graph.nodes[*id];
+      let handler = n
+        .handler_type
+        .clone()
+        .or_else(|| resolve_handler_from_shape(&n.shape));
+      handler.as_deref() == Some("codergen") && !n.is_start() && !n.is_exit() && n.prompt.is_none()
+    })
+    .map(|id| {
+      Diagnostic::new(
+        "prompt_on_llm_nodes",
+        Severity::Warning,
+        format!("Codergen node '{}' has no prompt", id),
+      )
+      .with_node(id)
+      .with_fix(format!("Add a prompt attribute to '{}'", id))
+    })
+    .collect()
+}
+
+/// A context key read by a prompt, tool arguments or an exec command (`$key`), a `consumes`
+/// attribute or an edge condition must be in the `produces` of some node that can run before the
+/// read. Commands are shell, where most `$name`s are shell variables (`$HOME`), so a command's
+/// `$name` is only checked when some node in the graph produces `name`.
+fn context_key_produced(graph: &AttractorGraph) -> Vec<Diagnostic> {
+  let mut incoming: HashMap<&str, Vec<&str>> = HashMap::new();
+  for e in &graph.edges {
+    incoming
+      .entry(e.to_node.as_str())
+      .or_default()
+      .push(e.from_node.as_str());
+  }
+  // Keys set by `id`'s ancestors, plus by `id` itself when `inclusive`.
+  let produced_before = |id: &str, inclusive: bool| -> HashSet<String> {
+    let mut seen: HashSet<&str> = HashSet::new();
+    let mut stack = vec![id];
+    if inclusive {
+      seen.insert(id);
+    }
+    while let Some(at) = stack.pop() {
+      for &from in incoming.get(at).into_iter().flatten() {
+        if seen.insert(from) {
+          stack.push(from);
+        }
+      }
+    }
+    let declared = seen
+      .into_iter()
+      .filter_map(|n| graph.nodes.get(n))
+      .flat_map(|n| n.produces());
+    BUILTIN_CONTEXT_KEYS
+      .iter()
+      .map(|k| k.to_string())
+      .chain(declared)
+      .map(|k| bare_context_key(&k).to_string())
+      .collect()
+  };
+  let unproduced = |key: &str, message: String| {
+    Diagnostic::new("context_key_produced", Severity::Warning, message).with_fix(format!(
+      "Add produces=\"{}\" to the node that sets it",
+      bare_context_key(key)
+    ))
+  };
+
+  let produced_anywhere: HashSet<String> = graph
+    .nodes
+    .values()
+    .flat_map(|n| n.produces())
+    .map(|k| bare_context_key(&k).to_string())
+    .collect();
+
+  let mut diagnostics = Vec::new();
+  for id in sorted_ids(graph) {
+    let n = &graph.nodes[id];
+    let command_keys: Vec<&str> = match (handler_type_of(n), n.command.as_deref()) {
+      ("exec", Some(command)) => prompt_variables(command)
+        .into_iter()
+        .filter(|key| produced_anywhere.contains(bare_context_key(key)))
+        .collect(),
+      _ => Vec::new(),
+    };
+    let prompt_keys = n
+      .prompt
+      .as_deref()
+      .map(prompt_variables)
+      .unwrap_or_default();
+    let args = tool_args(n).unwrap_or_default();
+    let arg_keys = if handler_type_of(n) == TOOL {
+      arg_variables(&args)
+    } else {
+      Vec::new()
+    };
+    let consumed = n.consumes();
+    if prompt_keys.is_empty()
+      && arg_keys.is_empty()
+      && command_keys.is_empty()
+      && consumed.is_empty()
+    {
+      continue;
+    }
+    let produced = produced_before(id, false);
+    let mut reported = HashSet::new();
+    for key in prompt_keys {
+      if !produced.contains(bare_context_key(key)) && reported.insert(key) {
+        let message = format!(
+          "Prompt of node '{}' reads ${}, which no node before it produces",
+          id, key
+        );
+        diagnostics.push(unproduced(key, message).with_node(id));
+      }
+    }
+    for key in arg_keys {
+      if !produced.contains(bare_context_key(key)) && reported.insert(key) {
+        let message = format!(
+          "Tool arguments of node '{}' read ${}, which no node before it produces",
+          id, key
+        );
+        diagnostics.push(unproduced(key, message).with_node(id));
+      }
+    }
+    for key in command_keys {
+      if !produced.contains(bare_context_key(key)) && reported.insert(key) {
+        let message = format!(
+          "Command of node '{}' reads ${}, which no node before it produces",
+          id, key
+        );
+        diagnostics.push(unproduced(key, message).with_node(id));
+      }
+    }
+    for key in &consumed {
+      if !produced.contains(bare_context_key(key)) && reported.insert(key) {
+        let message = format!(
+          "Node '{}' consumes context key '{}', which no node before it produces",
+          id, key
+        );
+        diagnostics.push(unproduced(key, message).with_node(id));
+      }
+    }
+  }
+  for e in &graph.edges {
+    let Some(condition) = &e.condition else {
+      continue;
+    };
+    let keys = condition.expr().context_keys();
+    if keys.is_empty() {
+      continue;
+    }
+    let produced = produced_before(&e.from_node, true);
+    for key in keys {
+      if !produced.contains(bare_context_key(key)) {
+        let message = format!(
+          "Condition on edge {} -> {} reads context key '{}', which no node up to '{}' produces",
+          e.from_node, e.to_node, key, e.from_node
+        );
+        diagnostics.push(unproduced(key, message).with_edge(&e.from_node, &e.to_node));
+      }
+    }
+  }
+  diagnostics
+}
+
+/// `key` without a leading `context.`, which conditions may use.
+fn bare_context_key(key: &str) -> &str {
+  key.strip_prefix("context.").unwrap_or(key)
+}
+
+/// `$name` references in a prompt, in order.
+fn prompt_variables(prompt: &str) -> Vec<&str> {
+  variable_references(prompt)
+    .into_iter()
+    .map(|(_, name)| name)
+    .collect()
+}
+
+/// `$name` references in `text`, in order, with the byte offset of each `$`; names are word
+/// characters, dotted segments allowed (`$graph.goal`). A `$` followed by a digit or anything
+/// else is not a reference.
+pub(crate) fn variable_references(text: &str) -> Vec<(usize, &str)> {
+  let is_word = |c: char| c.is_ascii_alphanumeric() || c == '_';
+  let starts_name = |s: &str| s.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_');
+  let mut vars = Vec::new();
+  let mut at = 0;
+  while let Some(i) = text[at..].find('$') {
+    let dollar = at + i;
+    let after = &text[dollar + 1..];
+    let mut end = 0;
+    if starts_name(after) {
+      loop {
+        end += after[end..]
+          .find(|c| !is_word(c))
+          .unwrap_or(after.len() - end);
+        if after[end..].starts_with('.') && starts_name(&after[end + 1..]) {
+          end += 1;
+        } else {
+          break;
+        }
+      }
+      vars.push((dollar, &after[..end]));
+    }
+    at = dollar + 1 + end;
+  }
+  vars
+}
+
+/// Node ids in sorted order, so diagnostics come out in a stable order.
+fn sorted_ids(graph: &AttractorGraph) -> Vec<&str> {
+  let mut ids: Vec<&str> = graph.nodes.keys().map(String::as_str).collect();
+  ids.sort();
+  ids
+}
+
+fn start_ids(graph: &AttractorGraph) -> Vec<&str> {
+  sorted_ids(graph)
+    .into_iter()
+    .filter(|id| graph.nodes[*id].is_start())
+    .collect()
+}
+
+fn exit_ids(graph: &AttractorGraph) -> Vec<&str> {
+  sorted_ids(graph)
+    .into_iter()
+    .filter(|id| graph.nodes[*id].is_exit())
+    .collect()
+}
+
+/// `none`, or the ids as `2 ('a', 'b')`.
+fn describe_ids(ids: &[&str]) -> String {
+  if ids.is_empty() {
+    return "none".to_string();
+  }
+  let quoted: Vec<String> = ids.iter().map(|id| format!("'{}'", id)).collect();
+  format!("{} ({})", ids.len(), quoted.join(", "))
+}
+
+/// Ids of the nodes reachable from `start` along edges, including `start`.
+fn reachable_from<'a>(graph: &'a AttractorGraph, start: &'a str) -> HashSet<&'a str> {
+  let mut seen = HashSet::from([start]);
+  let mut stack = vec![start];
+  while let Some(id) = stack.pop() {
+    for e in graph.outgoing_edges(id) {
+      if seen.insert(e.to_node.as_str()) {
+        stack.push(e.to_node.as_str());
+      }
+    }
+  }
+  seen
+}
+
+/// Cycles among the nodes that cannot reach an exit node, each as a closed path such as
+/// `["a", "b", "a"]` starting at its smallest id, and whether no edge leaves it. Empty when
+/// the graph has no exit node, which `terminal_node` reports.
+fn trapped_cycles(graph: &AttractorGraph) -> Vec<(Vec<&str>, bool)> {
+  let exits = exit_ids(graph);
+  if exits.is_empty() {
+    return vec![];
+  }
+  let mut incoming: HashMap<&str, Vec<&str>> = HashMap::new();
+  for e in &graph.edges {
+    incoming
+      .entry(e.to_node.as_str())
+      .or_default()
+      .push(e.from_node.as_str());
+  }
+  let mut reaches_exit: HashSet<&str> = exits.iter().copied().collect();
+  let mut stack = exits;
+  while let Some(id) = stack.pop() {
+    for &from in incoming.get(id).into_iter().flatten() {
+      if reaches_exit.insert(from) {
+        stack.push(from);
+      }
+    }
+  }
+  let trapped: HashSet<&str> = sorted_ids(graph)
+    .into_iter()
+    .filter(|id| !reaches_exit.contains(id))
+    .collect();
+  strongly_connected(graph, &trapped)
+    .into_iter()
+    .filter_map(|component| {
+      let cycle = cycle_through(graph, &component)?;
+      let closed = component.iter().all(|id| {
+        graph
+          .outgoing_edges(id)
+          .iter()
+          .all(|e| component.contains(e.to_node.as_str()) || !graph.nodes.contains_key(&e.to_node))
+      });
+      Some((cycle, closed))
+    })
+    .collect()
+}
+
+/// Strongly connected components of the subgraph on `ids` (Kosaraju, without recursion so
+/// large graphs cannot overflow the stack), ordered by their smallest id.
+fn strongly_connected<'a>(
+  graph: &'a AttractorGraph,
+  ids: &HashSet<&'a str>,
+) -> Vec<HashSet<&'a str>> {
+  let mut succ: HashMap<&str, Vec<&str>> = HashMap::new();
+  let mut pred: HashMap<&str, Vec<&str>> = HashMap::new();
+  for e in &graph.edges {
+    let (from, to) = (e.from_node.as_str(), e.to_node.as_str());
+    if ids.contains(from) && ids.contains(to) {
+      succ.entry(from).or_default().push(to);
+      pred.entry(to).or_default().push(from);
+    }
+  }
+  let mut roots: Vec<&str> = ids.iter().copied().collect();
+  roots.sort();
+
+  let mut finished = Vec::new();
+  let mut visited = HashSet::new();
+  for &root in &roots {
+    if !visited.insert(root) {
+      continue;
+    }
+    let mut stack = vec![(root, 0)];
+    while let Some(top) = stack.last_mut() {
+      let (id, i) = *top;
+      match succ.get(id).and_then(|s| s.get(i)) {
+        Some(&next) => {
+          top.1 += 1;
+          if visited.insert(next) {
+            stack.push((next, 0));
+          }
+        }
+        None => {
+          finished.push(id);
+          stack.pop();
+        }
+      }
+    }
   }
-  if graph.find_exit().is_none() {
-    return Err("Graph must have exactly one exit node (shape=Msquare)".to_string());
+
+  let mut assigned = HashSet::new();
+  let mut components = Vec::new();
+  for &root in finished.iter().rev() {
+    if !assigned.insert(root) {
+      continue;
+    }
+    let mut component = HashSet::from([root]);
+    let mut stack = vec![root];
+    while let Some(id) = stack.pop() {
+      for &from in pred.get(id).into_iter().flatten() {
+        if assigned.insert(from) {
+          component.insert(from);
+          stack.push(from);
+        }
+      }
+    }
+    components.push(component);
+  }
+  components.sort_by_key(|c| c.iter().min().copied());
+  components
+}
+
+/// Shortest closed path from the smallest id in `component` back to itself, following edges
+/// within the component, or `None` if it has no cycle (a single node without a self-loop).
+fn cycle_through<'a>(
+  graph: &'a AttractorGraph,
+  component: &HashSet<&'a str>,
+) -> Option<Vec<&'a str>> {
+  let first = *component.iter().min()?;
+  let mut parent: HashMap<&str, &str> = HashMap::new();
+  let mut queue = VecDeque::from([first]);
+  while let Some(id) = queue.pop_front() {
+    for e in graph.outgoing_edges(id) {
+      let to = e.to_node.as_str();
+      if to == first {
+        let mut path = vec![id];
+        let mut at = id;
+        while at != first {
+          at = parent[at];
+          path.push(at);
+        }
+        path.reverse();
+        path.push(first);
+        return Some(path);
+      }
+      if component.contains(to) && to != first && !parent.contains_key(to) {
+        parent.insert(to, id);
+        queue.push_back(to);
+      }
+    }
   }
-  Ok(())
+  None
 }
 
 #[async_trait]
@@ -96,8 +947,8 @@ impl Node for ValidateGraphNode {
               continue;
             }
           };
-          match validate(&graph) {
-            Ok(()) => {
+          match validate_or_raise(&graph) {
+            Ok(_) => {
               let _ = out_tx
                 .send(Arc::new(graph) as Arc<dyn Any + Send + Sync>)
                 .await;
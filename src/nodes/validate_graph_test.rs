@@ -3,12 +3,13 @@
 use std::collections::HashMap;
 use std::sync::Arc;
 
-use crate::types::{AttractorGraph, AttractorNode};
+use crate::dot_parser::parse_dot;
+use crate::types::{AttractorEdge, AttractorGraph, AttractorNode, Diagnostic, Severity};
 use futures::StreamExt;
 use streamweave::node::Node;
 use tokio_stream::wrappers::ReceiverStream;
 
-use super::validate_graph::{ValidateGraphNode, validate};
+use super::validate_graph::{ValidateGraphNode, validate, validate_or_raise};
 
 fn node(id: &str, shape: &str) -> AttractorNode {
   AttractorNode {
@@ -19,7 +20,12 @@ fn node(id: &str, shape: &str) -> AttractorNode {
     prompt: None,
     command: None,
     goal_gate: false,
-    max_retries: 0,
+    max_retries: None,
+    retry_policy: None,
+    retry_target: None,
+    fallback_retry_target: None,
+    classes: Vec::new(),
+    attrs: Default::default(),
   }
 }
 
@@ -31,6 +37,11 @@ fn graph(nodes: Vec<AttractorNode>) -> AttractorGraph {
     nodes: nodes_map,
     edges: vec![],
     default_max_retry: 50,
+    default_retry_policy: Default::default(),
+    retry_target: None,
+    fallback_retry_target: None,
+    name: None,
+    attrs: Default::default(),
   }
 }
 
@@ -45,16 +56,187 @@ fn node_trait_methods() {
   assert!(node.has_output_port("error"));
 }
 
+fn edge(from: &str, to: &str) -> AttractorEdge {
+  AttractorEdge {
+    from_node: from.to_string(),
+    to_node: to.to_string(),
+    label: None,
+    condition: None,
+    weight: 0,
+    attrs: Default::default(),
+  }
+}
+
+/// `rule` of each diagnostic, in order.
+fn rules(diagnostics: &[Diagnostic]) -> Vec<&str> {
+  diagnostics.iter().map(|d| d.rule.as_str()).collect()
+}
+
 #[test]
 fn validate_ok_with_start_and_exit() {
-  let g = graph(vec![node("start", "Mdiamond"), node("exit", "Msquare")]);
-  assert!(validate(&g).is_ok());
+  let mut g = graph(vec![node("start", "Mdiamond"), node("exit", "Msquare")]);
+  g.edges.push(edge("start", "exit"));
+  assert_eq!(validate(&g), vec![]);
+  assert_eq!(validate_or_raise(&g), Ok(vec![]));
+}
+
+/// Diagnostics from the cycle and dead-end rules only.
+fn termination_findings(dot: &str) -> Vec<(String, String)> {
+  validate(&parse_dot(dot).unwrap())
+    .into_iter()
+    .filter(|d| d.rule.starts_with("cycle_") || d.rule == "no_dead_ends")
+    .map(|d| (d.rule, d.message))
+    .collect()
+}
+
+#[test]
+fn validate_reports_fix_loop_with_no_way_out() {
+  let findings = termination_findings(
+    r#"digraph {
+      start [shape=Mdiamond] exit [shape=Msquare]
+      test [shape=parallelogram, command="cargo test"]
+      fix [shape=parallelogram, command="cargo fix"]
+      start -> exit [condition="outcome=fail"]
+      start -> test -> fix -> test
+    }"#,
+  );
+  assert_eq!(
+    findings,
+    [(
+      "cycle_has_escape".to_string(),
+      "Cycle fix -> test -> fix has no edge leaving it, so a run that enters it never ends"
+        .to_string()
+    )]
+  );
+}
+
+#[test]
+fn validate_reports_cycles_with_no_path_to_exit_and_dead_ends() {
+  let findings = termination_findings(
+    r#"digraph {
+      start [shape=Mdiamond] exit [shape=Msquare]
+      start -> exit
+      start -> a -> b -> a
+      b -> stuck
+      start -> spin -> spin
+    }"#,
+  );
+  let rules: Vec<&str> = findings.iter().map(|(r, _)| r.as_str()).collect();
+  assert_eq!(
+    rules,
+    ["cycle_has_escape", "cycle_reaches_exit", "no_dead_ends"]
+  );
+  assert!(findings[0].1.contains("spin -> spin"), "{:?}", findings);
+  assert!(findings[1].1.contains("a -> b -> a"), "{:?}", findings);
+  assert!(findings[2].1.contains("'stuck'"), "{:?}", findings);
+
+  let d = validate(&parse_dot("digraph { start [shape=Mdiamond] exit [shape=Msquare] start -> exit start -> a -> b -> a b -> stuck }").unwrap());
+  let cycle = d.iter().find(|d| d.rule == "cycle_reaches_exit").unwrap();
+  assert!(cycle.is_error());
+  assert_eq!(cycle.node_id.as_deref(), Some("a"));
+  let dead_end = d.iter().find(|d| d.rule == "no_dead_ends").unwrap();
+  assert_eq!(dead_end.severity, Severity::Warning);
+}
+
+#[test]
+fn validate_accepts_fix_loop_that_can_reach_exit() {
+  let findings = termination_findings(
+    r#"digraph {
+      start [shape=Mdiamond] exit [shape=Msquare]
+      test [shape=parallelogram, command="cargo test"]
+      fix [shape=parallelogram, command="cargo fix"]
+      start -> test
+      test -> exit [condition="outcome=success"]
+      test -> fix -> test
+    }"#,
+  );
+  assert!(findings.is_empty(), "{:?}", findings);
+}
+
+/// Messages from `context_key_produced`.
+fn dataflow_findings(dot: &str) -> Vec<String> {
+  validate(&parse_dot(dot).unwrap())
+    .into_iter()
+    .filter(|d| d.rule == "context_key_produced")
+    .map(|d| d.message)
+    .collect()
+}
+
+#[test]
+fn validate_warns_on_context_keys_nothing_upstream_produces() {
+  let findings = dataflow_findings(
+    r#"digraph {
+      start [shape=Mdiamond] exit [shape=Msquare]
+      pick [prompt="Pick a task for $goal and set $ready_task_id."]
+      close [prompt="Close $ready_task_id, cost $5.", consumes="branch"]
+      tag [shape=parallelogram, tool_command="gh issue edit", tool_args="{\"id\": \"$issue\"}"]
+      start -> pick -> close -> tag
+      close -> exit [condition="context.closed=true && outcome=success"]
+    }"#,
+  );
+  assert_eq!(
+    findings,
+    [
+      "Prompt of node 'close' reads $ready_task_id, which no node before it produces",
+      "Node 'close' consumes context key 'branch', which no node before it produces",
+      "Prompt of node 'pick' reads $ready_task_id, which no node before it produces",
+      "Tool arguments of node 'tag' read $issue, which no node before it produces",
+      "Condition on edge close -> exit reads context key 'context.closed', which no node up to 'close' produces",
+    ]
+  );
+}
+
+#[test]
+fn validate_accepts_context_keys_produced_upstream() {
+  let findings = dataflow_findings(
+    r#"digraph {
+      start [shape=Mdiamond] exit [shape=Msquare]
+      pick [prompt="Pick a task", produces="ready_task_id, branch"]
+      close [prompt="Close $ready_task_id on $graph.goal.", consumes="branch", produces="closed"]
+      start -> pick -> close
+      close -> exit [condition="context.closed=true"]
+      close -> pick [condition="ready_task_id"]
+    }"#,
+  );
+  assert!(findings.is_empty(), "{:?}", findings);
+}
+
+#[test]
+fn validate_checks_command_keys_that_some_node_produces() {
+  // $branch is a context key (merge produces it) read before it is set; $HOME and $1 are shell.
+  let findings = dataflow_findings(
+    r#"digraph {
+      start [shape=Mdiamond] exit [shape=Msquare]
+      push [type=exec, command="git push origin $branch && ls $HOME | head -n $1"]
+      merge [type=exec, command="./merge.sh", produces="branch"]
+      report [type=exec, command="echo merged $branch"]
+      start -> push -> merge -> report -> exit
+    }"#,
+  );
+  assert_eq!(
+    findings,
+    ["Command of node 'push' reads $branch, which no node before it produces"]
+  );
+}
+
+#[test]
+fn validate_counts_keys_produced_earlier_in_a_loop() {
+  let findings = dataflow_findings(
+    r#"digraph {
+      start [shape=Mdiamond] exit [shape=Msquare]
+      work [prompt="Continue from $cursor", produces="cursor"]
+      start -> work
+      work -> work [condition="cursor"]
+      work -> exit [condition="outcome=success"]
+    }"#,
+  );
+  assert!(findings.is_empty(), "{:?}", findings);
 }
 
 #[test]
 fn validate_err_no_start() {
   let g = graph(vec![node("exit", "Msquare")]);
-  let r = validate(&g);
+  let r = validate_or_raise(&g);
   assert!(r.is_err());
   assert!(r.unwrap_err().contains("start"));
 }
@@ -62,11 +244,244 @@ fn validate_err_no_start() {
 #[test]
 fn validate_err_no_exit() {
   let g = graph(vec![node("start", "Mdiamond")]);
-  let r = validate(&g);
+  let r = validate_or_raise(&g);
   assert!(r.is_err());
   assert!(r.unwrap_err().contains("exit"));
 }
 
+#[test]
+fn validate_err_unknown_retry_target() {
+  let mut gate = node("gate", "box");
+  gate.prompt = Some("check".to_string());
+  gate.retry_target = Some("nowhere".to_string());
+  let mut g = graph(vec![
+    node("start", "Mdiamond"),
+    node("exit", "Msquare"),
+    gate,
+  ]);
+  g.edges = vec![edge("start", "gate"), edge("gate", "exit")];
+  let err = validate_or_raise(&g).unwrap_err();
+  assert!(err.contains("retry_target on node 'gate'"), "{}", err);
+  assert!(err.contains("nowhere"), "{}", err);
+
+  g.nodes.get_mut("gate").unwrap().retry_target = Some("start".to_string());
+  assert!(validate_or_raise(&g).is_ok());
+  g.fallback_retry_target = Some("missing".to_string());
+  let d = validate(&g);
+  assert_eq!(rules(&d), ["retry_target_exists"]);
+  assert!(d[0].message.contains("graph fallback_retry_target"));
+  assert_eq!(d[0].node_id, None);
+}
+
+#[test]
+fn validate_reports_every_problem_at_once() {
+  let mut g = graph(vec![
+    node("start", "Mdiamond"),
+    node("begin", "Mdiamond"),
+    node("a", "box"),
+  ]);
+  g.edges = vec![edge("a", "ghost")];
+  let d = validate(&g);
+  assert_eq!(
+    rules(&d),
+    [
+      "start_node",
+      "terminal_node",
+      "edge_target_exists",
+      "no_dead_ends",
+      "no_dead_ends",
+      "prompt_on_llm_nodes"
+    ]
+  );
+  assert!(
+    d[0].message.contains("found 2 ('begin', 'start')"),
+    "{}",
+    d[0].message
+  );
+  assert!(d[1].message.contains("found none"), "{}", d[1].message);
+  assert_eq!(d[2].edge, Some(("a".to_string(), "ghost".to_string())));
+  assert_eq!(d[5].severity, Severity::Warning);
+  assert_eq!(d[5].node_id.as_deref(), Some("a"));
+  assert!(d[5].fix.is_some());
+
+  let err = validate_or_raise(&g).unwrap_err();
+  assert_eq!(err.lines().count(), 3, "{}", err);
+}
+
+#[test]
+fn validate_checks_start_exit_edges_and_reachability() {
+  let mut g = graph(vec![
+    node("start", "Mdiamond"),
+    node("exit", "Msquare"),
+    node("orphan", "parallelogram"),
+  ]);
+  g.edges = vec![edge("start", "exit"), edge("exit", "start")];
+  let d = validate(&g);
+  assert_eq!(
+    rules(&d),
+    [
+      "start_no_incoming",
+      "exit_no_outgoing",
+      "reachability",
+      "no_dead_ends"
+    ]
+  );
+  assert_eq!(d[3].node_id.as_deref(), Some("orphan"));
+  assert_eq!(d[0].edge, Some(("exit".to_string(), "start".to_string())));
+  assert_eq!(d[2].node_id.as_deref(), Some("orphan"));
+  assert_eq!(
+    d[2].to_string(),
+    "error[reachability]: Node 'orphan' is not reachable from start node 'start'"
+  );
+}
+
+#[test]
+fn validate_checks_conditions_types_and_goal_gates() {
+  let mut typo = node("typo", "box");
+  typo.attrs.insert("type", "codegen");
+  typo.handler_type = Some("codegen".to_string());
+  let mut gate = node("gate", "parallelogram");
+  gate.handler_type = Some("exec".to_string());
+  gate.command = Some("make check".to_string());
+  gate.goal_gate = true;
+  let mut g = graph(vec![
+    node("start", "Mdiamond"),
+    node("exit", "Msquare"),
+    typo,
+    gate,
+  ]);
+  let mut bad = edge("gate", "exit");
+  bad.attrs.insert("condition", "outcome=");
+  g.edges = vec![edge("start", "typo"), edge("typo", "gate"), bad];
+  let d = validate(&g);
+  assert_eq!(
+    rules(&d),
+    ["condition_syntax", "type_known", "goal_gate_has_retry"]
+  );
+  assert!(d[0].message.contains("gate -> exit"), "{}", d[0].message);
+  assert!(d[1].fix.as_deref().unwrap().contains("codergen"));
+
+  g.retry_target = Some("typo".to_string());
+  assert!(!rules(&validate(&g)).contains(&"goal_gate_has_retry"));
+}
+
+#[test]
+fn validate_err_exec_without_command() {
+  let mut run = node("run", "box");
+  run.handler_type = Some("exec".to_string());
+  let mut g = graph(vec![
+    node("start", "Mdiamond"),
+    node("exit", "Msquare"),
+    run,
+  ]);
+  g.edges = vec![edge("start", "run"), edge("run", "exit")];
+  let d = validate(&g);
+  assert_eq!(rules(&d), ["exec_has_command"]);
+  assert_eq!(d[0].message, "exec node 'run' requires a command attribute");
+
+  g.nodes.get_mut("run").unwrap().command = Some("true".to_string());
+  assert_eq!(validate(&g), vec![]);
+}
+
+#[test]
+fn validate_err_tool_without_command_or_with_bad_args() {
+  let dot = r#"digraph G {
+    start [shape=Mdiamond]
+    exit [shape=Msquare]
+    lookup [shape=parallelogram, tool_args="[1]"]
+    start -> lookup -> exit
+  }"#;
+  let d = validate(&parse_dot(dot).unwrap());
+  assert_eq!(rules(&d), ["tool_has_command", "tool_has_command"]);
+  assert_eq!(
+    d[0].message,
+    "tool node 'lookup' requires a tool_command attribute"
+  );
+  assert_eq!(
+    d[1].message,
+    "tool_args of node 'lookup' must be a JSON object"
+  );
+
+  let fixed = dot.replace(
+    r#"tool_args="[1]""#,
+    r#"tool_command="gh issue view", tool_args="{\"goal\": \"$goal\"}""#,
+  );
+  assert_eq!(validate(&parse_dot(&fixed).unwrap()), vec![]);
+}
+
+#[test]
+fn validate_err_parallel_without_fan_in_or_with_bad_join_policy() {
+  let dot = r#"digraph G {
+    start [shape=Mdiamond]
+    exit [shape=Msquare]
+    fork [shape=component, join_policy="all"]
+    a [type=exec, command="true"]
+    b [type=exec, command="true"]
+    start -> fork
+    fork -> a
+    fork -> b
+    a -> exit
+    b -> exit
+  }"#;
+  let d = validate(&parse_dot(dot).unwrap());
+  assert_eq!(rules(&d), ["parallel_join", "parallel_join"]);
+  assert_eq!(
+    d[0].message,
+    "Parallel node 'fork' has no parallel.fan_in node where its branches meet"
+  );
+  assert!(d[1].message.contains("unknown join_policy 'all'"));
+
+  let joined = dot.replace(r#", join_policy="all""#, "").replace(
+    "a -> exit\n    b -> exit",
+    "join [shape=tripleoctagon]\n    a -> join\n    b -> join\n    join -> exit",
+  );
+  assert_eq!(validate(&parse_dot(&joined).unwrap()), vec![]);
+}
+
+#[test]
+fn validate_err_conditional_node_no_edge_can_match() {
+  let dot = r#"digraph G {
+    start [shape=Mdiamond]
+    exit [shape=Msquare]
+    test [type=exec, command="make test", produces="tests_passed"]
+    check [shape=diamond]
+    fix [type=exec, command="make fix"]
+    start -> test -> check
+    check -> exit [condition="outcome=success && outcome=fail"]
+    check -> fix [condition="outcome=fail"]
+    fix -> test
+  }"#;
+  let d = validate(&parse_dot(dot).unwrap());
+  assert_eq!(rules(&d), ["conditional_routes"]);
+  assert_eq!(
+    d[0].message,
+    "Conditional node 'check' has no unconditional edge and none of its conditions can match: its outcome is always success"
+  );
+
+  let on_context = dot.replace(
+    r#"condition="outcome=fail"]"#,
+    r#"condition="tests_passed=false"]"#,
+  );
+  assert_eq!(validate(&parse_dot(&on_context).unwrap()), vec![]);
+  let fallback = dot.replace(r#" [condition="outcome=fail"]"#, "");
+  assert_eq!(validate(&parse_dot(&fallback).unwrap()), vec![]);
+}
+
+#[test]
+fn example_workflows_have_no_errors() {
+  let dir = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("examples/workflows");
+  for entry in std::fs::read_dir(dir).unwrap() {
+    let path = entry.unwrap().path();
+    if path.extension().and_then(|e| e.to_str()) != Some("dot") {
+      continue;
+    }
+    let g = crate::dot_parser::parse_dot(&std::fs::read_to_string(&path).unwrap()).unwrap();
+    if let Err(e) = validate_or_raise(&g) {
+      panic!("{}: {}", path.display(), e);
+    }
+  }
+}
+
 #[tokio::test]
 async fn node_execute_sends_error_on_wrong_type() {
   let node = ValidateGraphNode::new("validate");
@@ -93,7 +508,8 @@ async fn node_execute_sends_error_on_wrong_type() {
 
 #[tokio::test]
 async fn node_execute_validates_and_forwards() {
-  let g = graph(vec![node("start", "Mdiamond"), node("exit", "Msquare")]);
+  let mut g = graph(vec![node("start", "Mdiamond"), node("exit", "Msquare")]);
+  g.edges.push(edge("start", "exit"));
   let node = ValidateGraphNode::new("validate");
   let (tx, rx) = tokio::sync::mpsc::channel(4);
   tx.send(Arc::new(g) as Arc<dyn std::any::Any + Send + Sync>)
@@ -0,0 +1,457 @@
+//! Parallel fan-out (`parallel`, shape `component`) and fan-in (`parallel.fan_in`, shape
+//! `tripleoctagon`) handlers.
+//!
+//! A parallel node runs every outgoing branch concurrently, each on its own copy of the
+//! context, until the branch reaches the fan-in node where the branches meet (the nearest
+//! `parallel.fan_in` node after the parallel node). How many branches must succeed is the
+//! node's `join_policy`:
+//!
+//! | Policy | Succeeds when | Stops early |
+//! |--------|---------------|-------------|
+//! | `wait_all` (default) | every branch succeeds; partial success when some do | never |
+//! | `first_success` | any branch succeeds | at the first success |
+//! | `k_of_n` | `join_k` branches succeed | once `join_k` succeed, or too many fail |
+//! | `fail_fast` | every branch succeeds | at the first failure |
+//!
+//! Branches still running when it stops early are cancelled: the command, tool or agent
+//! process of the node they are on is killed (processes it started in the background itself
+//! are not). `max_parallel` caps how many branches run at once. Each branch step is recorded
+//! to the run's execution log with its `branch`. Each branch has its own stage directory,
+//! `<stage_dir>/parallel/<branch>`, so agents on different branches write separate
+//! `outcome.json` files.
+//!
+//! Each finished branch's results go into the context under branch-qualified keys, named by
+//! the branch's first node: `parallel.<branch>.outcome` is its last outcome status, and
+//! `parallel.<branch>.<key>` every context key the branch changed. `parallel.branches` lists
+//! the finished branches and `parallel.status` the joined status. The run then continues at
+//! the fan-in node, which succeeds or fails with that status.
+
+use std::collections::{HashSet, VecDeque};
+use std::fmt;
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::sync::Arc;
+
+use crate::handler::{Handler, HandlerServices, handler_type_of};
+use crate::nodes::execution_loop::apply_context_updates;
+use crate::nodes::retry::run_with_retries;
+use crate::nodes::select_edge::{SelectEdgeInput, select_edge};
+use crate::types::{AttractorGraph, AttractorNode, NodeOutcome, OutcomeStatus, RunContext};
+use async_trait::async_trait;
+use futures::StreamExt;
+use tracing::instrument;
+
+/// Handler type of parallel fan-out nodes.
+pub const PARALLEL: &str = "parallel";
+/// Handler type of fan-in nodes.
+pub const FAN_IN: &str = "parallel.fan_in";
+
+/// Steps one branch may take before it is stopped, as a guard against branches that loop.
+const MAX_BRANCH_STEPS: usize = 1000;
+
+/// How a parallel node decides its outcome from its branches' outcomes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum JoinPolicy {
+  /// Run every branch; success when all succeed, partial success when some do.
+  WaitAll,
+  /// Success as soon as one branch succeeds.
+  FirstSuccess,
+  /// Success as soon as this many branches succeed.
+  KOfN(usize),
+  /// Failure as soon as one branch fails; success when all succeed.
+  FailFast,
+}
+
+impl FromStr for JoinPolicy {
+  type Err = String;
+
+  /// Parses a policy name. `k_of_n` parses with k = 1; [JoinPolicy::of] reads `join_k`.
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    match s.trim() {
+      "wait_all" => Ok(Self::WaitAll),
+      "first_success" => Ok(Self::FirstSuccess),
+      "k_of_n" => Ok(Self::KOfN(1)),
+      "fail_fast" => Ok(Self::FailFast),
+      other => Err(format!(
+        "unknown join_policy '{}' (expected wait_all, first_success, k_of_n or fail_fast)",
+        other
+      )),
+    }
+  }
+}
+
+impl fmt::Display for JoinPolicy {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    match self {
+      Self::WaitAll => f.write_str("wait_all"),
+      Self::FirstSuccess => f.write_str("first_success"),
+      Self::KOfN(k) => write!(f, "k_of_n (k={})", k),
+      Self::FailFast => f.write_str("fail_fast"),
+    }
+  }
+}
+
+impl JoinPolicy {
+  /// The policy of a parallel node: its `join_policy` (default `wait_all`), with `join_k` for
+  /// `k_of_n`.
+  pub fn of(node: &AttractorNode) -> Result<Self, String> {
+    let policy = match node.attrs.get("join_policy") {
+      Some(name) => name.parse()?,
+      None => Self::WaitAll,
+    };
+    match (policy, node.attrs.get_int("join_k")?) {
+      (Self::KOfN(_), Some(k)) if k >= 1 => Ok(Self::KOfN(k as usize)),
+      (Self::KOfN(_), _) => Err(format!(
+        "join_policy k_of_n on node '{}' needs join_k of at least 1",
+        node.id
+      )),
+      (policy, _) => Ok(policy),
+    }
+  }
+
+  /// True once the finished branches settle the outcome, so the rest can be cancelled.
+  fn settled(&self, succeeded: usize, failed: usize, total: usize) -> bool {
+    match *self {
+      Self::WaitAll => false,
+      Self::FirstSuccess => succeeded >= 1,
+      Self::KOfN(k) => succeeded >= k || total - failed < k,
+      Self::FailFast => failed >= 1,
+    }
+  }
+
+  /// The joined status once branches have finished (or the policy settled early).
+  fn status(&self, succeeded: usize, failed: usize, total: usize) -> OutcomeStatus {
+    let met = match *self {
+      Self::WaitAll => succeeded == total,
+      Self::FirstSuccess => succeeded >= 1,
+      Self::KOfN(k) => succeeded >= k,
+      Self::FailFast => failed == 0,
+    };
+    if met {
+      OutcomeStatus::Success
+    } else if *self == Self::WaitAll && succeeded > 0 {
+      OutcomeStatus::PartialSuccess
+    } else {
+      OutcomeStatus::Error
+    }
+  }
+}
+
+/// The fan-in node where the branches of parallel node `node_id` meet: the nearest
+/// `parallel.fan_in` node reachable from its outgoing edges.
+pub fn fan_in_of<'a>(graph: &'a AttractorGraph, node_id: &str) -> Option<&'a str> {
+  let mut seen: HashSet<&str> = HashSet::new();
+  let mut queue: VecDeque<&str> = graph
+    .outgoing_edges(node_id)
+    .into_iter()
+    .map(|e| e.to_node.as_str())
+    .collect();
+  while let Some(id) = queue.pop_front() {
+    if !seen.insert(id) {
+      continue;
+    }
+    let Some(node) = graph.nodes.get(id) else {
+      continue;
+    };
+    if handler_type_of(node) == FAN_IN {
+      return Some(&node.id);
+    }
+    queue.extend(graph.outgoing_edges(id).iter().map(|e| e.to_node.as_str()));
+  }
+  None
+}
+
+/// The node to run after `node` with `outcome`: a parallel node continues at its fan-in (its
+/// branches already ran inside it), any other node follows [select_edge].
+pub(crate) fn next_node_id(
+  graph: &Arc<AttractorGraph>,
+  node: &AttractorNode,
+  outcome: &NodeOutcome,
+  context: &RunContext,
+) -> Option<String> {
+  if handler_type_of(node) == PARALLEL {
+    return fan_in_of(graph, &node.id).map(String::from);
+  }
+  select_edge(&SelectEdgeInput {
+    node_id: node.id.clone(),
+    outcome: outcome.clone(),
+    context: context.clone(),
+    graph: Arc::clone(graph),
+  })
+  .next_node_id
+}
+
+/// What one branch ended with.
+struct Branch {
+  /// Edge order of the branch, for reporting.
+  index: usize,
+  /// First node of the branch.
+  id: String,
+  outcome: NodeOutcome,
+  context: RunContext,
+}
+
+impl Branch {
+  fn succeeded(&self) -> bool {
+    matches!(
+      self.outcome.status,
+      OutcomeStatus::Success | OutcomeStatus::PartialSuccess
+    )
+  }
+}
+
+/// Runs branch `index` from `head` on `context` until it reaches `fan_in`, exit, or a node
+/// with no next node. Each node runs through its registered handler, with its retries, in the
+/// branch's own stage directory `<stage_dir>/parallel/<head>`, and its steps are recorded to the
+/// run's execution log marked with the branch.
+#[instrument(level = "trace", skip(context, services))]
+async fn run_branch(
+  index: usize,
+  head: &str,
+  fan_in: &str,
+  mut context: RunContext,
+  services: &HandlerServices,
+) -> Branch {
+  let stage_dir = services
+    .stage_dir
+    .clone()
+    .unwrap_or_else(|| PathBuf::from(crate::DEFAULT_STAGE_DIR))
+    .join("parallel")
+    .join(head);
+  let services = &HandlerServices {
+    stage_dir: Some(stage_dir),
+    step_recorder: services.step_recorder.as_ref().map(|r| r.in_branch(head)),
+    ..services.clone()
+  };
+  let recorder = services.step_recorder.as_ref();
+  let graph = &services.graph;
+  let mut outcome = NodeOutcome::success("empty branch");
+  let mut completed: Vec<String> = Vec::new();
+  let mut current = head.to_string();
+  while current != fan_in {
+    if completed.len() >= MAX_BRANCH_STEPS {
+      outcome = NodeOutcome::error(format!(
+        "branch '{}' ran {} steps without reaching '{}'",
+        head, MAX_BRANCH_STEPS, fan_in
+      ));
+      break;
+    }
+    let Some(node) = graph.nodes.get(&current) else {
+      outcome = NodeOutcome::error(format!("Node not found: {}", current));
+      break;
+    };
+    if node.is_exit() {
+      break;
+    }
+    let handler = match services.handlers.resolve(node) {
+      Ok(handler) => handler,
+      Err(e) => {
+        outcome = NodeOutcome::error(e);
+        break;
+      }
+    };
+    tracing::info!(branch = %head, node = %node.id, "running");
+    let attempt;
+    (outcome, attempt) = run_with_retries(
+      &node.id,
+      graph.max_retries_for(&node.id),
+      &graph.retry_policy_for(&node.id),
+      |attempt, outcome| {
+        if let Some(r) = recorder {
+          r.record_retry(&node.id, attempt, &context, outcome, &completed);
+        }
+      },
+      || handler.execute(node, &context, services),
+    )
+    .await;
+    let context_before = context.clone();
+    apply_context_updates(&mut context, &outcome);
+    completed.push(node.id.clone());
+    if let Some(r) = recorder {
+      r.record(
+        &node.id,
+        attempt,
+        context_before,
+        &outcome,
+        &context,
+        &completed,
+      );
+    }
+    match next_node_id(graph, node, &outcome, &context) {
+      Some(next) => current = next,
+      None => break,
+    }
+  }
+  Branch {
+    index,
+    id: head.to_string(),
+    outcome,
+    context,
+  }
+}
+
+/// Fan-out: runs every outgoing branch concurrently and joins them per the node's
+/// [JoinPolicy] (see the [module docs](self)).
+pub struct ParallelHandler;
+
+#[async_trait]
+impl Handler for ParallelHandler {
+  #[instrument(level = "trace", skip_all, fields(node = %node.id))]
+  async fn execute(
+    &self,
+    node: &AttractorNode,
+    context: &RunContext,
+    services: &HandlerServices,
+  ) -> NodeOutcome {
+    let graph = &services.graph;
+    let Some(fan_in) = fan_in_of(graph, &node.id) else {
+      return NodeOutcome::error(format!(
+        "parallel node '{}' has no parallel.fan_in node after it",
+        node.id
+      ));
+    };
+    let policy = match JoinPolicy::of(node) {
+      Ok(policy) => policy,
+      Err(e) => return NodeOutcome::error(e),
+    };
+    let heads: Vec<&str> = graph
+      .outgoing_edges(&node.id)
+      .into_iter()
+      .map(|e| e.to_node.as_str())
+      .collect();
+    let total = heads.len();
+    let max_parallel = match node.attrs.get_int("max_parallel") {
+      Ok(Some(n)) if n >= 1 => n as usize,
+      Ok(_) => total.max(1),
+      Err(e) => return NodeOutcome::error(e),
+    };
+
+    let branches: Vec<_> = heads
+      .iter()
+      .enumerate()
+      .map(|(i, head)| run_branch(i, head, fan_in, context.clone(), services))
+      .collect();
+    let mut running = futures::stream::iter(branches).buffer_unordered(max_parallel);
+    let mut finished: Vec<Branch> = Vec::new();
+    let (mut succeeded, mut failed) = (0, 0);
+    while let Some(branch) = running.next().await {
+      tracing::info!(node = %node.id, branch = %branch.id, status = %branch.outcome.status, "branch finished");
+      if branch.succeeded() {
+        succeeded += 1;
+      } else {
+        failed += 1;
+      }
+      finished.push(branch);
+      if policy.settled(succeeded, failed, total) {
+        break;
+      }
+    }
+    drop(running);
+    finished.sort_by_key(|b| b.index);
+
+    let status = policy.status(succeeded, failed, total);
+    let mut outcome = match status {
+      OutcomeStatus::Error => {
+        let failures: Vec<String> = finished
+          .iter()
+          .filter(|b| !b.succeeded())
+          .map(|b| {
+            format!(
+              "{} ({})",
+              b.id,
+              b.outcome.failure_reason.as_deref().unwrap_or("failed")
+            )
+          })
+          .collect();
+        NodeOutcome::error(format!(
+          "join policy {} not met: {} of {} branches succeeded; failed: {}",
+          policy,
+          succeeded,
+          total,
+          failures.join(", ")
+        ))
+      }
+      _ => NodeOutcome::success(format!(
+        "{} of {} branches succeeded ({})",
+        succeeded, total, policy
+      )),
+    };
+    outcome.status = status;
+    for branch in &finished {
+      let prefix = format!("parallel.{}", branch.id);
+      outcome.context_updates.insert(
+        format!("{}.outcome", prefix),
+        branch.outcome.status.to_string(),
+      );
+      for (key, value) in &branch.context {
+        if key != "outcome" && key != "preferred_label" && context.get(key) != Some(value) {
+          outcome
+            .context_updates
+            .insert(format!("{}.{}", prefix, key), value.clone());
+        }
+      }
+    }
+    let ids: Vec<&str> = finished.iter().map(|b| b.id.as_str()).collect();
+    outcome
+      .context_updates
+      .insert("parallel.branches".to_string(), ids.join(","));
+    outcome
+      .context_updates
+      .insert("parallel.status".to_string(), status.to_string());
+    outcome.suggested_next_ids = vec![fan_in.to_string()];
+    outcome
+  }
+}
+
+/// Fan-in: where a parallel node's branches meet. Succeeds or fails with the status the
+/// parallel node joined its branches to, naming the branches that failed.
+pub struct FanInHandler;
+
+#[async_trait]
+impl Handler for FanInHandler {
+  #[instrument(level = "trace", skip_all, fields(node = %node.id))]
+  async fn execute(
+    &self,
+    node: &AttractorNode,
+    context: &RunContext,
+    _: &HandlerServices,
+  ) -> NodeOutcome {
+    let (Some(branches), Some(status)) = (
+      context.get("parallel.branches"),
+      context.get("parallel.status"),
+    ) else {
+      return NodeOutcome::error(format!(
+        "fan-in node '{}' was reached without a parallel node before it",
+        node.id
+      ));
+    };
+    let branches: Vec<&str> = branches.split(',').filter(|b| !b.is_empty()).collect();
+    match status.as_str() {
+      "success" | "partial_success" => {
+        let mut outcome = NodeOutcome::success(format!("joined {}", branches.join(", ")));
+        if status == "partial_success" {
+          outcome.status = OutcomeStatus::PartialSuccess;
+        }
+        outcome
+      }
+      _ => {
+        let failed: Vec<&str> = branches
+          .into_iter()
+          .filter(|b| {
+            context
+              .get(&format!("parallel.{}.outcome", b))
+              .is_some_and(|s| s != "success" && s != "partial_success")
+          })
+          .collect();
+        NodeOutcome::error(format!(
+          "parallel branches failed: {}",
+          if failed.is_empty() {
+            "none finished".to_string()
+          } else {
+            failed.join(", ")
+          }
+        ))
+      }
+    }
+  }
+}
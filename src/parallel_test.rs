@@ -0,0 +1,303 @@
+//! Tests for `parallel`.
+
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use crate::dot_parser::parse_dot;
+use crate::handler::{Handler, HandlerRegistry, HandlerServices};
+use crate::parallel::{FanInHandler, JoinPolicy, ParallelHandler, fan_in_of};
+use crate::types::{AttractorGraph, AttractorNode, NodeOutcome, OutcomeStatus, RunContext};
+use async_trait::async_trait;
+
+/// Test step: waits `delay`, then sets `<id>=done` (and bumps `count`) and succeeds, or fails
+/// when `fail=true`.
+struct Step;
+
+#[async_trait]
+impl Handler for Step {
+  async fn execute(
+    &self,
+    node: &AttractorNode,
+    context: &RunContext,
+    _services: &HandlerServices,
+  ) -> NodeOutcome {
+    if let Some(delay) = node.attrs.get_duration("delay").unwrap() {
+      tokio::time::sleep(delay).await;
+    }
+    if node.attrs.get_bool("fail").unwrap() == Some(true) {
+      return NodeOutcome::error(format!("{} failed", node.id));
+    }
+    let count: u32 = context.get("count").map_or(0, |c| c.parse().unwrap());
+    let mut outcome = NodeOutcome::success(node.id.clone());
+    outcome
+      .context_updates
+      .insert(node.id.clone(), "done".to_string());
+    outcome
+      .context_updates
+      .insert("count".to_string(), (count + 1).to_string());
+    outcome
+  }
+}
+
+/// A parallel node `fork` with `fork_attrs`, branches `a` -> `a2`, `b` and `c` with the given
+/// attributes, joined at `join`.
+fn fork(fork_attrs: &str, a: &str, b: &str, c: &str) -> AttractorGraph {
+  parse_dot(&format!(
+    r#"digraph G {{
+      start [shape=Mdiamond]
+      exit [shape=Msquare]
+      fork [shape=component{}]
+      a [type=step{}]
+      a2 [type=step]
+      b [type=step{}]
+      c [type=step{}]
+      join [shape=tripleoctagon]
+      start -> fork
+      fork -> a
+      fork -> b
+      fork -> c
+      a -> a2 -> join
+      b -> join
+      c -> join
+      join -> exit
+    }}"#,
+    fork_attrs, a, b, c
+  ))
+  .unwrap()
+}
+
+async fn run_fork(graph: AttractorGraph) -> NodeOutcome {
+  let mut handlers = HandlerRegistry::default();
+  handlers.register("step", Step);
+  let node = graph.nodes["fork"].clone();
+  let services = HandlerServices {
+    graph: Arc::new(graph),
+    handlers,
+    ..Default::default()
+  };
+  let context: RunContext = [("count".to_string(), "5".to_string())].into();
+  ParallelHandler.execute(&node, &context, &services).await
+}
+
+#[test]
+fn join_policy_of_node() {
+  let graph = fork(r#", join_policy="k_of_n", join_k=2"#, "", "", "");
+  assert_eq!(
+    JoinPolicy::of(&graph.nodes["fork"]),
+    Ok(JoinPolicy::KOfN(2))
+  );
+  assert_eq!(JoinPolicy::of(&graph.nodes["a"]), Ok(JoinPolicy::WaitAll));
+  let graph = fork(r#", join_policy="k_of_n""#, "", "", "");
+  assert!(
+    JoinPolicy::of(&graph.nodes["fork"])
+      .unwrap_err()
+      .contains("join_k")
+  );
+  assert!("any".parse::<JoinPolicy>().is_err());
+  assert_eq!("fail_fast".parse(), Ok(JoinPolicy::FailFast));
+}
+
+#[test]
+fn fan_in_is_nearest_join_after_the_node() {
+  let graph = fork("", "", "", "");
+  assert_eq!(fan_in_of(&graph, "fork"), Some("join"));
+  assert_eq!(fan_in_of(&graph, "a"), Some("join"));
+  assert_eq!(fan_in_of(&graph, "join"), None);
+}
+
+#[tokio::test]
+async fn wait_all_merges_isolated_branch_contexts() {
+  let out = run_fork(fork("", "", "", "")).await;
+  assert_eq!(out.status, OutcomeStatus::Success);
+  assert_eq!(out.suggested_next_ids, ["join"]);
+  let updates = &out.context_updates;
+  assert_eq!(updates["parallel.branches"], "a,b,c");
+  assert_eq!(updates["parallel.status"], "success");
+  assert_eq!(updates["parallel.a.outcome"], "success");
+  assert_eq!(updates["parallel.a.a2"], "done");
+  // Each branch counted from the parallel node's context, not from another branch's.
+  assert_eq!(updates["parallel.a.count"], "7");
+  assert_eq!(updates["parallel.b.count"], "6");
+  assert!(!updates.contains_key("parallel.b.a"));
+  assert!(!updates.contains_key("count"));
+}
+
+#[tokio::test]
+async fn wait_all_with_a_failed_branch_is_partial_success() {
+  let out = run_fork(fork("", "", ", fail=true", "")).await;
+  assert_eq!(out.status, OutcomeStatus::PartialSuccess);
+  assert_eq!(out.context_updates["parallel.b.outcome"], "error");
+  assert_eq!(out.context_updates["parallel.status"], "partial_success");
+}
+
+#[tokio::test]
+async fn first_success_cancels_the_other_branches() {
+  let started = Instant::now();
+  let out = run_fork(fork(
+    r#", join_policy="first_success""#,
+    r#", delay="5s""#,
+    "",
+    r#", delay="5s""#,
+  ))
+  .await;
+  assert!(started.elapsed() < Duration::from_secs(2));
+  assert_eq!(out.status, OutcomeStatus::Success);
+  assert_eq!(out.context_updates["parallel.branches"], "b");
+}
+
+#[tokio::test]
+async fn cancelled_branches_kill_their_commands() {
+  let dir = tempfile::tempdir().unwrap();
+  let marker = dir.path().join("late");
+  let graph = parse_dot(&format!(
+    r#"digraph G {{
+      start [shape=Mdiamond]
+      exit [shape=Msquare]
+      fork [shape=component, join_policy="first_success"]
+      slow [type=exec, command="sleep 1 && touch {}"]
+      fast [type=exec, command="true"]
+      join [shape=tripleoctagon]
+      start -> fork
+      fork -> slow
+      fork -> fast
+      slow -> join
+      fast -> join
+      join -> exit
+    }}"#,
+    marker.display()
+  ))
+  .unwrap();
+  let node = graph.nodes["fork"].clone();
+  let services = HandlerServices {
+    graph: Arc::new(graph),
+    ..Default::default()
+  };
+  let out = ParallelHandler
+    .execute(&node, &RunContext::new(), &services)
+    .await;
+  assert_eq!(out.context_updates["parallel.branches"], "fast");
+  tokio::time::sleep(Duration::from_millis(1500)).await;
+  assert!(!marker.exists(), "cancelled branch kept running");
+}
+
+#[tokio::test]
+async fn agent_branches_write_their_own_outcome_files() {
+  let dir = tempfile::tempdir().unwrap();
+  let agent = dir.path().join("agent.sh");
+  std::fs::write(
+    &agent,
+    r#"read prompt
+printf '{"outcome":"success","context_updates":{"said":"%s"}}' "$prompt" > "$ATTRACTOR_STAGE_DIR/outcome.json"
+sleep 0.3
+"#,
+  )
+  .unwrap();
+  let graph = parse_dot(
+    r#"digraph G {
+      start [shape=Mdiamond]
+      exit [shape=Msquare]
+      fork [shape=component]
+      a [prompt="alpha"]
+      b [prompt="beta"]
+      join [shape=tripleoctagon]
+      start -> fork
+      fork -> a
+      fork -> b
+      a -> join
+      b -> join
+      join -> exit
+    }"#,
+  )
+  .unwrap();
+  let node = graph.nodes["fork"].clone();
+  let services = HandlerServices {
+    agent_cmd: Some(format!("sh {}", agent.display())),
+    stage_dir: Some(dir.path().join("stage")),
+    graph: Arc::new(graph),
+    ..Default::default()
+  };
+  let out = ParallelHandler
+    .execute(&node, &RunContext::new(), &services)
+    .await;
+  assert_eq!(out.status, OutcomeStatus::Success, "{:?}", out);
+  assert_eq!(out.context_updates["parallel.a.said"], "alpha");
+  assert_eq!(out.context_updates["parallel.b.said"], "beta");
+  assert!(dir.path().join("stage/parallel/a/outcome.json").exists());
+}
+
+#[tokio::test]
+async fn k_of_n_fails_once_k_successes_are_impossible() {
+  let out = run_fork(fork(
+    r#", join_policy="k_of_n", join_k=2"#,
+    r#", delay="5s""#,
+    ", fail=true",
+    ", fail=true",
+  ))
+  .await;
+  assert_eq!(out.status, OutcomeStatus::Error);
+  assert_eq!(
+    out.failure_reason.as_deref(),
+    Some(
+      "join policy k_of_n (k=2) not met: 0 of 3 branches succeeded; failed: b (b failed), c (c failed)"
+    )
+  );
+
+  let out = run_fork(fork(
+    r#", join_policy="k_of_n", join_k=2"#,
+    "",
+    ", fail=true",
+    "",
+  ))
+  .await;
+  assert_eq!(out.status, OutcomeStatus::Success);
+}
+
+#[tokio::test]
+async fn fail_fast_stops_at_first_failure() {
+  let started = Instant::now();
+  let out = run_fork(fork(
+    r#", join_policy="fail_fast", max_parallel=3"#,
+    r#", delay="5s""#,
+    ", fail=true",
+    r#", delay="5s""#,
+  ))
+  .await;
+  assert!(started.elapsed() < Duration::from_secs(2));
+  assert_eq!(out.status, OutcomeStatus::Error);
+  assert_eq!(out.context_updates["parallel.branches"], "b");
+}
+
+#[tokio::test]
+async fn fan_in_reports_the_joined_status() {
+  let services = HandlerServices::default();
+  let join = fork("", "", "", "").nodes["join"].clone();
+  let out = FanInHandler
+    .execute(&join, &RunContext::new(), &services)
+    .await;
+  assert!(
+    out
+      .failure_reason
+      .unwrap()
+      .contains("without a parallel node")
+  );
+
+  let mut context: RunContext = [
+    ("parallel.branches", "a,b"),
+    ("parallel.status", "error"),
+    ("parallel.a.outcome", "success"),
+    ("parallel.b.outcome", "error"),
+  ]
+  .into_iter()
+  .map(|(k, v)| (k.to_string(), v.to_string()))
+  .collect();
+  let out = FanInHandler.execute(&join, &context, &services).await;
+  assert_eq!(
+    out.failure_reason.as_deref(),
+    Some("parallel branches failed: b")
+  );
+
+  context.insert("parallel.status".to_string(), "partial_success".to_string());
+  let out = FanInHandler.execute(&join, &context, &services).await;
+  assert_eq!(out.status, OutcomeStatus::PartialSuccess);
+  assert_eq!(out.notes.as_deref(), Some("joined a, b"));
+}
@@ -0,0 +1,80 @@
+//! Loads and writes pipelines as DOT, JSON or YAML, chosen by file extension.
+//!
+//! JSON and YAML hold the same data as DOT: the graph `name` and `attrs`, `nodes` with an
+//! `id` and `attrs`, and `edges` with `from`, `to` and `attrs`. Attribute values are strings;
+//! numbers and booleans are also accepted. Converting between the formats keeps every node,
+//! edge and attribute, but not DOT comments, subgraphs or defaults (see [crate::dot_writer]).
+
+use std::fmt;
+use std::fs;
+use std::path::Path;
+
+use crate::dot_parser::parse_dot;
+use crate::dot_writer::write_dot;
+use crate::types::AttractorGraph;
+use tracing::instrument;
+
+/// A pipeline file format.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PipelineFormat {
+  Dot,
+  Json,
+  Yaml,
+}
+
+impl PipelineFormat {
+  /// Format for `path` by extension: `.json`, `.yaml`/`.yml`, and DOT for anything else.
+  pub fn from_path(path: &Path) -> Self {
+    match path
+      .extension()
+      .and_then(|e| e.to_str())
+      .map(str::to_ascii_lowercase)
+      .as_deref()
+    {
+      Some("json") => Self::Json,
+      Some("yaml" | "yml") => Self::Yaml,
+      _ => Self::Dot,
+    }
+  }
+}
+
+impl fmt::Display for PipelineFormat {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    f.write_str(match self {
+      Self::Dot => "DOT",
+      Self::Json => "JSON",
+      Self::Yaml => "YAML",
+    })
+  }
+}
+
+/// Parses a pipeline from `source` in `format`.
+#[instrument(level = "trace", skip(source))]
+pub fn parse_pipeline(source: &str, format: PipelineFormat) -> Result<AttractorGraph, String> {
+  match format {
+    PipelineFormat::Dot => parse_dot(source).map_err(|e| e.to_string()),
+    PipelineFormat::Json => serde_json::from_str(source).map_err(|e| e.to_string()),
+    PipelineFormat::Yaml => serde_yaml::from_str(source).map_err(|e| e.to_string()),
+  }
+}
+
+/// Writes `graph` in `format`. DOT and JSON output ends with a newline, as YAML's does.
+#[instrument(level = "trace", skip(graph))]
+pub fn write_pipeline(graph: &AttractorGraph, format: PipelineFormat) -> Result<String, String> {
+  match format {
+    PipelineFormat::Dot => Ok(write_dot(graph)),
+    PipelineFormat::Json => serde_json::to_string_pretty(graph)
+      .map(|json| json + "\n")
+      .map_err(|e| e.to_string()),
+    PipelineFormat::Yaml => serde_yaml::to_string(graph).map_err(|e| e.to_string()),
+  }
+}
+
+/// Reads and parses the pipeline at `path`, in the format given by its extension.
+#[instrument(level = "trace")]
+pub fn load_pipeline(path: &Path) -> Result<AttractorGraph, String> {
+  let source =
+    fs::read_to_string(path).map_err(|e| format!("reading {}: {}", path.display(), e))?;
+  let format = PipelineFormat::from_path(path);
+  parse_pipeline(&source, format).map_err(|e| format!("parsing {}: {}", format, e))
+}
@@ -0,0 +1,162 @@
+//! Tests for `pipeline_file`.
+
+use std::path::Path;
+
+use crate::dot_parser::parse_dot;
+use crate::dot_writer::write_dot;
+use crate::pipeline_file::{PipelineFormat, load_pipeline, parse_pipeline, write_pipeline};
+
+#[test]
+fn from_path_picks_format_by_extension() {
+  assert_eq!(
+    PipelineFormat::from_path(Path::new("a.json")),
+    PipelineFormat::Json
+  );
+  assert_eq!(
+    PipelineFormat::from_path(Path::new("a.YML")),
+    PipelineFormat::Yaml
+  );
+  assert_eq!(
+    PipelineFormat::from_path(Path::new("a.yaml")),
+    PipelineFormat::Yaml
+  );
+  assert_eq!(
+    PipelineFormat::from_path(Path::new("a.dot")),
+    PipelineFormat::Dot
+  );
+  assert_eq!(
+    PipelineFormat::from_path(Path::new("pipeline")),
+    PipelineFormat::Dot
+  );
+}
+
+#[test]
+fn json_holds_ids_endpoints_and_attributes() {
+  let g = parse_dot(
+    r#"digraph G { goal="Ship"; start [shape=Mdiamond] work [prompt="Do it", timeout="30s"] start -> work [condition="outcome=success"] }"#,
+  )
+  .unwrap();
+  let json: serde_json::Value =
+    serde_json::from_str(&write_pipeline(&g, PipelineFormat::Json).unwrap()).unwrap();
+  assert_eq!(
+    json,
+    serde_json::json!({
+      "name": "G",
+      "attrs": {"goal": "Ship"},
+      "nodes": [
+        {"id": "start", "attrs": {"shape": "Mdiamond"}},
+        {"id": "work", "attrs": {"prompt": "Do it", "timeout": "30s"}}
+      ],
+      "edges": [{"from": "start", "to": "work", "attrs": {"condition": "outcome=success"}}]
+    })
+  );
+}
+
+#[test]
+fn yaml_pipeline_parses_like_dot() {
+  let yaml = r#"
+attrs:
+  goal: Run tests
+  default_max_retry: 3
+nodes:
+  - id: start
+    attrs: {shape: Mdiamond}
+  - id: test
+    attrs:
+      shape: parallelogram
+      command: cargo test
+      goal_gate: true
+      max_retries: 2
+edges:
+  - {from: start, to: test}
+  - from: test
+    to: exit
+    attrs: {condition: outcome=success, weight: 5}
+"#;
+  let g = parse_pipeline(yaml, PipelineFormat::Yaml).unwrap();
+  assert_eq!(g.goal, "Run tests");
+  assert_eq!(g.default_max_retry, 3);
+  let test = &g.nodes["test"];
+  assert_eq!(test.handler_type.as_deref(), Some("tool"));
+  assert_eq!(test.command.as_deref(), Some("cargo test"));
+  assert!(test.goal_gate);
+  assert_eq!(test.max_retries, Some(2));
+  assert!(g.nodes["exit"].is_exit(), "edge-only node is created");
+  assert_eq!(g.edges[1].weight, 5);
+  assert_eq!(
+    g.edges[1].condition.as_ref().map(|c| c.as_str()),
+    Some("outcome=success")
+  );
+}
+
+#[test]
+fn parse_pipeline_rejects_bad_definitions() {
+  let cases = [
+    (
+      r#"{"nodes": [{"id": "a"}, {"id": "a"}]}"#,
+      "duplicate node \"a\"",
+    ),
+    (
+      r#"{"nodes": [{"id": "a", "shape": "box"}]}"#,
+      "unknown field `shape`",
+    ),
+    (
+      r#"{"edges": [{"from": "a", "to": "b", "attrs": {"condition": "outcome="}}]}"#,
+      "Invalid condition",
+    ),
+    (
+      r#"{"nodes": [{"id": "a", "attrs": {"retry_policy": "sometimes"}}]}"#,
+      "Invalid retry_policy",
+    ),
+  ];
+  for (json, expected) in cases {
+    let err = parse_pipeline(json, PipelineFormat::Json).unwrap_err();
+    assert!(err.contains(expected), "{}: {}", json, err);
+  }
+}
+
+#[test]
+fn example_workflows_convert_losslessly_between_formats() {
+  let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("examples/workflows");
+  for entry in std::fs::read_dir(dir).unwrap() {
+    let path = entry.unwrap().path();
+    if path.extension().and_then(|e| e.to_str()) != Some("dot") {
+      continue;
+    }
+    let g = parse_dot(&std::fs::read_to_string(&path).unwrap()).unwrap();
+    let dot = write_dot(&g);
+    for format in [PipelineFormat::Json, PipelineFormat::Yaml] {
+      let written = write_pipeline(&g, format).unwrap();
+      let reparsed = parse_pipeline(&written, format).unwrap();
+      assert_eq!(
+        write_dot(&reparsed),
+        dot,
+        "{} via {}",
+        path.display(),
+        format
+      );
+      assert_eq!(
+        write_pipeline(&reparsed, format).unwrap(),
+        written,
+        "{} via {}",
+        path.display(),
+        format
+      );
+    }
+  }
+}
+
+#[test]
+fn load_pipeline_reads_by_extension() {
+  let dir = tempfile::tempdir().unwrap();
+  let path = dir.path().join("p.json");
+  std::fs::write(&path, r#"{"edges": [{"from": "start", "to": "exit"}]}"#).unwrap();
+  let g = load_pipeline(&path).unwrap();
+  assert!(g.find_start().is_some() && g.find_exit().is_some());
+
+  std::fs::write(&path, "digraph { start -> exit }").unwrap();
+  let err = load_pipeline(&path).unwrap_err();
+  assert!(err.starts_with("parsing JSON: "), "{}", err);
+  let err = load_pipeline(&dir.path().join("missing.yaml")).unwrap_err();
+  assert!(err.starts_with("reading "), "{}", err);
+}
@@ -3,12 +3,11 @@
 //! - [run_streamweave_graph]: run a compiled graph (one trigger in, first output out).
 //! - [run_compiled_graph]: compile AST then run, return [crate::nodes::execution_loop::AttractorResult].
 
-use crate::execution_log_io::{
-  load_execution_log, resume_state_from_log, write_execution_log_partial,
-};
+use crate::compiler::CompileOptions;
+use crate::execution_log_io::{ExecutionLogSink, load_execution_log, resume_state_from_log};
+use crate::handler::HandlerRegistry;
+use crate::lint::LintRegistry;
 use crate::nodes::execution_loop::AttractorResult;
-use crate::nodes::execution_loop::{RunLoopResult, run_execution_loop_once};
-use crate::nodes::init_context::{create_initial_state, create_initial_state_from_resume_state};
 use crate::types::{AttractorGraph, ExecutionLog, GraphPayload, NodeOutcome, ResumeState};
 use std::path::Path;
 use std::sync::Arc;
@@ -66,6 +65,11 @@ pub struct RunOptions<'a> {
   pub stage_dir: Option<std::path::PathBuf>,
   /// If set, execution steps are recorded and written to this path as execution.log.json (on success and failure).
   pub execution_log_path: Option<std::path::PathBuf>,
+  /// Handlers for the graph's node types; [HandlerRegistry::default] has the built-in ones.
+  pub handlers: HandlerRegistry,
+  /// Rules the graph is checked with before it runs; [LintRegistry::default] has the built-in
+  /// ones. Its `type_known` rule checks against [RunOptions::handlers].
+  pub lints: LintRegistry,
 }
 
 /// Writes execution.log.json to the given path (on both success and failure).
@@ -94,99 +98,24 @@ fn write_execution_log(
 
 /// Compiles the Attractor graph to a StreamWeave graph, runs it, and returns an [AttractorResult].
 /// Uses [crate::compile_attractor_graph]. Initial context includes the graph goal.
-/// When [RunOptions::execution_log_path] is set, runs via the execution loop and writes execution.log.json.
+/// When [RunOptions::execution_log_path] is set, every compiled node records its steps there
+/// (partial log after each step, final log at the end) and an unfinished log is resumed.
 #[instrument(level = "trace", skip(ast, options))]
 pub async fn run_compiled_graph(
   ast: &AttractorGraph,
-  options: RunOptions<'_>,
+  mut options: RunOptions<'_>,
 ) -> Result<AttractorResult, String> {
   // When execution_log_path is set, load log at start; it is the single source of truth for already_completed and resume.
-  // Sync path: we do not write checkpoint.json; execution log is the only persisted state.
-  if let Some(ref log_path) = options.execution_log_path {
-    let exit_id = ast
-      .find_exit()
-      .map(|n| n.id.clone())
-      .ok_or("missing exit node")?;
-
-    let (started_at, goal, mut state) = match load_execution_log(log_path) {
-      Ok(log) => {
-        if let Some(from_log) = resume_state_from_log(&log, Some(exit_id.as_str())) {
-          if from_log.already_completed {
-            return Ok(AttractorResult {
-              last_outcome: NodeOutcome::success("Exit"),
-              completed_nodes: from_log.resume_state.completed_nodes.clone(),
-              context: from_log.resume_state.context.clone(),
-              already_completed: true,
-            });
-          }
-          let resume_state = from_log.resume_state;
-          (
-            log.started_at.clone(),
-            log.goal.clone(),
-            create_initial_state_from_resume_state(ast.clone(), &resume_state, Some(log.steps)),
-          )
-        } else {
-          (
-            chrono::Utc::now().to_rfc3339(),
-            ast.goal.clone(),
-            create_initial_state(ast.clone(), Some(vec![])),
-          )
-        }
-      }
-      Err(_) => (
-        chrono::Utc::now().to_rfc3339(),
-        ast.goal.clone(),
-        match &options.resume_state {
-          Some(st) => create_initial_state_from_resume_state(ast.clone(), st, Some(vec![])),
-          None => create_initial_state(ast.clone(), Some(vec![])),
-        },
-      ),
-    };
-
-    let mut after_step = |st: &crate::types::ExecutionState| {
-      let log = ExecutionLog {
-        version: 1,
-        goal: goal.clone(),
-        started_at: started_at.clone(),
-        finished_at: None,
-        final_status: "in_progress".to_string(),
-        completed_nodes: st.completed_nodes.clone(),
-        steps: st.step_log.clone().unwrap_or_default(),
-      };
-      write_execution_log_partial(log_path, &log).map_err(|e| e.to_string())
-    };
-    match run_execution_loop_once(&mut state, Some(&mut after_step)) {
-      RunLoopResult::Ok(result) => {
-        let steps = state.step_log.unwrap_or_default();
-        write_execution_log(
-          log_path,
-          &goal,
-          &started_at,
-          "success",
-          &result.completed_nodes,
-          steps,
-        )?;
-        // Sync path: execution log is the only persisted state; no checkpoint.json.
-        return Ok(result);
-      }
-      RunLoopResult::Err(e) => {
-        let steps = state.step_log.unwrap_or_default();
-        let completed = state.completed_nodes.clone();
-        write_execution_log(log_path, &goal, &started_at, "error", &completed, steps)?;
-        // Sync path: execution log is the only persisted state; no checkpoint.json.
-        return Err(e);
-      }
-    }
-  }
-
-  // When execution_log_path is not set, optional early return from explicit resume_state.
-  if let Some(ref st) = options.resume_state {
-    let exit_id = ast
-      .find_exit()
-      .map(|n| n.id.clone())
-      .ok_or("missing exit node")?;
-    let at_exit = st.current_node_id == exit_id;
-    if options.resume_already_completed || at_exit {
+  // We do not write checkpoint.json; execution log is the only persisted state.
+  let exit_id = ast
+    .find_exit()
+    .map(|n| n.id.clone())
+    .ok_or("missing exit node")?;
+  let Some(log_path) = options.execution_log_path.clone() else {
+    // Without an execution log, optional early return from explicit resume_state.
+    if let Some(ref st) = options.resume_state
+      && (options.resume_already_completed || st.current_node_id == exit_id)
+    {
       return Ok(AttractorResult {
         last_outcome: NodeOutcome::success("Exit"),
         completed_nodes: st.completed_nodes.clone(),
@@ -194,8 +123,67 @@ pub async fn run_compiled_graph(
         already_completed: true,
       });
     }
+    return run_graph(ast, &options, None).await;
+  };
+  let mut log = ExecutionLog {
+    version: 1,
+    goal: ast.goal.clone(),
+    started_at: chrono::Utc::now().to_rfc3339(),
+    finished_at: None,
+    final_status: "in_progress".to_string(),
+    completed_nodes: vec![],
+    steps: vec![],
+  };
+  if let Ok(existing) = load_execution_log(&log_path)
+    && let Some(from_log) = resume_state_from_log(&existing, Some(exit_id.as_str()))
+  {
+    // Exit already recorded (crash before the final log write): nothing left to run.
+    let exit_recorded = existing
+      .steps
+      .last()
+      .is_some_and(|st| st.node_id == exit_id);
+    if from_log.already_completed || exit_recorded {
+      return Ok(AttractorResult {
+        last_outcome: NodeOutcome::success("Exit"),
+        completed_nodes: from_log.resume_state.completed_nodes.clone(),
+        context: from_log.resume_state.context.clone(),
+        already_completed: true,
+      });
+    }
+    options.resume_state = Some(from_log.resume_state);
+    log = ExecutionLog {
+      finished_at: None,
+      final_status: "in_progress".to_string(),
+      ..existing
+    };
   }
 
+  let sink = ExecutionLogSink::new(log, Some(log_path.clone()));
+  let result = run_graph(ast, &options, Some(&sink)).await;
+  let log = sink.snapshot();
+  let (final_status, completed) = match &result {
+    Ok(r) => ("success", r.completed_nodes.clone()),
+    Err(_) => ("error", log.completed_nodes.clone()),
+  };
+  write_execution_log(
+    &log_path,
+    &log.goal,
+    &log.started_at,
+    final_status,
+    &completed,
+    log.steps,
+  )?;
+  result
+}
+
+/// Compiles and runs the graph once, feeding the initial (or resumed) payload and returning the
+/// first result from the exit router. Steps are recorded to `step_log` when set.
+#[instrument(level = "trace", skip(ast, options, step_log))]
+async fn run_graph(
+  ast: &AttractorGraph,
+  options: &RunOptions<'_>,
+  step_log: Option<&ExecutionLogSink>,
+) -> Result<AttractorResult, String> {
   let stage_dir = options
     .stage_dir
     .as_deref()
@@ -204,12 +192,15 @@ pub async fn run_compiled_graph(
     .resume_state
     .as_ref()
     .map(|st| st.current_node_id.as_str());
-  let mut graph = crate::compiler::compile_attractor_graph(
-    ast,
+  let compile_options = CompileOptions {
     entry_node_id,
-    options.agent_cmd.as_deref(),
+    agent_cmd: options.agent_cmd.as_deref(),
     stage_dir,
-  )?;
+    step_log,
+    handlers: options.handlers.clone(),
+    lints: options.lints.clone(),
+  };
+  let mut graph = crate::compiler::compile_attractor_graph(ast, &compile_options)?;
   let initial = match &options.resume_state {
     Some(st) => GraphPayload::from_resume_state(st),
     None => {
@@ -254,7 +245,13 @@ pub async fn run_compiled_graph(
   } else {
     rx_out.recv().await
   };
-  // Do not wait_for_completion(); first result decides outcome, avoids hang on merge graphs.
+  // First result decides outcome; the exit router has already shut the run down, so every node
+  // (including those in fix-and-retry cycles) stops and the graph completes.
+  tracing::trace!("run_compiled_graph: received result, calling wait_for_completion()");
+  graph
+    .wait_for_completion()
+    .await
+    .map_err(|e| e.to_string())?;
 
   let payload = first
     .and_then(|arc| arc.downcast::<GraphPayload>().ok())
@@ -3,11 +3,12 @@
 use std::fs;
 
 use crate::agent_run::read_outcome_file;
+use crate::types::OutcomeStatus;
 
 fn context_updates_from_file(
   stage_dir: &std::path::Path,
 ) -> Option<std::collections::HashMap<String, String>> {
-  read_outcome_file(Some(stage_dir)).map(|(_, updates)| updates)
+  read_outcome_file(Some(stage_dir)).map(|f| f.context_updates)
 }
 
 #[test]
@@ -59,3 +60,34 @@ fn read_outcome_file_returns_empty_map_when_no_context_updates_key() {
   assert!(updates.is_some());
   assert!(updates.unwrap().is_empty());
 }
+
+#[test]
+fn read_outcome_file_returns_routing_hints() {
+  let dir = tempfile::tempdir().unwrap();
+  std::fs::write(
+    dir.path().join("outcome.json"),
+    r#"{"outcome":"success","preferred_label":" Approve ","suggested_next_ids":["ship","qa"]}"#,
+  )
+  .unwrap();
+  let file = read_outcome_file(Some(dir.path())).unwrap();
+  assert_eq!(file.outcome, Some(OutcomeStatus::Success));
+  assert_eq!(file.preferred_label.as_deref(), Some("Approve"));
+  assert_eq!(file.suggested_next_ids, ["ship", "qa"]);
+}
+
+#[test]
+fn read_outcome_file_reads_retry_outcome() {
+  let dir = tempfile::tempdir().unwrap();
+  std::fs::write(dir.path().join("outcome.json"), r#"{"outcome":"Retry"}"#).unwrap();
+  let file = read_outcome_file(Some(dir.path())).unwrap();
+  assert_eq!(file.outcome, Some(OutcomeStatus::Retry));
+}
+
+#[test]
+fn read_outcome_file_without_routing_hints() {
+  let dir = tempfile::tempdir().unwrap();
+  std::fs::write(dir.path().join("outcome.json"), r#"{"preferred_label":""}"#).unwrap();
+  let file = read_outcome_file(Some(dir.path())).unwrap();
+  assert_eq!(file.preferred_label, None);
+  assert!(file.suggested_next_ids.is_empty());
+}
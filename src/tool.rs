@@ -0,0 +1,192 @@
+//! Tool nodes (shape `parallelogram`): run a program with structured arguments and read its
+//! JSON result into the context.
+//!
+//! `tool_command` names the program and any fixed arguments. It is split on whitespace and run
+//! without a shell, so there is no quoting, globbing or `$` expansion. `tool_args` is a JSON
+//! object; `$key` in its string values reads the context key (missing keys read as the empty
+//! string), and the templated object is written to the tool's stdin. The tool answers with a
+//! JSON object on stdout, and each field becomes a context update: strings as they are, other
+//! values as JSON.
+//!
+//! The outcome tells the tool's own result from a tool error:
+//!
+//! - exit 0 is success; stdout may be empty.
+//! - A non-zero exit with a JSON object on stdout is the tool's answer (e.g. checks that
+//!   failed): an error outcome whose fields are still applied, so `outcome=fail` edges can read
+//!   them.
+//! - Anything else is a tool error: the program cannot start, is killed by a signal, prints
+//!   something other than a JSON object, or exits non-zero without printing one. That is a
+//!   retry outcome with a `tool error:` reason, so the node's retry budget applies.
+//!
+//! The tool's stderr is captured rather than inherited; a tool error's reason ends with it.
+
+use std::process::Stdio;
+
+use crate::handler::{Handler, HandlerServices};
+use crate::nodes::validate_graph::variable_references;
+use crate::types::{AttractorNode, NodeOutcome, RunContext};
+use async_trait::async_trait;
+use serde_json::{Map, Value};
+use tokio::io::AsyncWriteExt;
+use tokio::process::Command;
+use tracing::instrument;
+
+/// Handler type of `parallelogram` nodes.
+pub const TOOL: &str = "tool";
+
+/// The node's `tool_args` object; empty when the node has none.
+pub fn tool_args(node: &AttractorNode) -> Result<Map<String, Value>, String> {
+  let Some(args) = node.attrs.get("tool_args") else {
+    return Ok(Map::new());
+  };
+  match serde_json::from_str(args) {
+    Ok(Value::Object(args)) => Ok(args),
+    Ok(_) => Err(format!(
+      "tool_args of node '{}' must be a JSON object",
+      node.id
+    )),
+    Err(e) => Err(format!(
+      "tool_args of node '{}' is not valid JSON: {}",
+      node.id, e
+    )),
+  }
+}
+
+/// Context keys read by `$key` references in the string values of `args`, by field name.
+pub(crate) fn arg_variables(args: &Map<String, Value>) -> Vec<&str> {
+  fn collect<'a>(value: &'a Value, vars: &mut Vec<&'a str>) {
+    match value {
+      Value::String(s) => vars.extend(variable_references(s).into_iter().map(|(_, name)| name)),
+      Value::Array(items) => items.iter().for_each(|v| collect(v, vars)),
+      Value::Object(fields) => fields.values().for_each(|v| collect(v, vars)),
+      _ => {}
+    }
+  }
+  let mut vars = Vec::new();
+  args.values().for_each(|v| collect(v, &mut vars));
+  vars
+}
+
+/// `value` with every `$key` in its strings replaced by the context value.
+pub(crate) fn template(value: &Value, context: &RunContext) -> Value {
+  match value {
+    Value::String(s) => Value::String(expand(s, context)),
+    Value::Array(items) => Value::Array(items.iter().map(|v| template(v, context)).collect()),
+    Value::Object(fields) => Value::Object(
+      fields
+        .iter()
+        .map(|(k, v)| (k.clone(), template(v, context)))
+        .collect(),
+    ),
+    other => other.clone(),
+  }
+}
+
+/// `text` with each `$key` replaced by its context value (`context.key` looks up `key` too).
+fn expand(text: &str, context: &RunContext) -> String {
+  let mut out = String::with_capacity(text.len());
+  let mut at = 0;
+  for (dollar, name) in variable_references(text) {
+    out.push_str(&text[at..dollar]);
+    let value = context
+      .get(name)
+      .or_else(|| name.strip_prefix("context.").and_then(|n| context.get(n)));
+    out.push_str(value.map_or("", String::as_str));
+    at = dollar + 1 + name.len();
+  }
+  out.push_str(&text[at..]);
+  out
+}
+
+/// Runs the node's `tool_command` with its templated `tool_args` and reads the JSON it prints
+/// (see [crate::tool]).
+pub struct ToolHandler;
+
+#[async_trait]
+impl Handler for ToolHandler {
+  #[instrument(level = "trace", skip_all, fields(node = %node.id))]
+  async fn execute(
+    &self,
+    node: &AttractorNode,
+    context: &RunContext,
+    _: &HandlerServices,
+  ) -> NodeOutcome {
+    let Some(command) = node.attrs.get("tool_command").map(str::to_string) else {
+      return NodeOutcome::error(format!(
+        "tool node '{}' requires a tool_command attribute",
+        node.id
+      ));
+    };
+    let args = match tool_args(node) {
+      Ok(args) => template(&Value::Object(args), context),
+      Err(e) => return NodeOutcome::error(e),
+    };
+    run_tool(&command, &args).await
+  }
+}
+
+/// Runs `command` with `args` as JSON on stdin and turns its exit status and stdout into an
+/// outcome. Dropping the future kills the tool.
+async fn run_tool(command: &str, args: &Value) -> NodeOutcome {
+  let mut words = command.split_whitespace();
+  let Some(program) = words.next() else {
+    return NodeOutcome::error("tool_command is empty");
+  };
+  let child = Command::new(program)
+    .args(words)
+    .stdin(Stdio::piped())
+    .stdout(Stdio::piped())
+    .stderr(Stdio::piped())
+    .kill_on_drop(true)
+    .spawn();
+  let mut child = match child {
+    Ok(child) => child,
+    Err(e) => return tool_error(format!("cannot run {}: {}", program, e)),
+  };
+  if let Some(mut stdin) = child.stdin.take() {
+    // A tool that takes no arguments may exit without reading them.
+    let _ = stdin.write_all(args.to_string().as_bytes()).await;
+  }
+  let output = match child.wait_with_output().await {
+    Ok(output) => output,
+    Err(e) => return tool_error(format!("{} failed: {}", program, e)),
+  };
+  let stderr = String::from_utf8_lossy(&output.stderr);
+  let tool_error = |reason: String| match stderr.trim() {
+    "" => tool_error(reason),
+    stderr => tool_error(format!("{}: {}", reason, stderr)),
+  };
+  let Some(code) = output.status.code() else {
+    return tool_error(format!("{} was killed by a signal", program));
+  };
+  let stdout = String::from_utf8_lossy(&output.stdout);
+  let fields = match serde_json::from_str(&stdout) {
+    Ok(Value::Object(fields)) => fields,
+    _ if code == 0 && stdout.trim().is_empty() => Map::new(),
+    _ if code == 0 => return tool_error(format!("{} did not print a JSON object", program)),
+    _ => {
+      return tool_error(format!(
+        "{} exited {} without printing a JSON object",
+        program, code
+      ));
+    }
+  };
+  let mut outcome = if code == 0 {
+    NodeOutcome::success(format!("{} ok", program))
+  } else {
+    NodeOutcome::error(format!("{} exited {}", program, code))
+  };
+  outcome.context_updates = fields
+    .into_iter()
+    .map(|(k, v)| match v {
+      Value::String(s) => (k, s),
+      v => (k, v.to_string()),
+    })
+    .collect();
+  outcome
+}
+
+/// A tool that could not give an answer: retried while the node's retry budget lasts.
+fn tool_error(reason: String) -> NodeOutcome {
+  NodeOutcome::retry(format!("tool error: {}", reason))
+}
@@ -0,0 +1,158 @@
+//! Tests for `tool`.
+
+use crate::dot_parser::parse_dot;
+use crate::handler::{Handler, HandlerServices};
+use crate::tool::{ToolHandler, arg_variables, template, tool_args};
+use crate::types::{AttractorNode, NodeOutcome, OutcomeStatus, RunContext};
+use serde_json::json;
+
+/// A tool node running `sh <script>` with `body` as the script, and `tool_args` when given.
+fn tool(dir: &tempfile::TempDir, body: &str, args: Option<&str>) -> AttractorNode {
+  let script = dir.path().join("tool.sh");
+  std::fs::write(&script, body).unwrap();
+  let mut node = parse_dot("digraph { lookup [shape=parallelogram] }")
+    .unwrap()
+    .nodes["lookup"]
+    .clone();
+  node
+    .attrs
+    .insert("tool_command", format!("sh {}", script.display()));
+  if let Some(args) = args {
+    node.attrs.insert("tool_args", args);
+  }
+  node
+}
+
+async fn run(node: &AttractorNode, context: &[(&str, &str)]) -> NodeOutcome {
+  let context: RunContext = context
+    .iter()
+    .map(|(k, v)| (k.to_string(), v.to_string()))
+    .collect();
+  ToolHandler
+    .execute(node, &context, &HandlerServices::default())
+    .await
+}
+
+#[test]
+fn template_reads_context_keys_in_strings() {
+  let context: RunContext = [
+    ("issue".to_string(), "42".to_string()),
+    ("repo".to_string(), "acme/app".to_string()),
+  ]
+  .into();
+  let args = json!({
+    "issue": "$issue",
+    "title": "Fix #$issue in $context.repo for $5",
+    "labels": ["bug", "$missing"],
+    "limit": 3,
+  });
+  assert_eq!(
+    template(&args, &context),
+    json!({
+      "issue": "42",
+      "title": "Fix #42 in acme/app for $5",
+      "labels": ["bug", ""],
+      "limit": 3,
+    })
+  );
+  assert_eq!(
+    arg_variables(args.as_object().unwrap()),
+    ["issue", "missing", "issue", "context.repo"]
+  );
+}
+
+#[test]
+fn tool_args_must_be_a_json_object() {
+  let dir = tempfile::tempdir().unwrap();
+  assert!(tool_args(&tool(&dir, "", None)).unwrap().is_empty());
+  assert_eq!(
+    tool_args(&tool(&dir, "", Some("[1]"))).unwrap_err(),
+    "tool_args of node 'lookup' must be a JSON object"
+  );
+  assert!(
+    tool_args(&tool(&dir, "", Some("{issue: 1}")))
+      .unwrap_err()
+      .contains("is not valid JSON")
+  );
+}
+
+#[tokio::test]
+async fn output_fields_become_context_updates() {
+  let dir = tempfile::tempdir().unwrap();
+  let node = tool(
+    &dir,
+    r#"args=$(cat); echo "{\"args\": $args, \"title\": \"Crash\", \"open\": true}""#,
+    Some(r#"{"id": "$issue"}"#),
+  );
+  let out = run(&node, &[("issue", "42")]).await;
+  assert_eq!(out.status, OutcomeStatus::Success);
+  assert_eq!(out.context_updates["args"], r#"{"id":"42"}"#);
+  assert_eq!(out.context_updates["title"], "Crash");
+  assert_eq!(out.context_updates["open"], "true");
+}
+
+#[tokio::test]
+async fn non_zero_exit_with_json_is_the_tools_answer() {
+  let dir = tempfile::tempdir().unwrap();
+  let node = tool(&dir, r#"echo '{"failed": "2"}'; exit 1"#, None);
+  let out = run(&node, &[]).await;
+  assert_eq!(out.status, OutcomeStatus::Error);
+  assert_eq!(out.failure_reason.as_deref(), Some("sh exited 1"));
+  assert_eq!(out.context_updates["failed"], "2");
+
+  let quiet = tool(&dir, "exit 0", None);
+  let out = run(&quiet, &[]).await;
+  assert_eq!(out.status, OutcomeStatus::Success);
+  assert!(out.context_updates.is_empty());
+}
+
+#[tokio::test]
+async fn tool_errors_ask_for_a_retry() {
+  let dir = tempfile::tempdir().unwrap();
+  for (body, reason) in [
+    (
+      "echo not json",
+      "tool error: sh did not print a JSON object",
+    ),
+    (
+      "echo oops >&2; exit 3",
+      "tool error: sh exited 3 without printing a JSON object: oops",
+    ),
+    ("kill -9 $$", "tool error: sh was killed by a signal"),
+  ] {
+    let out = run(&tool(&dir, body, None), &[]).await;
+    assert_eq!(out.status, OutcomeStatus::Retry, "{}", body);
+    assert_eq!(out.failure_reason.as_deref(), Some(reason));
+  }
+
+  let mut missing = tool(&dir, "", None);
+  missing
+    .attrs
+    .insert("tool_command", "no-such-tool-xyz --flag");
+  let out = run(&missing, &[]).await;
+  assert_eq!(out.status, OutcomeStatus::Retry);
+  assert!(
+    out
+      .failure_reason
+      .unwrap()
+      .starts_with("tool error: cannot run no-such-tool-xyz")
+  );
+}
+
+#[tokio::test]
+async fn missing_command_or_bad_args_is_an_error() {
+  let dir = tempfile::tempdir().unwrap();
+  let mut node = tool(&dir, "", Some("[]"));
+  let out = run(&node, &[]).await;
+  assert_eq!(out.status, OutcomeStatus::Error);
+  assert_eq!(
+    out.failure_reason.as_deref(),
+    Some("tool_args of node 'lookup' must be a JSON object")
+  );
+  node.attrs.remove("tool_command");
+  let out = run(&node, &[]).await;
+  assert_eq!(
+    out.failure_reason.as_deref(),
+    Some("tool node 'lookup' requires a tool_command attribute")
+  );
+}
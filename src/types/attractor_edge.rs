@@ -1,11 +1,49 @@
 //! An edge in the Attractor DOT graph.
 
+use serde::{Deserialize, Deserializer, Serialize, Serializer, de};
+
+use super::Attributes;
+use crate::condition::Condition;
+use crate::dot_parser::build_edge;
+use crate::dot_writer::edge_attributes;
+
 /// An edge in the Attractor DOT graph.
 #[derive(Debug, Clone)]
 pub struct AttractorEdge {
   pub from_node: String,
   pub to_node: String,
   pub label: Option<String>,
-  pub condition: Option<String>,
+  /// Parsed `condition` attribute (see [crate::condition]).
+  pub condition: Option<Condition>,
   pub weight: i32,
+  /// Every attribute as written, including the ones parsed into the fields above.
+  pub attrs: Attributes,
+}
+
+/// Serialized form of an edge: its endpoints and DOT attributes.
+#[derive(Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct EdgeDef {
+  from: String,
+  to: String,
+  #[serde(default, skip_serializing_if = "Attributes::is_empty")]
+  attrs: Attributes,
+}
+
+impl Serialize for AttractorEdge {
+  fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+    EdgeDef {
+      from: self.from_node.clone(),
+      to: self.to_node.clone(),
+      attrs: edge_attributes(self),
+    }
+    .serialize(serializer)
+  }
+}
+
+impl<'de> Deserialize<'de> for AttractorEdge {
+  fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+    let def = EdgeDef::deserialize(deserializer)?;
+    build_edge(&def.from, &def.to, &def.attrs.to_pairs()).map_err(de::Error::custom)
+  }
 }
@@ -9,6 +9,7 @@ fn edge(from: &str, to: &str) -> AttractorEdge {
     label: None,
     condition: None,
     weight: 0,
+    attrs: Default::default(),
   }
 }
 
@@ -28,11 +29,12 @@ fn edge_with_attrs() {
     from_node: "x".to_string(),
     to_node: "y".to_string(),
     label: Some("yes".to_string()),
-    condition: Some("when".to_string()),
+    condition: Some("when".parse().unwrap()),
     weight: 5,
+    attrs: Default::default(),
   };
   assert_eq!(e.label.as_deref(), Some("yes"));
-  assert_eq!(e.condition.as_deref(), Some("when"));
+  assert_eq!(e.condition.as_ref().map(|c| c.as_str()), Some("when"));
   assert_eq!(e.weight, 5);
 }
 
@@ -1,9 +1,12 @@
 //! Parsed Attractor pipeline graph (DOT).
 
+use serde::{Deserialize, Deserializer, Serialize, Serializer, de};
 use std::collections::HashMap;
 use tracing::instrument;
 
-use super::{AttractorEdge, AttractorNode};
+use super::{AttractorEdge, AttractorNode, Attributes, RetryPolicy};
+use crate::dot_parser::{apply_graph_attrs, parse_node_attrs};
+use crate::dot_writer::{graph_attributes, node_order};
 
 /// Parsed Attractor pipeline graph (DOT).
 #[derive(Debug, Clone)]
@@ -12,6 +15,33 @@ pub struct AttractorGraph {
   pub nodes: HashMap<String, AttractorNode>,
   pub edges: Vec<AttractorEdge>,
   pub default_max_retry: u32,
+  /// Retry backoff for nodes without a `retry_policy` attribute.
+  pub default_retry_policy: RetryPolicy,
+  /// Goal-gate retry target for nodes without their own (spec §3.4).
+  pub retry_target: Option<String>,
+  /// Used when no other retry target is set.
+  pub fallback_retry_target: Option<String>,
+  /// Name after `digraph`, if any.
+  pub name: Option<String>,
+  /// Every top-level graph attribute as written, including the ones parsed into fields above.
+  pub attrs: Attributes,
+}
+
+impl Default for AttractorGraph {
+  /// An empty graph with the defaults DOT parsing starts from.
+  fn default() -> Self {
+    Self {
+      goal: String::new(),
+      nodes: HashMap::new(),
+      edges: Vec::new(),
+      default_max_retry: 50,
+      default_retry_policy: RetryPolicy::default(),
+      retry_target: None,
+      fallback_retry_target: None,
+      name: None,
+      attrs: Attributes::new(),
+    }
+  }
 }
 
 impl AttractorGraph {
@@ -33,4 +63,100 @@ impl AttractorGraph {
       .filter(|e| e.from_node == node_id)
       .collect()
   }
+
+  /// Retry budget for `node_id`: its `max_retries`, else the graph's `default_max_retry`.
+  pub fn max_retries_for(&self, node_id: &str) -> u32 {
+    self
+      .nodes
+      .get(node_id)
+      .and_then(|n| n.max_retries)
+      .unwrap_or(self.default_max_retry)
+  }
+
+  /// Retry backoff for `node_id`: its `retry_policy`, else the graph's `default_retry_policy`.
+  pub fn retry_policy_for(&self, node_id: &str) -> RetryPolicy {
+    self
+      .nodes
+      .get(node_id)
+      .and_then(|n| n.retry_policy.clone())
+      .unwrap_or_else(|| self.default_retry_policy.clone())
+  }
+
+  /// Where to jump when goal gate `node_id` is unsatisfied (spec §3.4): the node's
+  /// `retry_target`, then its `fallback_retry_target`, then the graph-level ones.
+  pub fn retry_target_for(&self, node_id: &str) -> Option<&str> {
+    let node = self.nodes.get(node_id);
+    node
+      .and_then(|n| n.retry_target.as_deref())
+      .or_else(|| node.and_then(|n| n.fallback_retry_target.as_deref()))
+      .or(self.retry_target.as_deref())
+      .or(self.fallback_retry_target.as_deref())
+  }
+}
+
+/// Serialized form of a graph. Nodes are written in the same order as by
+/// [crate::dot_writer::write_dot].
+#[derive(Serialize)]
+struct GraphDefRef<'a> {
+  #[serde(skip_serializing_if = "Option::is_none")]
+  name: Option<&'a str>,
+  #[serde(skip_serializing_if = "Attributes::is_empty")]
+  attrs: Attributes,
+  nodes: Vec<&'a AttractorNode>,
+  edges: &'a [AttractorEdge],
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct GraphDef {
+  #[serde(default)]
+  name: Option<String>,
+  #[serde(default)]
+  attrs: Attributes,
+  #[serde(default)]
+  nodes: Vec<AttractorNode>,
+  #[serde(default)]
+  edges: Vec<AttractorEdge>,
+}
+
+impl Serialize for AttractorGraph {
+  fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+    GraphDefRef {
+      name: self.name.as_deref(),
+      attrs: graph_attributes(self),
+      nodes: node_order(self),
+      edges: &self.edges,
+    }
+    .serialize(serializer)
+  }
+}
+
+impl<'de> Deserialize<'de> for AttractorGraph {
+  /// Builds the graph as DOT parsing would: nodes named only by an edge are created with
+  /// default attributes, and a node listed twice is an error.
+  fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+    let def = GraphDef::deserialize(deserializer)?;
+    let mut graph = AttractorGraph {
+      name: def.name,
+      ..Default::default()
+    };
+    apply_graph_attrs(&def.attrs.to_pairs(), &mut graph).map_err(de::Error::custom)?;
+    graph.attrs = def.attrs;
+    for node in def.nodes {
+      if graph.nodes.contains_key(&node.id) {
+        return Err(de::Error::custom(format!("duplicate node {:?}", node.id)));
+      }
+      graph.nodes.insert(node.id.clone(), node);
+    }
+    for edge in &def.edges {
+      for id in [&edge.from_node, &edge.to_node] {
+        if !graph.nodes.contains_key(id) {
+          let node = parse_node_attrs(id, &[]).map_err(de::Error::custom)?;
+          graph.nodes.insert(id.clone(), node);
+        }
+      }
+    }
+    graph.edges = def.edges;
+    Ok(graph)
+  }
 }
@@ -13,7 +13,12 @@ fn node(id: &str, shape: &str) -> AttractorNode {
     prompt: None,
     command: None,
     goal_gate: false,
-    max_retries: 0,
+    max_retries: None,
+    retry_policy: None,
+    retry_target: None,
+    fallback_retry_target: None,
+    classes: Vec::new(),
+    attrs: Default::default(),
   }
 }
 
@@ -25,6 +30,11 @@ fn graph_with_nodes(nodes: Vec<AttractorNode>, edges: Vec<AttractorEdge>) -> Att
     nodes: nodes_map,
     edges,
     default_max_retry: 50,
+    default_retry_policy: Default::default(),
+    retry_target: None,
+    fallback_retry_target: None,
+    name: None,
+    attrs: Default::default(),
   }
 }
 
@@ -83,6 +93,7 @@ fn outgoing_edges() {
         label: None,
         condition: None,
         weight: 0,
+        attrs: Default::default(),
       },
       AttractorEdge {
         from_node: "a".to_string(),
@@ -90,6 +101,7 @@ fn outgoing_edges() {
         label: None,
         condition: None,
         weight: 0,
+        attrs: Default::default(),
       },
       AttractorEdge {
         from_node: "b".to_string(),
@@ -97,6 +109,7 @@ fn outgoing_edges() {
         label: None,
         condition: None,
         weight: 0,
+        attrs: Default::default(),
       },
     ],
   );
@@ -116,8 +129,37 @@ fn outgoing_edges_empty() {
       label: None,
       condition: None,
       weight: 0,
+      attrs: Default::default(),
     }],
   );
   let out = g.outgoing_edges("a");
   assert!(out.is_empty());
 }
+
+#[test]
+fn max_retries_for_falls_back_to_graph_default() {
+  let mut flaky = node("flaky", "box");
+  flaky.max_retries = Some(2);
+  let mut g = graph_with_nodes(vec![flaky, node("plain", "box")], vec![]);
+  g.default_max_retry = 7;
+  assert_eq!(g.max_retries_for("flaky"), 2);
+  assert_eq!(g.max_retries_for("plain"), 7);
+  assert_eq!(g.max_retries_for("missing"), 7);
+}
+
+#[test]
+fn retry_target_for_prefers_node_then_graph_targets() {
+  let mut a = node("a", "box");
+  a.retry_target = Some("plan".to_string());
+  a.fallback_retry_target = Some("other".to_string());
+  let mut b = node("b", "box");
+  b.fallback_retry_target = Some("fix".to_string());
+  let mut g = graph_with_nodes(vec![a, b, node("c", "box")], vec![]);
+  assert_eq!(g.retry_target_for("a"), Some("plan"));
+  assert_eq!(g.retry_target_for("b"), Some("fix"));
+  assert_eq!(g.retry_target_for("c"), None);
+  g.fallback_retry_target = Some("start".to_string());
+  assert_eq!(g.retry_target_for("c"), Some("start"));
+  g.retry_target = Some("implement".to_string());
+  assert_eq!(g.retry_target_for("c"), Some("implement"));
+}
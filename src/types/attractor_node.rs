@@ -1,6 +1,11 @@
 //! A node in the Attractor DOT graph (parsed from DOT).
+use serde::{Deserialize, Deserializer, Serialize, Serializer, de};
 use tracing::instrument;
 
+use super::{Attributes, RetryPolicy};
+use crate::dot_parser::parse_node_attrs;
+use crate::dot_writer::node_attributes;
+
 /// Returns true if the shape indicates a start node (Mdiamond).
 #[instrument(level = "trace", skip(shape))]
 #[instrument(level = "trace", skip(shape))]
@@ -37,7 +42,18 @@ pub struct AttractorNode {
   /// Command to run for `exec` handler; success on exit 0, fail otherwise.
   pub command: Option<String>,
   pub goal_gate: bool,
-  pub max_retries: u32,
+  /// Additional attempts after a `Retry` outcome; None falls back to the graph's `default_max_retry`.
+  pub max_retries: Option<u32>,
+  /// Backoff between retries; None falls back to the graph's `default_retry_policy`.
+  pub retry_policy: Option<RetryPolicy>,
+  /// Node to jump back to when this goal gate is unsatisfied at exit (spec §3.4).
+  pub retry_target: Option<String>,
+  /// Used when `retry_target` is unset.
+  pub fallback_retry_target: Option<String>,
+  /// Classes from the `class` attribute, then those derived from enclosing subgraph labels.
+  pub classes: Vec<String>,
+  /// Every attribute as written, including the ones parsed into the fields above.
+  pub attrs: Attributes,
 }
 
 impl AttractorNode {
@@ -52,4 +68,41 @@ impl AttractorNode {
   pub fn is_terminal(&self) -> bool {
     self.is_exit()
   }
+
+  /// Context keys this node sets, from its comma-separated `produces` attribute.
+  pub fn produces(&self) -> Vec<String> {
+    self.attrs.get_list("produces").unwrap_or_default()
+  }
+
+  /// Context keys this node reads beyond those named in its prompt, from its comma-separated
+  /// `consumes` attribute.
+  pub fn consumes(&self) -> Vec<String> {
+    self.attrs.get_list("consumes").unwrap_or_default()
+  }
+}
+
+/// Serialized form of a node: its id and DOT attributes, so JSON and YAML mean the same as DOT.
+#[derive(Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct NodeDef {
+  id: String,
+  #[serde(default, skip_serializing_if = "Attributes::is_empty")]
+  attrs: Attributes,
+}
+
+impl Serialize for AttractorNode {
+  fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+    NodeDef {
+      id: self.id.clone(),
+      attrs: node_attributes(self),
+    }
+    .serialize(serializer)
+  }
+}
+
+impl<'de> Deserialize<'de> for AttractorNode {
+  fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+    let def = NodeDef::deserialize(deserializer)?;
+    parse_node_attrs(&def.id, &def.attrs.to_pairs()).map_err(de::Error::custom)
+  }
 }
@@ -13,7 +13,12 @@ fn node(id: &str, shape: &str) -> AttractorNode {
     prompt: None,
     command: None,
     goal_gate: false,
-    max_retries: 0,
+    max_retries: None,
+    retry_policy: None,
+    retry_target: None,
+    fallback_retry_target: None,
+    classes: Vec::new(),
+    attrs: Default::default(),
   }
 }
 
@@ -99,3 +104,13 @@ fn is_terminal_when_not_exit() {
   let n = node("foo", "ellipse");
   assert!(!n.is_terminal());
 }
+
+#[test]
+fn produces_and_consumes_read_comma_separated_attrs() {
+  let mut n = node("pick", "box");
+  assert!(n.produces().is_empty() && n.consumes().is_empty());
+  n.attrs.insert("produces", "ready_task_id, branch,");
+  n.attrs.insert("consumes", "goal");
+  assert_eq!(n.produces(), ["ready_task_id", "branch"]);
+  assert_eq!(n.consumes(), ["goal"]);
+}
@@ -0,0 +1,210 @@
+//! All DOT attributes of a node or edge, in the order they were first set.
+//!
+//! Known attributes are also parsed into typed fields on [super::AttractorNode] and
+//! [super::AttractorEdge]; this map keeps every attribute (e.g. `timeout`, `fidelity`,
+//! `thread_id`) so handlers can read ones the parser does not know about.
+//!
+//! Serialized as a map of strings. Numbers and booleans are accepted when deserializing and
+//! kept as their text, as DOT would.
+
+use std::fmt;
+use std::time::Duration;
+
+use serde::de::{self, MapAccess, Visitor};
+use serde::ser::SerializeMap;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use super::retry_policy::parse_duration;
+
+/// Ordered attribute map. Setting an existing key replaces its value in place.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Attributes {
+  entries: Vec<(String, String)>,
+}
+
+impl Attributes {
+  pub fn new() -> Self {
+    Self::default()
+  }
+
+  /// Raw value of `key`.
+  pub fn get(&self, key: &str) -> Option<&str> {
+    self
+      .entries
+      .iter()
+      .find(|(k, _)| k == key)
+      .map(|(_, v)| v.as_str())
+  }
+
+  pub fn contains_key(&self, key: &str) -> bool {
+    self.get(key).is_some()
+  }
+
+  /// Sets `key`, keeping its position if already present. Returns the previous value.
+  pub fn insert(&mut self, key: impl Into<String>, value: impl Into<String>) -> Option<String> {
+    let key = key.into();
+    let value = value.into();
+    match self.entries.iter_mut().find(|(k, _)| *k == key) {
+      Some(entry) => Some(std::mem::replace(&mut entry.1, value)),
+      None => {
+        self.entries.push((key, value));
+        None
+      }
+    }
+  }
+
+  pub fn remove(&mut self, key: &str) -> Option<String> {
+    let idx = self.entries.iter().position(|(k, _)| k == key)?;
+    Some(self.entries.remove(idx).1)
+  }
+
+  /// Attributes in order.
+  pub fn iter(&self) -> impl Iterator<Item = (&str, &str)> {
+    self.entries.iter().map(|(k, v)| (k.as_str(), v.as_str()))
+  }
+
+  pub fn len(&self) -> usize {
+    self.entries.len()
+  }
+
+  pub fn is_empty(&self) -> bool {
+    self.entries.is_empty()
+  }
+
+  /// Attributes as owned pairs, in order.
+  pub(crate) fn to_pairs(&self) -> Vec<(String, String)> {
+    self.entries.clone()
+  }
+
+  /// `key` as a boolean: `true`/`false`, `yes`/`no` or `1`/`0` (any case).
+  pub fn get_bool(&self, key: &str) -> Result<Option<bool>, String> {
+    self.typed(key, "boolean", |v| match v.to_ascii_lowercase().as_str() {
+      "true" | "yes" | "1" => Some(true),
+      "false" | "no" | "0" => Some(false),
+      _ => None,
+    })
+  }
+
+  /// `key` as an integer.
+  pub fn get_int(&self, key: &str) -> Result<Option<i64>, String> {
+    self.typed(key, "integer", |v| v.parse().ok())
+  }
+
+  /// `key` as a duration: `250ms`, `30s`, `15m`, `2h`, `1d`, or a bare number of milliseconds.
+  pub fn get_duration(&self, key: &str) -> Result<Option<Duration>, String> {
+    self.typed(key, "duration", |v| parse_duration(v).ok())
+  }
+
+  /// `key` as a comma-separated list, with items trimmed and empty items dropped.
+  pub fn get_list(&self, key: &str) -> Option<Vec<String>> {
+    self.get(key).map(|v| {
+      v.split(',')
+        .map(str::trim)
+        .filter(|item| !item.is_empty())
+        .map(String::from)
+        .collect()
+    })
+  }
+
+  /// Parses `key` with `parse`; a value it rejects is an error naming the key and `kind`.
+  fn typed<T>(
+    &self,
+    key: &str,
+    kind: &str,
+    parse: impl FnOnce(&str) -> Option<T>,
+  ) -> Result<Option<T>, String> {
+    let Some(v) = self.get(key) else {
+      return Ok(None);
+    };
+    parse(v.trim())
+      .map(Some)
+      .ok_or_else(|| format!("attribute {} = {:?} is not a valid {}", key, v, kind))
+  }
+}
+
+impl<K: Into<String>, V: Into<String>> FromIterator<(K, V)> for Attributes {
+  fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+    let mut attrs = Self::new();
+    for (k, v) in iter {
+      attrs.insert(k, v);
+    }
+    attrs
+  }
+}
+
+impl Serialize for Attributes {
+  fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+    let mut map = serializer.serialize_map(Some(self.len()))?;
+    for (k, v) in self.iter() {
+      map.serialize_entry(k, v)?;
+    }
+    map.end()
+  }
+}
+
+impl<'de> Deserialize<'de> for Attributes {
+  fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+    deserializer.deserialize_map(AttributesVisitor)
+  }
+}
+
+struct AttributesVisitor;
+
+impl<'de> Visitor<'de> for AttributesVisitor {
+  type Value = Attributes;
+
+  fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    f.write_str("a map of attribute names to strings, numbers or booleans")
+  }
+
+  fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Attributes, A::Error> {
+    let mut attrs = Attributes::new();
+    while let Some((k, v)) = map.next_entry::<String, AttributeValue>()? {
+      attrs.insert(k, v.0);
+    }
+    Ok(attrs)
+  }
+}
+
+/// One attribute value: a string, or a number or boolean taken as its text.
+struct AttributeValue(String);
+
+impl<'de> Deserialize<'de> for AttributeValue {
+  fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+    deserializer.deserialize_any(AttributeValueVisitor)
+  }
+}
+
+struct AttributeValueVisitor;
+
+impl Visitor<'_> for AttributeValueVisitor {
+  type Value = AttributeValue;
+
+  fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    f.write_str("a string, number or boolean")
+  }
+
+  fn visit_str<E: de::Error>(self, v: &str) -> Result<AttributeValue, E> {
+    Ok(AttributeValue(v.to_string()))
+  }
+
+  fn visit_string<E: de::Error>(self, v: String) -> Result<AttributeValue, E> {
+    Ok(AttributeValue(v))
+  }
+
+  fn visit_bool<E: de::Error>(self, v: bool) -> Result<AttributeValue, E> {
+    Ok(AttributeValue(v.to_string()))
+  }
+
+  fn visit_i64<E: de::Error>(self, v: i64) -> Result<AttributeValue, E> {
+    Ok(AttributeValue(v.to_string()))
+  }
+
+  fn visit_u64<E: de::Error>(self, v: u64) -> Result<AttributeValue, E> {
+    Ok(AttributeValue(v.to_string()))
+  }
+
+  fn visit_f64<E: de::Error>(self, v: f64) -> Result<AttributeValue, E> {
+    Ok(AttributeValue(v.to_string()))
+  }
+}
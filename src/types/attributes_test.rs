@@ -0,0 +1,61 @@
+//! Tests for `Attributes`.
+
+use std::time::Duration;
+
+use super::Attributes;
+
+fn attrs(pairs: &[(&str, &str)]) -> Attributes {
+  pairs.iter().copied().collect()
+}
+
+#[test]
+fn keeps_insertion_order_and_replaces_in_place() {
+  let mut a = attrs(&[("shape", "box"), ("timeout", "30s")]);
+  assert_eq!(a.insert("fidelity", "full"), None);
+  assert_eq!(a.insert("shape", "diamond").as_deref(), Some("box"));
+  let keys: Vec<&str> = a.iter().map(|(k, _)| k).collect();
+  assert_eq!(keys, ["shape", "timeout", "fidelity"]);
+  assert_eq!(a.get("shape"), Some("diamond"));
+  assert_eq!(a.len(), 3);
+  assert_eq!(a.remove("timeout").as_deref(), Some("30s"));
+  assert!(!a.contains_key("timeout"));
+  assert!(Attributes::new().is_empty());
+}
+
+#[test]
+fn get_bool_accepts_common_spellings() {
+  let a = attrs(&[("a", "true"), ("b", "No"), ("c", "1"), ("d", "maybe")]);
+  assert_eq!(a.get_bool("a"), Ok(Some(true)));
+  assert_eq!(a.get_bool("b"), Ok(Some(false)));
+  assert_eq!(a.get_bool("c"), Ok(Some(true)));
+  assert_eq!(a.get_bool("missing"), Ok(None));
+  let err = a.get_bool("d").unwrap_err();
+  assert!(err.contains("d = \"maybe\""), "{}", err);
+}
+
+#[test]
+fn get_int_and_duration() {
+  let a = attrs(&[
+    ("max", "-3"),
+    ("bad", "3x"),
+    ("timeout", "15m"),
+    ("ms", "250"),
+    ("day", "1d"),
+  ]);
+  assert_eq!(a.get_int("max"), Ok(Some(-3)));
+  assert!(a.get_int("bad").is_err());
+  assert_eq!(
+    a.get_duration("timeout"),
+    Ok(Some(Duration::from_secs(900)))
+  );
+  assert_eq!(a.get_duration("ms"), Ok(Some(Duration::from_millis(250))));
+  assert_eq!(a.get_duration("day"), Ok(Some(Duration::from_secs(86_400))));
+  assert!(a.get_duration("bad").is_err());
+}
+
+#[test]
+fn get_list_splits_on_commas() {
+  let a = attrs(&[("class", " fast, ,review ")]);
+  assert_eq!(a.get_list("class").unwrap(), ["fast", "review"]);
+  assert_eq!(a.get_list("missing"), None);
+}
@@ -0,0 +1,78 @@
+//! A finding from graph validation (attractor-spec §7).
+
+use serde::Serialize;
+use std::fmt;
+
+/// How serious a [Diagnostic] is. Only errors stop a pipeline from running.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Severity {
+  Error,
+  Warning,
+  Info,
+}
+
+impl fmt::Display for Severity {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    f.write_str(match self {
+      Severity::Error => "error",
+      Severity::Warning => "warning",
+      Severity::Info => "info",
+    })
+  }
+}
+
+/// One validation finding: the rule that produced it, what is wrong, where, and how to fix it.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Diagnostic {
+  /// Rule id, e.g. `start_node` or `reachability`.
+  pub rule: String,
+  pub severity: Severity,
+  pub message: String,
+  /// Node the finding is about, if any.
+  pub node_id: Option<String>,
+  /// Edge the finding is about, as `(from, to)`, if any.
+  pub edge: Option<(String, String)>,
+  /// Suggested fix, if there is an obvious one.
+  pub fix: Option<String>,
+}
+
+impl Diagnostic {
+  /// A diagnostic for the graph as a whole.
+  pub fn new(rule: impl Into<String>, severity: Severity, message: impl Into<String>) -> Self {
+    Self {
+      rule: rule.into(),
+      severity,
+      message: message.into(),
+      node_id: None,
+      edge: None,
+      fix: None,
+    }
+  }
+
+  pub fn with_node(mut self, node_id: impl Into<String>) -> Self {
+    self.node_id = Some(node_id.into());
+    self
+  }
+
+  pub fn with_edge(mut self, from: impl Into<String>, to: impl Into<String>) -> Self {
+    self.edge = Some((from.into(), to.into()));
+    self
+  }
+
+  pub fn with_fix(mut self, fix: impl Into<String>) -> Self {
+    self.fix = Some(fix.into());
+    self
+  }
+
+  pub fn is_error(&self) -> bool {
+    self.severity == Severity::Error
+  }
+}
+
+impl fmt::Display for Diagnostic {
+  /// `severity[rule]: message`.
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    write!(f, "{}[{}]: {}", self.severity, self.rule, self.message)
+  }
+}
@@ -0,0 +1,37 @@
+//! Tests for `Diagnostic`.
+
+use super::{Diagnostic, Severity};
+
+#[test]
+fn display_and_json_name_rule_and_severity() {
+  let d = Diagnostic::new(
+    "reachability",
+    Severity::Warning,
+    "Node 'x' is not reachable",
+  )
+  .with_node("x")
+  .with_fix("Add an edge into 'x'");
+  assert_eq!(
+    d.to_string(),
+    "warning[reachability]: Node 'x' is not reachable"
+  );
+  assert!(!d.is_error());
+  assert_eq!(
+    serde_json::to_value(&d).unwrap(),
+    serde_json::json!({
+      "rule": "reachability",
+      "severity": "warning",
+      "message": "Node 'x' is not reachable",
+      "node_id": "x",
+      "edge": null,
+      "fix": "Add an edge into 'x'"
+    })
+  );
+}
+
+#[test]
+fn severities_sort_errors_first() {
+  let mut s = vec![Severity::Info, Severity::Error, Severity::Warning];
+  s.sort();
+  assert_eq!(s, [Severity::Error, Severity::Warning, Severity::Info]);
+}
@@ -14,6 +14,9 @@ pub struct ExecutionStepEntry {
   pub step: u32,
   /// Node that was executed.
   pub node_id: String,
+  /// 1-based attempt of this node execution; above 1 when the node was retried.
+  #[serde(default = "first_attempt")]
+  pub attempt: u32,
   /// Handler type (e.g. "start", "exit", "codergen", "exec").
   pub handler_type: Option<String>,
   /// Context before executing the node.
@@ -26,6 +29,10 @@ pub struct ExecutionStepEntry {
   pub next_node_id: Option<String>,
   /// completed_nodes list after this step.
   pub completed_nodes_after: Vec<String>,
+  /// Parallel branch the step ran in, named by the branch's first node; None outside branches.
+  /// Branch steps have the branch's own context and completed nodes, so resume skips them.
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub branch: Option<String>,
 }
 
 impl ExecutionStepEntry {
@@ -44,14 +51,32 @@ impl ExecutionStepEntry {
     Self {
       step,
       node_id: node_id.into(),
+      attempt: 1,
       handler_type,
       context_before,
       outcome,
       context_after,
       next_node_id,
       completed_nodes_after,
+      branch: None,
     }
   }
+
+  /// Sets the attempt number (default 1).
+  pub fn with_attempt(mut self, attempt: u32) -> Self {
+    self.attempt = attempt;
+    self
+  }
+
+  /// Marks the step as run in parallel branch `branch`.
+  pub fn with_branch(mut self, branch: Option<String>) -> Self {
+    self.branch = branch;
+    self
+  }
+}
+
+fn first_attempt() -> u32 {
+  1
 }
 
 /// Root structure for execution.log.json.
@@ -100,6 +125,7 @@ mod tests {
     let parsed: serde_json::Value = serde_json::from_str(&json).unwrap();
     assert_eq!(parsed["step"], 1);
     assert_eq!(parsed["node_id"], "start");
+    assert_eq!(parsed["attempt"], 1);
     assert_eq!(parsed["handler_type"], "start");
     assert_eq!(parsed["next_node_id"], "next");
     assert_eq!(
@@ -143,4 +169,24 @@ mod tests {
     assert_eq!(parsed["completed_nodes"], serde_json::json!(["n1"]));
     assert_eq!(parsed["steps"].as_array().unwrap().len(), 1);
   }
+
+  #[test]
+  fn execution_step_entry_without_attempt_deserializes_as_first() {
+    let entry = ExecutionStepEntry::new(
+      3,
+      "n1",
+      None,
+      HashMap::new(),
+      NodeOutcome::success("ok"),
+      HashMap::new(),
+      None,
+      vec![],
+    )
+    .with_attempt(2);
+    let mut json = serde_json::to_value(&entry).unwrap();
+    assert_eq!(json["attempt"], 2);
+    json.as_object_mut().unwrap().remove("attempt");
+    let parsed: ExecutionStepEntry = serde_json::from_value(json).unwrap();
+    assert_eq!(parsed.attempt, 1);
+  }
 }
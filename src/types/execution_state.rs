@@ -1,9 +1,11 @@
 //! Execution state for one step of the Attractor loop.
 
 use std::collections::HashMap;
+use std::path::PathBuf;
 use tracing::instrument;
 
 use super::{AttractorGraph, ExecutionStepEntry, NodeOutcome, RunContext};
+use crate::handler::HandlerRegistry;
 
 /// Execution state for one step of the Attractor loop.
 #[derive(Debug, Clone)]
@@ -15,6 +17,12 @@ pub struct ExecutionState {
   pub node_outcomes: HashMap<String, NodeOutcome>,
   /// Optional step log sink; when `Some`, each node execution + select_edge pushes one entry.
   pub step_log: Option<Vec<ExecutionStepEntry>>,
+  /// Command for codergen nodes (same as the compiled graph's codergen handler).
+  pub agent_cmd: Option<String>,
+  /// Directory for agent outcome.json and staging.
+  pub stage_dir: Option<PathBuf>,
+  /// Handlers by handler type.
+  pub handlers: HandlerRegistry,
 }
 
 impl ExecutionState {
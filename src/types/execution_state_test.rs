@@ -13,7 +13,12 @@ fn node(id: &str, shape: &str) -> AttractorNode {
     prompt: None,
     command: None,
     goal_gate: false,
-    max_retries: 0,
+    max_retries: None,
+    retry_policy: None,
+    retry_target: None,
+    fallback_retry_target: None,
+    classes: Vec::new(),
+    attrs: Default::default(),
   }
 }
 
@@ -25,6 +30,11 @@ fn graph_with_nodes(nodes: Vec<AttractorNode>) -> AttractorGraph {
     nodes: nodes_map,
     edges: vec![],
     default_max_retry: 50,
+    default_retry_policy: Default::default(),
+    retry_target: None,
+    fallback_retry_target: None,
+    name: None,
+    attrs: Default::default(),
   }
 }
 
@@ -38,6 +48,9 @@ fn is_done_when_at_exit() {
     completed_nodes: vec![],
     node_outcomes: HashMap::new(),
     step_log: None,
+    agent_cmd: None,
+    stage_dir: None,
+    handlers: Default::default(),
   };
   assert!(state.is_done());
 }
@@ -56,6 +69,9 @@ fn is_done_false_when_at_non_exit() {
     completed_nodes: vec![],
     node_outcomes: HashMap::new(),
     step_log: None,
+    agent_cmd: None,
+    stage_dir: None,
+    handlers: Default::default(),
   };
   assert!(!state.is_done());
 }
@@ -70,6 +86,9 @@ fn is_done_false_when_unknown_node() {
     completed_nodes: vec![],
     node_outcomes: HashMap::new(),
     step_log: None,
+    agent_cmd: None,
+    stage_dir: None,
+    handlers: Default::default(),
   };
   assert!(!state.is_done());
 }
@@ -3,6 +3,8 @@
 //! and context_updates from outcomes are applied along the path.
 //! Tracks current_node_id and completed_nodes for resume (execution log).
 
+use std::collections::HashMap;
+
 use super::{NodeOutcome, RunContext};
 use tracing::instrument;
 
@@ -16,6 +18,8 @@ pub struct GraphPayload {
   pub current_node_id: String,
   /// Ordered list of node ids that have completed (for resume from execution log).
   pub completed_nodes: Vec<String>,
+  /// Latest outcome of each executed node, checked against goal gates at exit (spec §3.4).
+  pub node_outcomes: HashMap<String, NodeOutcome>,
 }
 
 impl GraphPayload {
@@ -34,9 +38,16 @@ impl GraphPayload {
       outcome,
       current_node_id,
       completed_nodes,
+      node_outcomes: HashMap::new(),
     }
   }
 
+  /// Sets the per-node outcomes carried to the goal gate check.
+  pub fn with_node_outcomes(mut self, node_outcomes: HashMap<String, NodeOutcome>) -> Self {
+    self.node_outcomes = node_outcomes;
+    self
+  }
+
   /// Initial payload for graph entry (e.g. from start node).
   #[instrument(level = "trace", skip(context, start_node_id))]
   pub fn initial(context: RunContext, start_node_id: impl Into<String>) -> Self {
@@ -46,6 +57,7 @@ impl GraphPayload {
       outcome: None,
       current_node_id: start.clone(),
       completed_nodes: vec![],
+      node_outcomes: HashMap::new(),
     }
   }
 
@@ -56,6 +68,7 @@ impl GraphPayload {
       outcome: None,
       current_node_id: st.current_node_id.clone(),
       completed_nodes: st.completed_nodes.clone(),
+      node_outcomes: st.node_outcomes.clone(),
     }
   }
 
@@ -70,6 +83,7 @@ impl GraphPayload {
       outcome: self.outcome.clone(),
       current_node_id: node_id,
       completed_nodes: completed,
+      node_outcomes: self.node_outcomes.clone(),
     }
   }
 }
@@ -23,6 +23,7 @@ fn from_resume_state_restores_context_and_completed() {
     context: ctx,
     current_node_id: "run".to_string(),
     completed_nodes: vec!["start".to_string(), "run".to_string()],
+    node_outcomes: HashMap::new(),
   };
   let p = GraphPayload::from_resume_state(&st);
   assert_eq!(p.context.get("k").map(String::as_str), Some("v"));
@@ -13,6 +13,12 @@ mod attractor_graph_test;
 mod attractor_node;
 #[cfg(test)]
 mod attractor_node_test;
+mod attributes;
+#[cfg(test)]
+mod attributes_test;
+mod diagnostic;
+#[cfg(test)]
+mod diagnostic_test;
 mod execution_log;
 mod execution_state;
 #[cfg(test)]
@@ -29,16 +35,22 @@ mod outcome_status_test;
 mod resume_state;
 #[cfg(test)]
 mod resume_state_test;
+mod retry_policy;
+#[cfg(test)]
+mod retry_policy_test;
 
 pub use attractor_edge::AttractorEdge;
 pub use attractor_graph::AttractorGraph;
 pub use attractor_node::AttractorNode;
+pub use attributes::Attributes;
+pub use diagnostic::{Diagnostic, Severity};
 pub use execution_log::{ExecutionLog, ExecutionStepEntry};
 pub use execution_state::ExecutionState;
 pub use graph_payload::GraphPayload;
 pub use node_outcome::NodeOutcome;
 pub use outcome_status::OutcomeStatus;
 pub use resume_state::ResumeState;
+pub use retry_policy::{Backoff, RetryPolicy};
 
 /// Key-value context shared across the pipeline run.
 pub type RunContext = HashMap<String, String>;
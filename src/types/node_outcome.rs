@@ -42,4 +42,14 @@ impl NodeOutcome {
       suggested_next_ids: vec![],
     }
   }
+
+  /// Asks for the node to run again (e.g. a transient failure); escalates to an error once
+  /// the node's retry budget is spent.
+  #[instrument(level = "trace", skip(reason))]
+  pub fn retry(reason: impl Into<String>) -> Self {
+    Self {
+      status: OutcomeStatus::Retry,
+      ..Self::error(reason)
+    }
+  }
 }
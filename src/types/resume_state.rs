@@ -1,6 +1,8 @@
 //! Resume state for --resume from execution log (execution.log.json only; no checkpoint.json).
 
-use super::RunContext;
+use std::collections::HashMap;
+
+use super::{NodeOutcome, RunContext};
 use serde::{Deserialize, Serialize};
 
 /// Resume state (context, current node, completed nodes). Used by --resume from execution log only.
@@ -9,4 +11,7 @@ pub struct ResumeState {
   pub context: RunContext,
   pub current_node_id: String,
   pub completed_nodes: Vec<String>,
+  /// Latest outcome of each executed node, so goal gates still apply after resume.
+  #[serde(default)]
+  pub node_outcomes: HashMap<String, NodeOutcome>,
 }
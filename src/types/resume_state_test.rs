@@ -12,6 +12,7 @@ fn resume_state_roundtrip_serde() {
     context: ctx,
     current_node_id: "run".to_string(),
     completed_nodes: vec!["start".to_string(), "run".to_string()],
+    node_outcomes: HashMap::new(),
   };
   let json = serde_json::to_string(&st).unwrap();
   let st2: ResumeState = serde_json::from_str(&json).unwrap();
@@ -28,6 +29,7 @@ fn construct_resume_state() {
     context: ctx.clone(),
     current_node_id: "node1".to_string(),
     completed_nodes: vec!["start".to_string(), "node1".to_string()],
+    node_outcomes: HashMap::new(),
   };
   assert_eq!(st.context.get("key").map(String::as_str), Some("val"));
   assert_eq!(st.current_node_id, "node1");
@@ -42,6 +44,7 @@ fn clone_resume_state() {
     context: ctx,
     current_node_id: "here".to_string(),
     completed_nodes: vec!["a".to_string()],
+    node_outcomes: HashMap::new(),
   };
   let c = st.clone();
   assert_eq!(c.current_node_id, st.current_node_id);
@@ -0,0 +1,173 @@
+//! Backoff between retry attempts, from the `retry_policy` node or graph attribute.
+//!
+//! Syntax: a backoff kind followed by optional `key=value` settings, separated by spaces or
+//! commas, e.g. `retry_policy="exponential delay=500ms max_delay=30s jitter=true retry_on=retry|error"`.
+//!
+//! | Setting     | Meaning                                                          | Default    |
+//! |-------------|------------------------------------------------------------------|------------|
+//! | kind        | `none`, `fixed`, `linear` or `exponential`                       | `none`     |
+//! | `delay`     | Wait before the first retry (`ms`, `s`, `m`, `h` or `d`; bare numbers are ms) | `200ms` |
+//! | `factor`    | Multiplier per attempt for `exponential`                         | `2`        |
+//! | `max_delay` | Upper bound for any single wait                                  | `60s`      |
+//! | `jitter`    | Scale each wait by a random factor in [0.5, 1.5)                 | `false`    |
+//! | `retry_on`  | `\|`-separated outcomes that are retried: `retry`, `error`/`fail`, `partial_success` (`retry` always is) | `retry` |
+
+use std::str::FromStr;
+use std::time::Duration;
+
+use super::OutcomeStatus;
+
+/// How the wait grows between attempts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Backoff {
+  /// Retry immediately.
+  None,
+  /// Wait `delay` before every retry.
+  Fixed,
+  /// Wait `delay * n` before retry `n`.
+  Linear,
+  /// Wait `delay * factor^(n-1)` before retry `n`.
+  Exponential,
+}
+
+/// Backoff and retryable outcomes for a node; the attempt budget is `max_retries`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RetryPolicy {
+  pub backoff: Backoff,
+  /// Base wait (see [Backoff]).
+  pub delay: Duration,
+  /// Growth factor for [Backoff::Exponential].
+  pub factor: f64,
+  /// Cap applied to every wait, before and after jitter.
+  pub max_delay: Duration,
+  /// When true, each wait is scaled by a random factor in [0.5, 1.5).
+  pub jitter: bool,
+  /// Outcome statuses that trigger a retry.
+  pub retry_on: Vec<OutcomeStatus>,
+}
+
+impl Default for RetryPolicy {
+  fn default() -> Self {
+    Self {
+      backoff: Backoff::None,
+      delay: Duration::from_millis(200),
+      factor: 2.0,
+      max_delay: Duration::from_secs(60),
+      jitter: false,
+      retry_on: vec![OutcomeStatus::Retry],
+    }
+  }
+}
+
+impl RetryPolicy {
+  /// True when an outcome with `status` should be retried.
+  pub fn retries(&self, status: OutcomeStatus) -> bool {
+    self.retry_on.contains(&status)
+  }
+
+  /// Wait before retry number `retry` (1 = the wait after the first attempt), without jitter.
+  pub fn base_delay(&self, retry: u32) -> Duration {
+    let n = retry.max(1);
+    let secs = self.delay.as_secs_f64();
+    let wait = match self.backoff {
+      Backoff::None => 0.0,
+      Backoff::Fixed => secs,
+      Backoff::Linear => secs * f64::from(n),
+      Backoff::Exponential => secs * self.factor.powi(n.saturating_sub(1) as i32),
+    };
+    Duration::try_from_secs_f64(wait)
+      .unwrap_or(self.max_delay)
+      .min(self.max_delay)
+  }
+
+  /// Wait before retry number `retry`, with jitter applied when enabled.
+  pub fn delay_for(&self, retry: u32) -> Duration {
+    let base = self.base_delay(retry);
+    if !self.jitter || base.is_zero() {
+      return base;
+    }
+    base
+      .mul_f64(rand::random_range(0.5..1.5))
+      .min(self.max_delay)
+  }
+}
+
+/// Parses `250ms`, `2s`, `1m`, `2h`, `1d` or a bare number of milliseconds.
+pub(crate) fn parse_duration(s: &str) -> Result<Duration, String> {
+  let (num, unit) = match s.find(|c: char| !(c.is_ascii_digit() || c == '.')) {
+    Some(i) => s.split_at(i),
+    None => (s, "ms"),
+  };
+  let n: f64 = num
+    .parse()
+    .map_err(|_| format!("invalid duration {:?}", s))?;
+  let secs = match unit {
+    "ms" => n / 1000.0,
+    "s" => n,
+    "m" => n * 60.0,
+    "h" => n * 3600.0,
+    "d" => n * 86400.0,
+    _ => return Err(format!("invalid duration unit in {:?}", s)),
+  };
+  Duration::try_from_secs_f64(secs).map_err(|_| format!("invalid duration {:?}", s))
+}
+
+fn parse_status(s: &str) -> Result<OutcomeStatus, String> {
+  match s.to_ascii_lowercase().as_str() {
+    "retry" => Ok(OutcomeStatus::Retry),
+    "error" | "fail" => Ok(OutcomeStatus::Error),
+    "partial_success" => Ok(OutcomeStatus::PartialSuccess),
+    other => Err(format!("unknown retry_on outcome {:?}", other)),
+  }
+}
+
+impl FromStr for RetryPolicy {
+  type Err = String;
+
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    let mut policy = RetryPolicy::default();
+    for (i, part) in s
+      .split(|c: char| c == ',' || c.is_whitespace())
+      .filter(|p| !p.is_empty())
+      .enumerate()
+    {
+      let Some((key, value)) = part.split_once('=') else {
+        if i > 0 {
+          return Err(format!("expected key=value, got {:?}", part));
+        }
+        policy.backoff = match part.to_ascii_lowercase().as_str() {
+          "none" => Backoff::None,
+          "fixed" => Backoff::Fixed,
+          "linear" => Backoff::Linear,
+          "exponential" => Backoff::Exponential,
+          other => return Err(format!("unknown backoff {:?}", other)),
+        };
+        continue;
+      };
+      match key {
+        "delay" => policy.delay = parse_duration(value)?,
+        "max_delay" => policy.max_delay = parse_duration(value)?,
+        "factor" => {
+          policy.factor = value
+            .parse()
+            .ok()
+            .filter(|f: &f64| f.is_finite() && *f >= 1.0)
+            .ok_or_else(|| format!("factor must be a number >= 1, got {:?}", value))?
+        }
+        "jitter" => {
+          policy.jitter = value
+            .parse()
+            .map_err(|_| format!("jitter must be true or false, got {:?}", value))?
+        }
+        "retry_on" => {
+          policy.retry_on = value
+            .split('|')
+            .map(parse_status)
+            .collect::<Result<_, _>>()?
+        }
+        other => return Err(format!("unknown retry_policy setting {:?}", other)),
+      }
+    }
+    Ok(policy)
+  }
+}
@@ -0,0 +1,78 @@
+//! Tests for `RetryPolicy`.
+
+use std::time::Duration;
+
+use super::{Backoff, OutcomeStatus, RetryPolicy};
+
+fn policy(s: &str) -> RetryPolicy {
+  s.parse().unwrap_or_else(|e| panic!("parse {:?}: {}", s, e))
+}
+
+#[test]
+fn default_retries_immediately_on_retry_only() {
+  let p = RetryPolicy::default();
+  assert_eq!(p.backoff, Backoff::None);
+  assert_eq!(p.delay_for(3), Duration::ZERO);
+  assert!(p.retries(OutcomeStatus::Retry));
+  assert!(!p.retries(OutcomeStatus::Error));
+}
+
+#[test]
+fn fixed_linear_and_exponential_delays() {
+  let fixed = policy("fixed delay=500ms");
+  assert_eq!(fixed.delay_for(1), Duration::from_millis(500));
+  assert_eq!(fixed.delay_for(4), Duration::from_millis(500));
+
+  let linear = policy("linear delay=1s");
+  assert_eq!(linear.delay_for(1), Duration::from_secs(1));
+  assert_eq!(linear.delay_for(3), Duration::from_secs(3));
+
+  let exp = policy("exponential, delay=100, factor=3");
+  assert_eq!(exp.delay_for(1), Duration::from_millis(100));
+  assert_eq!(exp.delay_for(3), Duration::from_millis(900));
+}
+
+#[test]
+fn max_delay_caps_growth() {
+  let p = policy("exponential delay=1s max_delay=5s");
+  assert_eq!(p.delay_for(3), Duration::from_secs(4));
+  assert_eq!(p.delay_for(4), Duration::from_secs(5));
+  assert_eq!(p.delay_for(200), Duration::from_secs(5));
+}
+
+#[test]
+fn jitter_stays_within_bounds() {
+  let p = policy("fixed delay=1s jitter=true max_delay=1200ms");
+  for _ in 0..100 {
+    let d = p.delay_for(1);
+    assert!(d >= Duration::from_millis(500), "{:?}", d);
+    assert!(d <= Duration::from_millis(1200), "{:?}", d);
+  }
+}
+
+#[test]
+fn retry_on_lists_outcomes() {
+  let p = policy("fixed retry_on=retry|fail");
+  assert_eq!(p.retry_on, [OutcomeStatus::Retry, OutcomeStatus::Error]);
+  assert!(p.retries(OutcomeStatus::Error));
+}
+
+#[test]
+fn invalid_policies_are_rejected() {
+  for bad in [
+    "sometimes",
+    "fixed delay=soon",
+    "fixed delay=5w",
+    "fixed 2s",
+    "exponential factor=0.5",
+    "fixed jitter=maybe",
+    "fixed retry_on=success",
+    "fixed tries=3",
+  ] {
+    assert!(
+      bad.parse::<RetryPolicy>().is_err(),
+      "{:?} should not parse",
+      bad
+    );
+  }
+}
@@ -136,6 +136,8 @@ async fn integration_lib_minimal_succeeds() {
       agent_cmd: None,
       stage_dir: None,
       execution_log_path: None,
+      handlers: Default::default(),
+      lints: Default::default(),
     },
   )
   .await
@@ -161,6 +163,8 @@ async fn integration_lib_test_success_only_succeeds() {
       agent_cmd: None,
       stage_dir: None,
       execution_log_path: None,
+      handlers: Default::default(),
+      lints: Default::default(),
     },
   )
   .await
@@ -182,6 +186,8 @@ async fn integration_lib_test_out_error_succeeds() {
       agent_cmd: None,
       stage_dir: None,
       execution_log_path: None,
+      handlers: Default::default(),
+      lints: Default::default(),
     },
   )
   .await
@@ -208,6 +214,8 @@ async fn integration_lib_pre_push_exec_only_succeeds() {
         agent_cmd: None,
         stage_dir: None,
         execution_log_path: Some(log_path),
+        handlers: Default::default(),
+        lints: Default::default(),
       },
     ),
   )
@@ -232,6 +240,8 @@ async fn integration_lib_exec_fail_exit_returns_failure() {
       agent_cmd: None,
       stage_dir: None,
       execution_log_path: None,
+      handlers: Default::default(),
+      lints: Default::default(),
     },
   )
   .await
@@ -239,6 +249,700 @@ async fn integration_lib_exec_fail_exit_returns_failure() {
   assert!(format!("{:?}", r.last_outcome.status) != "Success");
 }
 
+#[tokio::test]
+async fn integration_lib_exec_fail_exit_with_execution_log_returns_failure() {
+  // Enabling the execution log must not change the outcome: exec really runs and fails.
+  let dir = tempfile::tempdir().expect("tempdir");
+  let log_path = dir.path().join("execution.log.json");
+  let dot = std::fs::read_to_string(dot_path("exec_fail_exit.dot")).expect("read");
+  let ast = streamweave_attractor::dot_parser::parse_dot(&dot).expect("parse");
+  let r = streamweave_attractor::run_compiled_graph(
+    &ast,
+    streamweave_attractor::RunOptions {
+      run_dir: None,
+      resume_state: None,
+      resume_already_completed: false,
+      agent_cmd: None,
+      stage_dir: None,
+      execution_log_path: Some(log_path.clone()),
+      handlers: Default::default(),
+      lints: Default::default(),
+    },
+  )
+  .await
+  .expect("run_compiled_graph");
+  assert!(format!("{:?}", r.last_outcome.status) != "Success");
+  assert!(r.completed_nodes.contains(&"fail_step".to_string()));
+
+  // The compiled graph records the failing exec step with its routed next node.
+  let log = streamweave_attractor::execution_log_io::load_execution_log(&log_path).expect("log");
+  assert!(log.finished_at.is_some());
+  let step = log
+    .steps
+    .iter()
+    .find(|s| s.node_id == "fail_step")
+    .expect("fail_step recorded");
+  assert_eq!(step.handler_type.as_deref(), Some("exec"));
+  assert_eq!(format!("{:?}", step.outcome.status), "Error");
+  assert_eq!(step.next_node_id.as_deref(), Some("exit"));
+}
+
+#[tokio::test]
+async fn integration_lib_fix_loop_survives_repeated_failures() {
+  // `check` fails twice before passing; each failure goes through `fix` and back. The node must
+  // keep accepting input after failing, and the run must shut down cleanly once exit is reached.
+  let dir = tempfile::tempdir().expect("tempdir");
+  let counter = dir.path().join("count");
+  let dot = format!(
+    r#"
+    digraph G {{
+      graph [goal="fix loop"]
+      start [shape=Mdiamond]
+      exit [shape=Msquare]
+      check [type=exec, command="n=$(cat {c} 2>/dev/null || echo 0); n=$((n+1)); echo $n > {c}; [ $n -ge 3 ]"]
+      fix [type=exec, command="true"]
+      start -> check
+      check -> exit [condition="outcome=success"]
+      check -> fix [condition="outcome=fail"]
+      fix -> check
+    }}
+  "#,
+    c = counter.display()
+  );
+  let ast = streamweave_attractor::dot_parser::parse_dot(&dot).expect("parse");
+  let r = streamweave_attractor::run_compiled_graph(
+    &ast,
+    streamweave_attractor::RunOptions {
+      run_dir: None,
+      resume_state: None,
+      resume_already_completed: false,
+      agent_cmd: None,
+      stage_dir: None,
+      execution_log_path: None,
+      handlers: Default::default(),
+      lints: Default::default(),
+    },
+  )
+  .await
+  .expect("run_compiled_graph");
+  assert!(format!("{:?}", r.last_outcome.status) == "Success");
+  let checks = r.completed_nodes.iter().filter(|n| *n == "check").count();
+  assert_eq!(checks, 3, "completed: {:?}", r.completed_nodes);
+  assert_eq!(r.completed_nodes.last().map(String::as_str), Some("exit"));
+}
+
+#[tokio::test]
+async fn integration_lib_exec_retry_reruns_node_and_numbers_attempts() {
+  // `flaky` exits 75 (retry) twice, then succeeds; max_retries=2 lets the third attempt run.
+  let dir = tempfile::tempdir().expect("tempdir");
+  let counter = dir.path().join("count");
+  let log_path = dir.path().join("execution.log.json");
+  let dot = format!(
+    r#"
+    digraph G {{
+      graph [goal="retry"]
+      start [shape=Mdiamond]
+      exit [shape=Msquare]
+      flaky [type=exec, max_retries=2, command="n=$(cat {c} 2>/dev/null || echo 0); n=$((n+1)); echo $n > {c}; [ $n -ge 3 ] || exit 75"]
+      start -> flaky
+      flaky -> exit
+    }}
+  "#,
+    c = counter.display()
+  );
+  let ast = streamweave_attractor::dot_parser::parse_dot(&dot).expect("parse");
+  let r = streamweave_attractor::run_compiled_graph(
+    &ast,
+    streamweave_attractor::RunOptions {
+      run_dir: None,
+      resume_state: None,
+      resume_already_completed: false,
+      agent_cmd: None,
+      stage_dir: None,
+      execution_log_path: Some(log_path.clone()),
+      handlers: Default::default(),
+      lints: Default::default(),
+    },
+  )
+  .await
+  .expect("run_compiled_graph");
+  assert!(format!("{:?}", r.last_outcome.status) == "Success");
+  assert_eq!(r.completed_nodes, ["start", "flaky", "exit"]);
+
+  let log = streamweave_attractor::execution_log_io::load_execution_log(&log_path).expect("log");
+  let attempts: Vec<(u32, String)> = log
+    .steps
+    .iter()
+    .filter(|s| s.node_id == "flaky")
+    .map(|s| (s.attempt, format!("{:?}", s.outcome.status)))
+    .collect();
+  assert_eq!(
+    attempts,
+    [
+      (1, "Retry".to_string()),
+      (2, "Retry".to_string()),
+      (3, "Success".to_string())
+    ]
+  );
+}
+
+#[tokio::test]
+async fn integration_lib_exhausted_retries_take_fail_edge() {
+  // Once the retry budget is spent the node fails and its fail edge is taken.
+  let dot = r#"
+    digraph G {
+      graph [goal="retry budget"]
+      start [shape=Mdiamond]
+      exit [shape=Msquare]
+      flaky [type=exec, max_retries=1, command="exit 75"]
+      report [type=exec, command="true"]
+      start -> flaky
+      flaky -> exit [condition="outcome=success"]
+      flaky -> report [condition="outcome=fail"]
+      report -> exit
+    }
+  "#;
+  let ast = streamweave_attractor::dot_parser::parse_dot(dot).expect("parse");
+  let r = streamweave_attractor::run_compiled_graph(
+    &ast,
+    streamweave_attractor::RunOptions {
+      run_dir: None,
+      resume_state: None,
+      resume_already_completed: false,
+      agent_cmd: None,
+      stage_dir: None,
+      execution_log_path: None,
+      handlers: Default::default(),
+      lints: Default::default(),
+    },
+  )
+  .await
+  .expect("run_compiled_graph");
+  assert_eq!(r.completed_nodes, ["start", "flaky", "report", "exit"]);
+}
+
+#[tokio::test]
+async fn integration_lib_goal_gate_retries_from_retry_target() {
+  // `tests` is a goal gate that fails the first time; its unconditional edge still reaches exit,
+  // where the unsatisfied gate sends the run back to `implement` instead of finishing.
+  let dir = tempfile::tempdir().expect("tempdir");
+  let counter = dir.path().join("count");
+  let dot = format!(
+    r#"
+    digraph G {{
+      graph [goal="goal gate"]
+      start [shape=Mdiamond]
+      exit [shape=Msquare]
+      implement [type=exec, command="true"]
+      tests [type=exec, goal_gate=true, retry_target=implement, command="n=$(cat {c} 2>/dev/null || echo 0); n=$((n+1)); echo $n > {c}; [ $n -ge 2 ]"]
+      start -> implement
+      implement -> tests
+      tests -> exit
+    }}
+  "#,
+    c = counter.display()
+  );
+  let ast = streamweave_attractor::dot_parser::parse_dot(&dot).expect("parse");
+  let r = streamweave_attractor::run_compiled_graph(
+    &ast,
+    streamweave_attractor::RunOptions {
+      run_dir: None,
+      resume_state: None,
+      resume_already_completed: false,
+      agent_cmd: None,
+      stage_dir: None,
+      execution_log_path: None,
+      handlers: Default::default(),
+      lints: Default::default(),
+    },
+  )
+  .await
+  .expect("run_compiled_graph");
+  assert!(format!("{:?}", r.last_outcome.status) == "Success");
+  assert_eq!(
+    r.completed_nodes,
+    ["start", "implement", "tests", "implement", "tests", "exit"]
+  );
+}
+
+#[tokio::test]
+async fn integration_lib_goal_gate_without_retry_target_fails_run() {
+  let dot = r#"
+    digraph G {
+      graph [goal="goal gate"]
+      start [shape=Mdiamond]
+      exit [shape=Msquare]
+      tests [type=exec, goal_gate=true, command="false"]
+      start -> tests
+      tests -> exit
+    }
+  "#;
+  let ast = streamweave_attractor::dot_parser::parse_dot(dot).expect("parse");
+  let r = streamweave_attractor::run_compiled_graph(
+    &ast,
+    streamweave_attractor::RunOptions {
+      run_dir: None,
+      resume_state: None,
+      resume_already_completed: false,
+      agent_cmd: None,
+      stage_dir: None,
+      execution_log_path: None,
+      handlers: Default::default(),
+      lints: Default::default(),
+    },
+  )
+  .await
+  .expect("run_compiled_graph");
+  assert!(format!("{:?}", r.last_outcome.status) == "Error");
+  assert!(
+    r.last_outcome
+      .failure_reason
+      .as_deref()
+      .unwrap_or_default()
+      .contains("goal gate 'tests'")
+  );
+  assert!(!r.completed_nodes.contains(&"exit".to_string()));
+}
+
+#[tokio::test]
+async fn integration_lib_unrouted_failure_ends_run() {
+  // A failing node with no outgoing edge ends the run with that failure instead of hanging.
+  // Exit is reachable (validation requires it) only by an edge that is not taken.
+  let dot = r#"
+    digraph G {
+      graph [goal="dead end"]
+      start [shape=Mdiamond]
+      exit [shape=Msquare]
+      check [type=exec, command="false"]
+      start -> check
+      start -> exit [condition="outcome=fail"]
+    }
+  "#;
+  let ast = streamweave_attractor::dot_parser::parse_dot(dot).expect("parse");
+  let r = streamweave_attractor::run_compiled_graph(
+    &ast,
+    streamweave_attractor::RunOptions {
+      run_dir: None,
+      resume_state: None,
+      resume_already_completed: false,
+      agent_cmd: None,
+      stage_dir: None,
+      execution_log_path: None,
+      handlers: Default::default(),
+      lints: Default::default(),
+    },
+  )
+  .await
+  .expect("run_compiled_graph");
+  assert!(format!("{:?}", r.last_outcome.status) == "Error");
+  assert!(!r.completed_nodes.contains(&"exit".to_string()));
+}
+
+#[tokio::test]
+async fn integration_lib_negated_outcome_condition_routes_failure() {
+  // `outcome!=success` is evaluated at runtime (not treated as an unconditional success edge).
+  let dot = r#"
+    digraph G {
+      graph [goal="negated condition"]
+      start [shape=Mdiamond]
+      exit [shape=Msquare]
+      check [type=exec, command="false"]
+      report [type=exec, command="true"]
+      start -> check
+      check -> exit [condition="outcome=success"]
+      check -> report [condition="outcome!=success"]
+      report -> exit
+    }
+  "#;
+  let ast = streamweave_attractor::dot_parser::parse_dot(dot).expect("parse");
+  let r = streamweave_attractor::run_compiled_graph(
+    &ast,
+    streamweave_attractor::RunOptions {
+      run_dir: None,
+      resume_state: None,
+      resume_already_completed: false,
+      agent_cmd: None,
+      stage_dir: None,
+      execution_log_path: None,
+      handlers: Default::default(),
+      lints: Default::default(),
+    },
+  )
+  .await
+  .expect("run_compiled_graph");
+  assert_eq!(r.completed_nodes, ["start", "check", "report", "exit"]);
+}
+
+#[tokio::test]
+async fn integration_lib_agent_preferred_label_picks_branch() {
+  // The agent reports preferred_label in outcome.json; the compiled graph follows that edge.
+  let dir = tempfile::tempdir().expect("tempdir");
+  let script = dir.path().join("agent.sh");
+  std::fs::write(
+    &script,
+    format!(
+      "cat > /dev/null; echo '{{\"preferred_label\":\"Revise\"}}' > {}/outcome.json\n",
+      dir.path().display()
+    ),
+  )
+  .expect("write agent script");
+  let dot = r#"
+    digraph G {
+      graph [goal="label routing"]
+      start [shape=Mdiamond]
+      exit [shape=Msquare]
+      review [label="Review", prompt="approve or revise"]
+      ship [type=exec, command="true"]
+      rework [type=exec, command="true"]
+      start -> review
+      review -> ship [label="[A] Approve"]
+      review -> rework [label="[R] Revise"]
+      ship -> exit
+      rework -> exit
+    }
+  "#;
+  let ast = streamweave_attractor::dot_parser::parse_dot(dot).expect("parse");
+  let r = streamweave_attractor::run_compiled_graph(
+    &ast,
+    streamweave_attractor::RunOptions {
+      run_dir: None,
+      resume_state: None,
+      resume_already_completed: false,
+      agent_cmd: Some(format!("sh {}", script.display())),
+      stage_dir: Some(dir.path().to_path_buf()),
+      execution_log_path: None,
+      handlers: Default::default(),
+      lints: Default::default(),
+    },
+  )
+  .await
+  .expect("run_compiled_graph");
+  assert_eq!(r.completed_nodes, ["start", "review", "rework", "exit"]);
+}
+
+/// Custom handler for `type="notify"` nodes: records the message in the context.
+struct Notify;
+
+#[async_trait::async_trait]
+impl streamweave_attractor::Handler for Notify {
+  async fn execute(
+    &self,
+    node: &streamweave_attractor::AttractorNode,
+    _context: &streamweave_attractor::types::RunContext,
+    _services: &streamweave_attractor::HandlerServices,
+  ) -> streamweave_attractor::NodeOutcome {
+    let mut outcome = streamweave_attractor::NodeOutcome::success("notified");
+    outcome.context_updates.insert(
+      "notified".to_string(),
+      node.prompt.clone().unwrap_or_default(),
+    );
+    outcome
+  }
+}
+
+#[tokio::test]
+async fn integration_lib_custom_handler_runs_registered_type() {
+  // A node type unknown to the crate runs once its handler is registered, and is rejected
+  // at validation otherwise.
+  let dot = r#"
+    digraph G {
+      graph [goal="custom handler"]
+      start [shape=Mdiamond]
+      exit [shape=Msquare]
+      ping [type="notify", prompt="build is green"]
+      start -> ping -> exit
+    }
+  "#;
+  let ast = streamweave_attractor::dot_parser::parse_dot(dot).expect("parse");
+  let options = |handlers| streamweave_attractor::RunOptions {
+    run_dir: None,
+    resume_state: None,
+    resume_already_completed: false,
+    agent_cmd: None,
+    stage_dir: None,
+    execution_log_path: None,
+    handlers,
+    lints: Default::default(),
+  };
+
+  let err = streamweave_attractor::run_compiled_graph(&ast, options(Default::default()))
+    .await
+    .err()
+    .expect("unregistered type is rejected");
+  assert!(err.contains("'notify'"), "{}", err);
+
+  let mut handlers = streamweave_attractor::HandlerRegistry::default();
+  handlers.register("notify", Notify);
+  let r = streamweave_attractor::run_compiled_graph(&ast, options(handlers))
+    .await
+    .expect("run_compiled_graph");
+  assert_eq!(r.completed_nodes, ["start", "ping", "exit"]);
+  assert_eq!(
+    r.context.get("notified").map(String::as_str),
+    Some("build is green")
+  );
+}
+
+#[tokio::test]
+async fn integration_lib_human_gate_routes_on_answers() {
+  // The gate asks twice: Revise sends the run back through rework, Approve ships.
+  let dot = r#"
+    digraph G {
+      graph [goal="human gate"]
+      start [shape=Mdiamond]
+      exit [shape=Msquare]
+      review [shape=hexagon, label="Ship it?"]
+      ship [type=exec, command="true"]
+      rework [type=exec, command="true"]
+      start -> review
+      review -> ship [label="[A] Approve"]
+      review -> rework [label="[R] Revise"]
+      rework -> review
+      ship -> exit
+    }
+  "#;
+  let ast = streamweave_attractor::dot_parser::parse_dot(dot).expect("parse");
+  let interviewer = std::sync::Arc::new(
+    streamweave_attractor::interviewer::QueueInterviewer::selecting(["R", "approve"]),
+  );
+  let mut handlers = streamweave_attractor::HandlerRegistry::default();
+  handlers.register(
+    "wait.human",
+    streamweave_attractor::handler::WaitHumanHandler::new(interviewer.clone()),
+  );
+  let r = streamweave_attractor::run_compiled_graph(
+    &ast,
+    streamweave_attractor::RunOptions {
+      run_dir: None,
+      resume_state: None,
+      resume_already_completed: false,
+      agent_cmd: None,
+      stage_dir: None,
+      execution_log_path: None,
+      handlers,
+      lints: Default::default(),
+    },
+  )
+  .await
+  .expect("run_compiled_graph");
+  assert_eq!(
+    r.completed_nodes,
+    ["start", "review", "rework", "review", "ship", "exit"]
+  );
+  assert_eq!(interviewer.remaining(), 0);
+  assert_eq!(
+    r.context.get("human.gate.label").map(String::as_str),
+    Some("[A] Approve")
+  );
+}
+
+#[tokio::test]
+async fn integration_lib_parallel_branches_join_at_fan_in() {
+  // Both branches run on their own copy of the context; their results come back under
+  // branch-qualified keys and the run continues at the fan-in. Branch steps are logged too.
+  let dir = tempfile::tempdir().expect("tempdir");
+  let dot = format!(
+    r#"
+    digraph G {{
+      graph [goal="parallel"]
+      start [shape=Mdiamond]
+      exit [shape=Msquare]
+      checks [shape=component]
+      lint [type=exec, command="touch {dir}/lint"]
+      test [type=exec, command="touch {dir}/test"]
+      join [shape=tripleoctagon]
+      report [type=exec, command="test -f {dir}/lint && test -f {dir}/test"]
+      start -> checks
+      checks -> lint
+      checks -> test
+      lint -> join
+      test -> join
+      join -> report
+      report -> exit
+    }}
+  "#,
+    dir = dir.path().display()
+  );
+  let ast = streamweave_attractor::dot_parser::parse_dot(&dot).expect("parse");
+  let log_path = dir.path().join("execution.log.json");
+  let r = streamweave_attractor::run_compiled_graph(
+    &ast,
+    streamweave_attractor::RunOptions {
+      run_dir: None,
+      resume_state: None,
+      resume_already_completed: false,
+      agent_cmd: None,
+      stage_dir: None,
+      execution_log_path: Some(log_path.clone()),
+      handlers: Default::default(),
+      lints: Default::default(),
+    },
+  )
+  .await
+  .expect("run_compiled_graph");
+  assert_eq!(
+    r.completed_nodes,
+    ["start", "checks", "join", "report", "exit"]
+  );
+  assert!(format!("{:?}", r.last_outcome.status) == "Success");
+  assert_eq!(
+    r.context.get("parallel.branches").map(String::as_str),
+    Some("lint,test")
+  );
+  assert_eq!(
+    r.context.get("parallel.test.outcome").map(String::as_str),
+    Some("success")
+  );
+
+  let log = streamweave_attractor::execution_log_io::load_execution_log(&log_path).expect("log");
+  let mut branch_steps: Vec<(&str, &str)> = log
+    .steps
+    .iter()
+    .filter_map(|s| Some((s.branch.as_deref()?, s.node_id.as_str())))
+    .collect();
+  branch_steps.sort();
+  assert_eq!(branch_steps, [("lint", "lint"), ("test", "test")]);
+  let run_steps: Vec<&str> = log
+    .steps
+    .iter()
+    .filter(|s| s.branch.is_none())
+    .map(|s| s.node_id.as_str())
+    .collect();
+  assert_eq!(run_steps, ["start", "checks", "join", "report", "exit"]);
+  let checks = log.steps.iter().find(|s| s.node_id == "checks").unwrap();
+  assert_eq!(checks.next_node_id.as_deref(), Some("join"));
+  assert_eq!(log.completed_nodes, r.completed_nodes);
+}
+
+#[tokio::test]
+async fn integration_lib_conditional_node_routes_on_context() {
+  // The diamond does nothing itself; its edge conditions pick the branch from the context.
+  let dir = tempfile::tempdir().expect("tempdir");
+  let dot = format!(
+    r#"
+    digraph G {{
+      graph [goal="ship"]
+      start [shape=Mdiamond]
+      exit [shape=Msquare]
+      check [shape=diamond]
+      deploy [type=exec, command="touch {dir}/deployed"]
+      skip [type=exec, command="touch {dir}/skipped"]
+      start -> check
+      check -> deploy [condition="goal=ship"]
+      check -> skip [condition="goal!=ship"]
+      deploy -> exit
+      skip -> exit
+    }}
+  "#,
+    dir = dir.path().display()
+  );
+  let ast = streamweave_attractor::dot_parser::parse_dot(&dot).expect("parse");
+  streamweave_attractor::validate_or_raise(&ast).expect("valid");
+  let r = streamweave_attractor::run_compiled_graph(
+    &ast,
+    streamweave_attractor::RunOptions {
+      run_dir: None,
+      resume_state: None,
+      resume_already_completed: false,
+      agent_cmd: None,
+      stage_dir: None,
+      execution_log_path: None,
+      handlers: Default::default(),
+      lints: Default::default(),
+    },
+  )
+  .await
+  .expect("run_compiled_graph");
+  assert_eq!(r.completed_nodes, ["start", "check", "deploy", "exit"]);
+  assert!(dir.path().join("deployed").exists());
+  assert!(!dir.path().join("skipped").exists());
+}
+
+#[tokio::test]
+async fn integration_lib_tool_output_routes_the_run() {
+  // The tool gets its arguments as JSON on stdin and answers with JSON on stdout; the answer's
+  // fields land in the context, where the diamond routes on them.
+  let dir = tempfile::tempdir().expect("tempdir");
+  std::fs::write(
+    dir.path().join("triage.sh"),
+    r#"grep -q '"title":"crash in ship"' && echo '{"severity": "high", "score": 9}'"#,
+  )
+  .expect("write tool");
+  let dot = format!(
+    r#"
+    digraph G {{
+      graph [goal="ship"]
+      start [shape=Mdiamond]
+      exit [shape=Msquare]
+      triage [shape=parallelogram, tool_command="sh {dir}/triage.sh", tool_args="{{\"title\": \"crash in $goal\"}}", produces="severity, score"]
+      route [shape=diamond]
+      page [type=exec, command="touch {dir}/paged"]
+      start -> triage -> route
+      route -> page [condition="severity=high"]
+      route -> exit
+      page -> exit
+    }}
+  "#,
+    dir = dir.path().display()
+  );
+  let ast = streamweave_attractor::dot_parser::parse_dot(&dot).expect("parse");
+  streamweave_attractor::validate_or_raise(&ast).expect("valid");
+  let r = streamweave_attractor::run_compiled_graph(
+    &ast,
+    streamweave_attractor::RunOptions {
+      run_dir: None,
+      resume_state: None,
+      resume_already_completed: false,
+      agent_cmd: None,
+      stage_dir: None,
+      execution_log_path: None,
+      handlers: Default::default(),
+      lints: Default::default(),
+    },
+  )
+  .await
+  .expect("run_compiled_graph");
+  assert_eq!(
+    r.completed_nodes,
+    ["start", "triage", "route", "page", "exit"]
+  );
+  assert_eq!(r.context.get("score").map(String::as_str), Some("9"));
+  assert!(dir.path().join("paged").exists());
+}
+
+#[tokio::test]
+async fn integration_lib_codergen_with_execution_log_runs_agent_cmd() {
+  let dir = tempfile::tempdir().expect("tempdir");
+  let marker = dir.path().join("agent_ran");
+  let dot = r#"
+    digraph G {
+      graph [goal="codergen with log"]
+      start [shape=Mdiamond]
+      exit [shape=Msquare]
+      work [label="Work", prompt="do it"]
+      start -> work -> exit
+    }
+  "#;
+  let ast = streamweave_attractor::dot_parser::parse_dot(dot).expect("parse");
+  let r = streamweave_attractor::run_compiled_graph(
+    &ast,
+    streamweave_attractor::RunOptions {
+      run_dir: None,
+      resume_state: None,
+      resume_already_completed: false,
+      agent_cmd: Some(format!("touch {}", marker.display())),
+      stage_dir: Some(dir.path().to_path_buf()),
+      execution_log_path: Some(dir.path().join("execution.log.json")),
+      handlers: Default::default(),
+      lints: Default::default(),
+    },
+  )
+  .await
+  .expect("run_compiled_graph");
+  assert!(format!("{:?}", r.last_outcome.status) == "Success");
+  assert!(marker.exists(), "agent command should have run");
+}
+
 /// When execution_log_path is set, the runner loads the log at start. First run executes
 /// and writes the log; second run with same path loads the log, sees finished_at, and
 /// returns already_completed without re-running.
@@ -259,6 +963,8 @@ async fn integration_lib_two_runs_without_resume_both_run_fully() {
       agent_cmd: None,
       stage_dir: None,
       execution_log_path: Some(log_path.clone()),
+      handlers: Default::default(),
+      lints: Default::default(),
     },
   )
   .await
@@ -287,6 +993,8 @@ async fn integration_lib_two_runs_without_resume_both_run_fully() {
       agent_cmd: None,
       stage_dir: None,
       execution_log_path: Some(log_path.clone()),
+      handlers: Default::default(),
+      lints: Default::default(),
     },
   )
   .await
@@ -68,6 +68,171 @@ fn run_dot_succeeds_with_minimal_start_exit_dot() {
   assert!(stdout.contains("Success") || stdout.contains("completed"));
 }
 
+#[test]
+fn run_dot_succeeds_with_minimal_yaml_pipeline() {
+  let dir = tempfile::tempdir().expect("temp dir");
+  let path = dir.path().join("minimal.yaml");
+  std::fs::write(
+    &path,
+    "attrs: {goal: test}\nnodes:\n  - {id: start, attrs: {shape: Mdiamond}}\n  - {id: exit, attrs: {shape: Msquare}}\nedges:\n  - {from: start, to: exit}\n",
+  )
+  .expect("write yaml");
+
+  let out = run_run_dot(&[path.to_str().expect("path")]);
+  assert!(
+    out.status.success(),
+    "stderr: {} stdout: {}",
+    String::from_utf8_lossy(&out.stderr),
+    String::from_utf8_lossy(&out.stdout)
+  );
+  assert!(String::from_utf8_lossy(&out.stdout).contains("Pipeline completed"));
+}
+
+#[test]
+fn run_dot_fmt_check_then_rewrite() {
+  let dir = tempfile::tempdir().expect("temp dir");
+  let path = dir.path().join("messy.dot");
+  std::fs::write(
+    &path,
+    "digraph G { // done\n exit [shape=Msquare]; start [shape=Mdiamond]; start -> exit }",
+  )
+  .expect("write dot");
+  let path_str = path.to_str().expect("path");
+
+  let out = run_run_dot(&["fmt", "--check", path_str]);
+  assert!(!out.status.success());
+  assert!(String::from_utf8_lossy(&out.stdout).contains("would reformat"));
+
+  let out = run_run_dot(&["fmt", path_str]);
+  assert!(
+    out.status.success(),
+    "stderr: {}",
+    String::from_utf8_lossy(&out.stderr)
+  );
+  assert_eq!(
+    std::fs::read_to_string(&path).expect("read dot"),
+    "digraph G {\n  // done\n  exit [shape=Msquare]\n  start [shape=Mdiamond]\n  start -> exit\n}\n"
+  );
+
+  let out = run_run_dot(&["fmt", "--check", path_str]);
+  assert!(out.status.success());
+}
+
+#[test]
+fn run_dot_fmt_leaves_files_it_cannot_format() {
+  let dir = tempfile::tempdir().expect("temp dir");
+  let path = dir.path().join("commented.dot");
+  let source = "digraph G { start [shape=Mdiamond] start /* why */ -> exit }";
+  std::fs::write(&path, source).expect("write dot");
+  let path_str = path.to_str().expect("path");
+
+  for args in [&["fmt", "--check", path_str][..], &["fmt", path_str]] {
+    let out = run_run_dot(args);
+    assert!(!out.status.success());
+    assert!(
+      String::from_utf8_lossy(&out.stderr).contains("comment inside a statement"),
+      "stderr: {}",
+      String::from_utf8_lossy(&out.stderr)
+    );
+  }
+  assert_eq!(std::fs::read_to_string(&path).expect("read dot"), source);
+}
+
+#[test]
+fn run_dot_validate_reports_and_exits_by_severity() {
+  let dir = tempfile::tempdir().expect("temp dir");
+  let warn_path = dir.path().join("warn.dot");
+  std::fs::write(
+    &warn_path,
+    "digraph G { start [shape=Mdiamond] exit [shape=Msquare] work start -> work -> exit }",
+  )
+  .expect("write dot");
+  let err_path = dir.path().join("err.dot");
+  std::fs::write(&err_path, "digraph G { start [shape=Mdiamond] }").expect("write dot");
+  let warn_str = warn_path.to_str().expect("path");
+  let err_str = err_path.to_str().expect("path");
+
+  let out = run_run_dot(&["validate", warn_str]);
+  let stdout = String::from_utf8_lossy(&out.stdout);
+  assert!(out.status.success(), "stdout: {}", stdout);
+  assert!(
+    stdout.contains("warning[prompt_on_llm_nodes]"),
+    "stdout: {}",
+    stdout
+  );
+  assert!(
+    stdout.contains("0 error(s), 1 warning(s)"),
+    "stdout: {}",
+    stdout
+  );
+
+  let out = run_run_dot(&["validate", "--deny-warnings", warn_str]);
+  assert!(!out.status.success());
+  let out = run_run_dot(&[
+    "validate",
+    "--deny-warnings",
+    "--allow",
+    "prompt_on_llm_nodes",
+    warn_str,
+  ]);
+  assert!(out.status.success());
+
+  let out = run_run_dot(&["validate", "--format", "json", warn_str, err_str]);
+  assert!(!out.status.success());
+  let json: serde_json::Value = serde_json::from_slice(&out.stdout).expect("json output");
+  let rules: Vec<(&str, &str)> = json
+    .as_array()
+    .expect("array")
+    .iter()
+    .map(|d| (d["file"].as_str().unwrap(), d["rule"].as_str().unwrap()))
+    .collect();
+  assert_eq!(
+    rules,
+    [
+      (warn_str, "prompt_on_llm_nodes"),
+      (err_str, "terminal_node"),
+      (err_str, "no_dead_ends")
+    ]
+  );
+}
+
+#[test]
+fn run_dot_auto_approve_takes_human_gate_default() {
+  let dir = tempfile::tempdir().expect("temp dir");
+  let path = dir.path().join("gate.dot");
+  std::fs::write(
+    &path,
+    format!(
+      r#"digraph G {{
+  graph [goal="test"]
+  start [shape=Mdiamond]
+  exit [shape=Msquare]
+  review [shape=hexagon, label="Ship it?", "human.default_choice"="revise"]
+  ship [type=exec, command="touch {dir}/shipped"]
+  rework [type=exec, command="touch {dir}/reworked"]
+  start -> review
+  review -> ship [label="[A] Approve"]
+  review -> rework [label="[R] Revise"]
+  ship -> exit
+  rework -> exit
+}}"#,
+      dir = dir.path().display()
+    ),
+  )
+  .expect("write dot");
+
+  let path_str = path.to_str().expect("path");
+  let out = run_run_dot(&["--auto-approve", path_str]);
+  assert!(
+    out.status.success(),
+    "stderr: {} stdout: {}",
+    String::from_utf8_lossy(&out.stderr),
+    String::from_utf8_lossy(&out.stdout)
+  );
+  assert!(dir.path().join("reworked").exists());
+  assert!(!dir.path().join("shipped").exists());
+}
+
 #[test]
 fn run_dot_execution_log_cli_writes_log_file() {
   let dir = tempfile::tempdir().expect("temp dir");
@@ -306,6 +471,8 @@ async fn execution_log_path_writes_execution_log_json() {
       agent_cmd: None,
       stage_dir: None,
       execution_log_path: Some(log_path.clone()),
+      handlers: Default::default(),
+      lints: Default::default(),
     },
   )
   .await
@@ -351,6 +518,8 @@ async fn pre_push_dot_via_run_compiled_graph() {
       agent_cmd: None,
       stage_dir: None,
       execution_log_path: None,
+      handlers: Default::default(),
+      lints: Default::default(),
     },
   )
   .await
@@ -389,6 +558,8 @@ async fn test_out_error_dot_error_path_then_fix_to_exit() {
       agent_cmd: None,
       stage_dir: None,
       execution_log_path: None,
+      handlers: Default::default(),
+      lints: Default::default(),
     },
   )
   .await
@@ -431,6 +602,8 @@ async fn run_dir_writes_execution_log() {
       agent_cmd: None,
       stage_dir: None,
       execution_log_path: Some(log_path.clone()),
+      handlers: Default::default(),
+      lints: Default::default(),
     },
   )
   .await
@@ -485,6 +658,8 @@ async fn resume_from_execution_log_completes() {
       agent_cmd: None,
       stage_dir: None,
       execution_log_path: Some(log_path.clone()),
+      handlers: Default::default(),
+      lints: Default::default(),
     },
   )
   .await
@@ -506,6 +681,8 @@ async fn resume_from_execution_log_completes() {
       agent_cmd: None,
       stage_dir: None,
       execution_log_path: Some(log_path),
+      handlers: Default::default(),
+      lints: Default::default(),
     },
   )
   .await
@@ -580,6 +757,8 @@ async fn resume_from_partial_log() {
       agent_cmd: None,
       stage_dir: None,
       execution_log_path: Some(log_path),
+      handlers: Default::default(),
+      lints: Default::default(),
     },
   )
   .await
@@ -618,6 +797,8 @@ async fn resume_from_completed_log_returns_already_completed() {
       agent_cmd: None,
       stage_dir: None,
       execution_log_path: Some(log_path.clone()),
+      handlers: Default::default(),
+      lints: Default::default(),
     },
   )
   .await
@@ -642,6 +823,8 @@ async fn resume_from_completed_log_returns_already_completed() {
       agent_cmd: None,
       stage_dir: None,
       execution_log_path: Some(log_path),
+      handlers: Default::default(),
+      lints: Default::default(),
     },
   )
   .await
@@ -665,9 +848,9 @@ async fn resume_from_completed_log_returns_already_completed() {
 /// Timeout for "graph must complete" tests. Short enough to fail fast when we hang.
 const GRAPH_COMPLETION_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(3);
 
-/// CodergenNode error path: no agent_cmd → node sends on error port.
-/// Downstream (merge → exit) must see stream close; requires CodergenNode to drop err_tx after send.
-/// CULPRIT when this times out: CodergenNode (src/nodes/codergen_node.rs) does not drop err_tx after send on error path. See history/CULPRIT-one-shot-sender-not-dropped.md.
+/// HandlerNode (codergen) error path: no agent_cmd → node sends on error port.
+/// Downstream (merge → exit) must see stream close; requires HandlerNode (codergen) to drop err_tx after send.
+/// CULPRIT when this times out: HandlerNode (src/nodes/handler_node.rs) does not drop err_tx after send on error path. See history/CULPRIT-one-shot-sender-not-dropped.md.
 #[tokio::test]
 async fn tdd_codergen_error_path_graph_completes_within_timeout() {
   let dot = r#"
@@ -694,6 +877,8 @@ async fn tdd_codergen_error_path_graph_completes_within_timeout() {
         agent_cmd: None,
         stage_dir: None,
         execution_log_path: None,
+        handlers: Default::default(),
+        lints: Default::default(),
       },
     ),
   )
@@ -707,13 +892,13 @@ async fn tdd_codergen_error_path_graph_completes_within_timeout() {
   let run_result = result.unwrap().expect("run_compiled_graph");
   assert!(
     run_result.completed_nodes.contains(&"fix".to_string()),
-    "fix (CodergenNode) should have run and completed; completed: {:?}",
+    "fix (codergen HandlerNode) should have run and completed; completed: {:?}",
     run_result.completed_nodes
   );
 }
 
-/// CodergenNode success path: agent_cmd that succeeds → node sends on out port.
-/// Downstream must see stream close; requires CodergenNode to drop out_tx after send.
+/// HandlerNode (codergen) success path: agent_cmd that succeeds → node sends on out port.
+/// Downstream must see stream close; requires HandlerNode (codergen) to drop out_tx after send.
 /// Uses a temp stage_dir so no leftover outcome.json (e.g. "outcome":"fail") is read.
 #[tokio::test]
 async fn tdd_codergen_success_path_graph_completes_within_timeout() {
@@ -740,6 +925,8 @@ async fn tdd_codergen_success_path_graph_completes_within_timeout() {
         agent_cmd: Some("true".to_string()),
         stage_dir: Some(stage_dir.path().to_path_buf()),
         execution_log_path: None,
+        handlers: Default::default(),
+        lints: Default::default(),
       },
     ),
   )
@@ -753,12 +940,12 @@ async fn tdd_codergen_success_path_graph_completes_within_timeout() {
   let run_result = result.unwrap().expect("run_compiled_graph");
   assert!(
     run_result.completed_nodes.contains(&"fix".to_string()),
-    "fix (CodergenNode) should have run and completed; completed: {:?}",
+    "fix (codergen HandlerNode) should have run and completed; completed: {:?}",
     run_result.completed_nodes
   );
 }
 
-/// ExecNode error path: command fails → node sends on error port.
+/// HandlerNode (exec) error path: command fails → node sends on error port.
 /// Same assumption: sender for the used port must be dropped so graph completes.
 #[tokio::test]
 async fn tdd_exec_error_path_graph_completes_within_timeout() {
@@ -785,6 +972,8 @@ async fn tdd_exec_error_path_graph_completes_within_timeout() {
         agent_cmd: None,
         stage_dir: None,
         execution_log_path: None,
+        handlers: Default::default(),
+        lints: Default::default(),
       },
     ),
   )
@@ -792,7 +981,7 @@ async fn tdd_exec_error_path_graph_completes_within_timeout() {
   assert!(
     result.is_ok(),
     "graph must complete within {:?} (exec error path). \
-     If this times out, ExecNode did not drop the error port sender after send.",
+     If this times out, HandlerNode (exec) did not drop the error port sender after send.",
     GRAPH_COMPLETION_TIMEOUT
   );
 }
@@ -829,6 +1018,8 @@ async fn tdd_cyclic_exec_error_path_graph_completes_within_timeout() {
         agent_cmd: None,
         stage_dir: None,
         execution_log_path: None,
+        handlers: Default::default(),
+        lints: Default::default(),
       },
     ),
   )
@@ -836,12 +1027,12 @@ async fn tdd_cyclic_exec_error_path_graph_completes_within_timeout() {
   assert!(
     result.is_ok(),
     "cyclic graph must complete within {:?} (exec error path). \
-     If this times out, ExecNode did not break after error send, causing MergeNode deadlock.",
+     If this times out, HandlerNode (exec) did not break after error send, causing MergeNode deadlock.",
     GRAPH_COMPLETION_TIMEOUT
   );
 }
 
-/// ExecNode success path: command succeeds → node sends on out port.
+/// HandlerNode (exec) success path: command succeeds → node sends on out port.
 #[tokio::test]
 async fn tdd_exec_success_path_graph_completes_within_timeout() {
   let dot = r#"
@@ -866,6 +1057,8 @@ async fn tdd_exec_success_path_graph_completes_within_timeout() {
         agent_cmd: None,
         stage_dir: None,
         execution_log_path: None,
+        handlers: Default::default(),
+        lints: Default::default(),
       },
     ),
   )
@@ -873,12 +1066,12 @@ async fn tdd_exec_success_path_graph_completes_within_timeout() {
   assert!(
     result.is_ok(),
     "graph must complete within {:?} (exec success path). \
-     If this times out, ExecNode did not drop the out port sender after send.",
+     If this times out, HandlerNode (exec) did not drop the out port sender after send.",
     GRAPH_COMPLETION_TIMEOUT
   );
 }
 
-/// Cyclic graph with Merge: start and loop_back feed merge → check (CodergenNode) → middle → loop_back.
+/// Cyclic graph with Merge: start and loop_back feed merge → check (HandlerNode (codergen)) → middle → loop_back.
 /// When check fails (no agent_cmd), it sends on error port and must break to avoid deadlock.
 #[tokio::test]
 async fn tdd_cyclic_codergen_error_path_graph_completes_within_timeout() {
@@ -909,13 +1102,15 @@ async fn tdd_cyclic_codergen_error_path_graph_completes_within_timeout() {
         agent_cmd: None,
         stage_dir: None,
         execution_log_path: None,
+        handlers: Default::default(),
+        lints: Default::default(),
       },
     ),
   )
   .await;
   assert!(
     result.is_ok(),
-    "cyclic graph must complete within {:?} (codergen error path).      If this times out, CodergenNode did not break after error send.",
+    "cyclic graph must complete within {:?} (codergen error path).      If this times out, HandlerNode (codergen) did not break after error send.",
     GRAPH_COMPLETION_TIMEOUT
   );
 }